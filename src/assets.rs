@@ -0,0 +1,445 @@
+use crate::config::Config;
+use crate::error::{Error, Result};
+use crate::folder_manager::FolderManager;
+use crate::link_rewriter::relative_link;
+use crate::rate_limiter::{check_rate_limited, retry_with_backoff};
+use crate::types::RetryConfig;
+use regex::{Captures, Regex};
+use reqwest::Client;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+use tokio::sync::Mutex;
+use url::Url;
+
+// Markdown 图片 ![alt](url "title") 或普通链接 [text](url)，一次匹配，同一引用只处理一次；
+// 普通链接只有指向媒体文件时才当作资源处理
+static MARKDOWN_REFERENCE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(!\[[^\]]*\]\(\s*<?|\]\(\s*<?)([^)\s>]+)").expect("valid regex"));
+
+// 内嵌 HTML 的媒体元素
+static HTML_MEDIA: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(<(?:img|video|audio|source)\b[^>]*?\s(?:src|poster)\s*=\s*["'])([^"']+)"#)
+        .expect("valid regex")
+});
+
+const MEDIA_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "svg", "webp", "avif", "bmp", "ico", "mp4", "webm", "ogv", "mov",
+    "mp3", "ogg", "oga", "wav", "flac", "m4a",
+];
+
+#[derive(Debug, Clone)]
+pub struct AssetConfig {
+    pub enabled: bool,
+    pub max_size_mb: u64,
+    pub allowed_types: Vec<String>,
+}
+
+impl Default for AssetConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_size_mb: 10,
+            allowed_types: default_allowed_types(),
+        }
+    }
+}
+
+pub fn default_allowed_types() -> Vec<String> {
+    ["image/*", "video/*", "audio/*"]
+        .iter()
+        .map(|t| t.to_string())
+        .collect()
+}
+
+// 单个页面已下载到本地的资源：远程 URL -> 本地文件
+#[derive(Debug, Clone, Default)]
+pub struct LocalAssets {
+    page_url: String,
+    files: HashMap<String, PathBuf>,
+}
+
+impl LocalAssets {
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    // 把资源引用改写为相对 file_path 所在目录的本地路径
+    pub fn rewrite(&self, content: &str, file_path: &Path) -> String {
+        if self.files.is_empty() {
+            return content.to_string();
+        }
+        let Some(from_dir) = file_path.parent() else {
+            return content.to_string();
+        };
+
+        map_references(content, |reference| {
+            let resolved = resolve_reference(&self.page_url, reference)?;
+            self.files
+                .get(&resolved)
+                .map(|local| relative_link(from_dir, local))
+        })
+    }
+}
+
+// 下载页面引用的图片等资源，按内容哈希命名并去重
+#[derive(Clone)]
+pub struct AssetDownloader {
+    client: Client,
+    config: AssetConfig,
+    user_agent: String,
+    timeout: u64,
+    retry_config: RetryConfig,
+    folder_manager: FolderManager,
+    // 本次运行中已经处理过的资源 URL；None 表示永久失败或被过滤，不再重试。
+    // 超时、限流等可重试的失败不记录，之后的页面引用同一资源时会再次下载
+    downloaded: Arc<Mutex<HashMap<String, Option<PathBuf>>>>,
+}
+
+impl AssetDownloader {
+    pub fn new(config: &Config, folder_manager: FolderManager) -> Self {
+        Self {
+            client: Client::new(),
+            config: config.assets.clone(),
+            user_agent: config.user_agent.clone(),
+            timeout: config.timeout,
+            retry_config: config.retry.assets.clone(),
+            folder_manager,
+            downloaded: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub async fn download_assets(&self, page_url: &str, markdown: &str) -> LocalAssets {
+        let mut references = Vec::new();
+        map_references(markdown, |reference| {
+            if let Some(resolved) = resolve_reference(page_url, reference)
+                && !references.contains(&resolved)
+            {
+                references.push(resolved);
+            }
+            None
+        });
+
+        let mut files = HashMap::new();
+        if references.is_empty() {
+            return LocalAssets {
+                page_url: page_url.to_string(),
+                files,
+            };
+        }
+
+        let assets_folder = match self.folder_manager.get_assets_folder(page_url) {
+            Ok(folder) => folder,
+            Err(e) => {
+                eprintln!("无法确定资源目录: {}", e);
+                return LocalAssets {
+                    page_url: page_url.to_string(),
+                    files,
+                };
+            }
+        };
+
+        for asset_url in references {
+            let cached = self.downloaded.lock().await.get(&asset_url).cloned();
+            let local = match cached {
+                Some(local) => local,
+                None => {
+                    let download = retry_with_backoff(
+                        || self.download(&asset_url, &assets_folder),
+                        &self.retry_config,
+                        None,
+                    );
+                    match download.await {
+                        Ok(path) => {
+                            self.downloaded
+                                .lock()
+                                .await
+                                .insert(asset_url.clone(), Some(path.clone()));
+                            Some(path)
+                        }
+                        Err(e) => {
+                            eprintln!("跳过资源: {} - {}", asset_url, e);
+                            if !e.is_retryable() {
+                                self.downloaded.lock().await.insert(asset_url.clone(), None);
+                            }
+                            None
+                        }
+                    }
+                }
+            };
+
+            if let Some(local) = local {
+                files.insert(asset_url, local);
+            }
+        }
+
+        LocalAssets {
+            page_url: page_url.to_string(),
+            files,
+        }
+    }
+
+    async fn download(&self, asset_url: &str, assets_folder: &Path) -> Result<PathBuf> {
+        let max_size = self.config.max_size_mb * 1024 * 1024;
+
+        let mut response = self
+            .client
+            .get(asset_url)
+            .header("User-Agent", &self.user_agent)
+            .timeout(Duration::from_secs(self.timeout))
+            .send()
+            .await
+            .map_err(|e| Error::network(asset_url, e))?;
+
+        check_rate_limited(&response, asset_url)?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(Error::http_status(asset_url, status, ""));
+        }
+
+        let mime = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.split(';').next().unwrap_or("").trim().to_lowercase())
+            .unwrap_or_default();
+
+        if !self.is_allowed_type(&mime) {
+            let mime = if mime.is_empty() { "unknown" } else { &mime };
+            return Err(Error::rejected(
+                asset_url,
+                format!("MIME type not allowed: {}", mime),
+            ));
+        }
+
+        if response
+            .content_length()
+            .is_some_and(|length| length > max_size)
+        {
+            return Err(Error::rejected(
+                asset_url,
+                format!("exceeds size limit of {} MB", self.config.max_size_mb),
+            ));
+        }
+
+        // 服务器不一定返回 Content-Length，边下载边检查大小
+        let mut bytes = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            bytes.extend_from_slice(&chunk);
+            if bytes.len() as u64 > max_size {
+                return Err(Error::rejected(
+                    asset_url,
+                    format!("exceeds size limit of {} MB", self.config.max_size_mb),
+                ));
+            }
+        }
+
+        let hash = format!("{:x}", Sha256::digest(&bytes));
+        let filename = format!("{}.{}", &hash[..16], Self::extension_for(&mime, asset_url));
+
+        self.folder_manager.ensure_folder_exists(assets_folder)?;
+        let path = assets_folder.join(filename);
+        // 内容相同的资源只保存一份
+        if !path.exists() {
+            fs::write(&path, &bytes).map_err(|e| Error::filesystem(&path, e))?;
+            println!("保存资源: {}", path.display());
+        }
+
+        Ok(path)
+    }
+
+    fn is_allowed_type(&self, mime: &str) -> bool {
+        if mime.is_empty() {
+            return false;
+        }
+        self.config.allowed_types.iter().any(|allowed| {
+            let allowed = allowed.trim().to_lowercase();
+            match allowed.strip_suffix("/*") {
+                Some(prefix) => mime.split('/').next() == Some(prefix),
+                None => allowed == "*" || allowed == mime,
+            }
+        })
+    }
+
+    fn extension_for(mime: &str, asset_url: &str) -> String {
+        let from_mime = match mime {
+            "image/jpeg" => Some("jpg"),
+            "image/svg+xml" => Some("svg"),
+            "image/x-icon" | "image/vnd.microsoft.icon" => Some("ico"),
+            "audio/mpeg" => Some("mp3"),
+            "video/quicktime" => Some("mov"),
+            _ => None,
+        };
+        if let Some(ext) = from_mime {
+            return ext.to_string();
+        }
+
+        if let Some(ext) = url_extension(asset_url) {
+            return ext;
+        }
+
+        mime.split('/')
+            .nth(1)
+            .map(|subtype| subtype.trim_start_matches("x-").to_string())
+            .filter(|subtype| {
+                !subtype.is_empty() && subtype.chars().all(|c| c.is_ascii_alphanumeric())
+            })
+            .unwrap_or_else(|| "bin".to_string())
+    }
+}
+
+// 对内容中的每个资源引用调用 f，f 返回 Some 时用返回值替换该引用；代码块内的内容不处理
+fn map_references(content: &str, mut f: impl FnMut(&str) -> Option<String>) -> String {
+    let mut output = String::with_capacity(content.len());
+    let mut fence: Option<String> = None;
+
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+
+        if let Some(marker) = &fence {
+            if trimmed.starts_with(marker.as_str()) {
+                fence = None;
+            }
+            output.push_str(line);
+            continue;
+        }
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fence = Some(trimmed[..3].to_string());
+            output.push_str(line);
+            continue;
+        }
+
+        let mut replace = |caps: &Captures, media_only: bool| -> String {
+            let reference = &caps[2];
+            let is_candidate = !media_only
+                || url_extension(reference)
+                    .is_some_and(|ext| MEDIA_EXTENSIONS.contains(&ext.as_str()));
+            match is_candidate.then(|| f(reference)).flatten() {
+                Some(local) => format!("{}{}", &caps[1], local),
+                None => caps[0].to_string(),
+            }
+        };
+
+        let line = MARKDOWN_REFERENCE.replace_all(line, |caps: &Captures| {
+            let is_image = caps[1].starts_with('!');
+            replace(caps, !is_image)
+        });
+        let line = HTML_MEDIA.replace_all(&line, |caps: &Captures| replace(caps, false));
+        output.push_str(&line);
+    }
+
+    output
+}
+
+// 把引用解析为绝对 URL，跳过 data: 等非 HTTP 资源
+fn resolve_reference(page_url: &str, reference: &str) -> Option<String> {
+    let resolved = Url::parse(page_url).ok()?.join(reference.trim()).ok()?;
+    matches!(resolved.scheme(), "http" | "https").then(|| resolved.to_string())
+}
+
+fn url_extension(url: &str) -> Option<String> {
+    let path = url.split(['?', '#']).next()?;
+    let name = path.rsplit('/').next()?;
+    let (_, ext) = name.rsplit_once('.')?;
+    let ext = ext.to_lowercase();
+    (!ext.is_empty() && ext.len() <= 5 && ext.chars().all(|c| c.is_ascii_alphanumeric()))
+        .then_some(ext)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 页面 https://site.com/docs/intro 已下载的资源保存在 out/site.com/assets 下
+    fn assets() -> LocalAssets {
+        let files = [
+            (
+                "https://site.com/img/logo.png",
+                "out/site.com/assets/aaaa.png",
+            ),
+            (
+                "https://site.com/docs/diagram.svg",
+                "out/site.com/assets/bbbb.svg",
+            ),
+            (
+                "https://cdn.site.com/intro.mp4",
+                "out/site.com/assets/cccc.mp4",
+            ),
+        ];
+        LocalAssets {
+            page_url: "https://site.com/docs/intro".to_string(),
+            files: files
+                .iter()
+                .map(|(url, path)| (url.to_string(), PathBuf::from(path)))
+                .collect(),
+        }
+    }
+
+    fn rewrite(content: &str) -> String {
+        assets().rewrite(content, Path::new("out/site.com/docs/intro.md"))
+    }
+
+    #[test]
+    fn markdown_images_point_to_local_files() {
+        assert_eq!(
+            rewrite("![Logo](/img/logo.png \"title\") and ![d](<diagram.svg>)"),
+            "![Logo](../assets/aaaa.png \"title\") and ![d](<../assets/bbbb.svg>)"
+        );
+        assert_eq!(
+            rewrite("![missing](/img/other.png)"),
+            "![missing](/img/other.png)"
+        );
+    }
+
+    #[test]
+    fn html_media_points_to_local_files() {
+        assert_eq!(
+            rewrite(
+                "<video poster=\"/img/logo.png\"><source src='https://cdn.site.com/intro.mp4'></video>"
+            ),
+            "<video poster=\"../assets/aaaa.png\"><source src='../assets/cccc.mp4'></video>"
+        );
+    }
+
+    #[test]
+    fn links_are_rewritten_only_for_media_files() {
+        assert_eq!(
+            rewrite("[download](/img/logo.png)"),
+            "[download](../assets/aaaa.png)"
+        );
+        assert_eq!(rewrite("[guide](/docs/guide)"), "[guide](/docs/guide)");
+    }
+
+    #[test]
+    fn each_reference_is_visited_once() {
+        let content =
+            "![Logo](/img/logo.png) [![badge](diagram.svg)](https://cdn.site.com/intro.mp4)\n";
+        let mut visited = Vec::new();
+        let output = map_references(content, |reference| {
+            visited.push(reference.to_string());
+            Some(format!("local/{}", visited.len()))
+        });
+
+        assert_eq!(
+            visited,
+            [
+                "/img/logo.png",
+                "diagram.svg",
+                "https://cdn.site.com/intro.mp4"
+            ]
+        );
+        assert_eq!(output, "![Logo](local/1) [![badge](local/2)](local/3)\n");
+    }
+
+    #[test]
+    fn code_blocks_are_left_alone() {
+        let content = "```\n![Logo](/img/logo.png)\n```\n![Logo](/img/logo.png)\n";
+        assert_eq!(
+            rewrite(content),
+            "```\n![Logo](/img/logo.png)\n```\n![Logo](../assets/aaaa.png)\n"
+        );
+    }
+}
@@ -1,166 +1,829 @@
-use clap::{Arg, Command};
 use crate::config::Config;
-use crate::types::TranslationConfig;
-use crate::config_file::ConfigFile;
-use crate::error::Result;
+use crate::config_file::{ConfigFile, LanguageList};
+use crate::converter::ConverterBackend;
+use crate::error::{Error, Result};
+use crate::links_manager::FailureFilter;
+use crate::translation_memory::TranslationMemory;
+use crate::types::{ExistingFilePolicy, OutputLayout, TranslationBackendKind};
+use clap::{Arg, ArgMatches, Command};
+use std::path::PathBuf;
 
-pub fn parse_args() -> Result<Config> {
-    let matches = Command::new("markdown-downloader")
+// 解析后的命令，由 main 执行
+#[derive(Debug, Clone)]
+pub enum CliCommand {
+    // 爬取网站（或只处理指定页面、重新处理失败链接），转换并翻译
+    Run(Config),
+    // 只列出爬取范围内的页面，不转换
+    ListUrls(Config),
+    // 翻译本地目录中的 Markdown 文件
+    TranslateDir(Config),
+    Status {
+        output_dir: String,
+        url: String,
+        json: bool,
+    },
+    ExportLinks {
+        output_dir: String,
+        url: String,
+        path: String,
+    },
+    ExportMemory {
+        output_dir: String,
+        path: String,
+    },
+    PruneMemory {
+        output_dir: String,
+        days: u64,
+    },
+    InitConfig {
+        path: String,
+    },
+}
+
+pub fn parse_args() -> Result<CliCommand> {
+    let matches = build_command().get_matches();
+
+    match matches.subcommand() {
+        Some(("crawl", sub_matches)) => {
+            let config = run_config(sub_matches)?;
+            if sub_matches.get_flag("list-only") {
+                Ok(CliCommand::ListUrls(config))
+            } else {
+                Ok(CliCommand::Run(config))
+            }
+        }
+        Some(("convert", sub_matches)) => {
+            let config = run_config(sub_matches)?;
+            let url = config.url.clone();
+            Ok(CliCommand::Run(config.with_page_urls(vec![url])))
+        }
+        Some(("translate", sub_matches)) => {
+            let source_dir = PathBuf::from(required_value(sub_matches, "dir")?);
+            if !source_dir.is_dir() {
+                return Err(Error::Custom(format!(
+                    "Not a directory: {}",
+                    source_dir.display()
+                )));
+            }
+            let mut config = build_config(sub_matches, String::new())?.with_source_dir(source_dir);
+            config.translation.enabled = true;
+            config.validate()?;
+            Ok(CliCommand::TranslateDir(config))
+        }
+        Some(("retry-failed", sub_matches)) => {
+            let config = run_config(sub_matches)?.with_retry_failed(failure_filter(sub_matches)?);
+            config.validate()?;
+            Ok(CliCommand::Run(config))
+        }
+        Some(("status", sub_matches)) => Ok(CliCommand::Status {
+            output_dir: output_dir(sub_matches)?,
+            url: required_url(sub_matches)?,
+            json: sub_matches.get_flag("json"),
+        }),
+        Some(("export", sub_matches)) => match sub_matches.subcommand() {
+            Some(("links", export_matches)) => Ok(CliCommand::ExportLinks {
+                output_dir: output_dir(export_matches)?,
+                url: required_url(export_matches)?,
+                path: required_value(export_matches, "file")?,
+            }),
+            Some(("memory", export_matches)) => Ok(CliCommand::ExportMemory {
+                output_dir: output_dir(export_matches)?,
+                path: required_value(export_matches, "file")?,
+            }),
+            _ => unreachable!("export requires a subcommand"),
+        },
+        Some(("prune-memory", sub_matches)) => Ok(CliCommand::PruneMemory {
+            output_dir: output_dir(sub_matches)?,
+            days: prune_days(sub_matches)?,
+        }),
+        Some(("config", sub_matches)) => match sub_matches.subcommand() {
+            Some(("init", init_matches)) => Ok(CliCommand::InitConfig {
+                path: required_value(init_matches, "file")?,
+            }),
+            _ => unreachable!("config requires a subcommand"),
+        },
+        // 不带子命令时与 crawl 相同
+        _ => Ok(CliCommand::Run(run_config(&matches)?)),
+    }
+}
+
+fn build_command() -> Command {
+    Command::new("markdown-downloader")
         .version("1.0")
         .about("Download website content as markdown files")
-        .arg(
-            Arg::new("url")
-                .help("Target website URL")
-                .required(false)
-                .index(1),
-        )
+        .args_conflicts_with_subcommands(true)
+        .arg_required_else_help(true)
+        .arg(url_arg("Target website URL").required(false))
+        .args(source_args())
+        .args(crawl_args())
+        .args(scope_args())
+        .args(batch_args())
+        .args(page_output_args())
+        .args(output_args())
+        .args(translation_args())
         .arg(
             Arg::new("output")
                 .short('o')
                 .long("output")
-                .help("Output directory")
-                .default_value("./downloads"),
+                .help("Output directory [default: ./downloads]")
+                .global(true),
         )
         .arg(
-            Arg::new("max-pages")
-                .short('m')
-                .long("max-pages")
-                .help("Maximum pages to crawl")
-                .default_value("50"),
+            Arg::new("config")
+                .short('c')
+                .long("config")
+                .help("Path to configuration file")
+                .value_name("FILE")
+                .global(true),
         )
-        .arg(
-            Arg::new("batch-size")
-                .short('b')
-                .long("batch-size")
-                .help("Number of pages to process before waiting")
-                .default_value("10"),
+        .subcommand(
+            Command::new("crawl")
+                .about("Crawl a site, then convert and translate every page (default when no subcommand is given)")
+                .arg(url_arg("Target website URL").required(false).required_unless_present("urls-file"))
+                .args(source_args())
+                .args(crawl_args())
+                .args(scope_args())
+                .args(batch_args())
+                .args(page_output_args())
+                .args(output_args())
+                .args(translation_args())
+                .arg(
+                    Arg::new("list-only")
+                        .long("list-only")
+                        .help("Only print the URLs that would be processed, without converting them")
+                        .action(clap::ArgAction::SetTrue)
+                        .conflicts_with_all(["no-crawl", "urls-file"]),
+                ),
         )
-        .arg(
-            Arg::new("wait-time")
-                .short('w')
-                .long("wait-time")
-                .help("Wait time in seconds between batches")
-                .default_value("60"),
+        .subcommand(
+            Command::new("convert")
+                .about("Convert (and optionally translate) a single page without crawling")
+                .arg(url_arg("Page URL"))
+                .args(page_output_args())
+                .args(output_args())
+                .args(translation_args()),
         )
-        .arg(
-            Arg::new("translate")
-                .short('t')
-                .long("translate")
-                .help("Enable translation")
-                .action(clap::ArgAction::SetTrue),
+        .subcommand(
+            Command::new("translate")
+                .about("Translate the Markdown files of a local directory into a mirrored tree")
+                .arg(
+                    Arg::new("dir")
+                        .help("Directory containing .md files (searched recursively)")
+                        .value_name("DIR")
+                        .required(true)
+                        .index(1),
+                )
+                .args(output_args())
+                .args(translation_args()),
         )
-        .arg(
-            Arg::new("source-lang")
-                .long("source-lang")
-                .help("Source language for translation"),
+        .subcommand(
+            Command::new("retry-failed")
+                .about("Re-process the failed URLs recorded in the state file instead of crawling")
+                .arg(url_arg("Site URL whose state file should be read"))
+                .args(scope_args())
+                .args(batch_args())
+                .args(page_output_args())
+                .args(output_args())
+                .args(translation_args())
+                .args(failure_filter_args()),
         )
-        .arg(
-            Arg::new("target-lang")
-                .long("target-lang")
-                .help("Target language for translation"),
+        .subcommand(
+            Command::new("status")
+                .about("Show the recorded state of previous runs for a site")
+                .arg(url_arg("Site URL whose state file should be read"))
+                .arg(
+                    Arg::new("json")
+                        .long("json")
+                        .help("Print the report as JSON")
+                        .action(clap::ArgAction::SetTrue),
+                ),
         )
-        .arg(
-            Arg::new("deeplx-url")
-                .long("deeplx-url")
-                .help("DeepLX API URL"),
+        .subcommand(
+            Command::new("export")
+                .about("Export recorded data")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("links")
+                        .about("Write the processed URLs of a site to FILE")
+                        .arg(url_arg("Site URL whose state file should be read"))
+                        .arg(Arg::new("file").value_name("FILE").required(true).index(2)),
+                )
+                .subcommand(
+                    Command::new("memory")
+                        .about("Export the translation memory as JSON to FILE")
+                        .arg(Arg::new("file").value_name("FILE").required(true).index(1)),
+                ),
         )
-        .arg(
-            Arg::new("config")
-                .short('c')
-                .long("config")
-                .help("Path to configuration file")
-                .value_name("FILE"),
+        .subcommand(
+            Command::new("prune-memory")
+                .about("Remove translation memory entries unused for DAYS days")
+                .arg(Arg::new("days").value_name("DAYS").required(true).index(1)),
         )
-        .arg(
-            Arg::new("generate-config")
-                .long("generate-config")
-                .help("Generate example configuration file")
-                .value_name("FILE")
-                .action(clap::ArgAction::Set),
+        .subcommand(
+            Command::new("config")
+                .about("Manage configuration files")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("init")
+                        .about("Generate an example configuration file")
+                        .arg(
+                            Arg::new("file")
+                                .value_name("FILE")
+                                .default_value("config.toml")
+                                .index(1),
+                        ),
+                ),
         )
-        .get_matches();
+}
+
+fn url_arg(help: &'static str) -> Arg {
+    Arg::new("url").help(help).required(true).index(1)
+}
+
+// 不爬取网站，直接处理给出的页面
+fn source_args() -> Vec<Arg> {
+    vec![
+        Arg::new("no-crawl")
+            .long("no-crawl")
+            .help("Process only the given URL instead of crawling from it")
+            .action(clap::ArgAction::SetTrue)
+            .conflicts_with("urls-file"),
+        Arg::new("urls-file")
+            .long("urls-file")
+            .help("Process the newline-separated URLs in FILE (- for stdin) instead of crawling")
+            .value_name("FILE"),
+    ]
+}
+
+// 爬取范围和页面发现
+fn crawl_args() -> Vec<Arg> {
+    vec![
+        Arg::new("max-pages")
+            .short('m')
+            .long("max-pages")
+            .help("Maximum pages to crawl [default: 50]"),
+        Arg::new("max-depth").short('d').long("max-depth").help(
+            "Maximum URL path depth in segments, e.g. /a/b/c is 3; not link hops (0 = unlimited)",
+        ),
+        Arg::new("stay-under-start-path")
+            .long("stay-under-start-path")
+            .help("Only follow links under the start URL's path")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("sitemap")
+            .long("sitemap")
+            .help("Discover pages from robots.txt / sitemap.xml instead of crawling")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("sitemap-url")
+            .long("sitemap-url")
+            .help("Explicit sitemap or sitemap index URL (implies --sitemap)")
+            .value_name("URL"),
+        Arg::new("sitemap-lastmod")
+            .long("sitemap-lastmod")
+            .help("Re-process pages whose sitemap <lastmod> is newer than the last run")
+            .action(clap::ArgAction::SetTrue),
+    ]
+}
+
+fn scope_args() -> Vec<Arg> {
+    vec![
+        Arg::new("include")
+            .long("include")
+            .help("Only process URLs whose path matches this glob (repeatable)")
+            .value_name("GLOB")
+            .action(clap::ArgAction::Append),
+        Arg::new("exclude")
+            .long("exclude")
+            .help("Skip URLs whose path matches this glob (repeatable)")
+            .value_name("GLOB")
+            .action(clap::ArgAction::Append),
+        Arg::new("include-regex")
+            .long("include-regex")
+            .help("Only process URLs matching this regex (repeatable)")
+            .value_name("REGEX")
+            .action(clap::ArgAction::Append),
+        Arg::new("exclude-regex")
+            .long("exclude-regex")
+            .help("Skip URLs matching this regex (repeatable)")
+            .value_name("REGEX")
+            .action(clap::ArgAction::Append),
+    ]
+}
 
-    // 处理生成配置文件的情况
-    if let Some(config_path) = matches.get_one::<String>("generate-config") {
-        ConfigFile::create_example_config(config_path)?;
-        println!("已生成示例配置文件: {}", config_path);
-        std::process::exit(0);
+fn batch_args() -> Vec<Arg> {
+    vec![
+        Arg::new("batch-size")
+            .short('b')
+            .long("batch-size")
+            .help("Number of pages to process before waiting [default: 10]"),
+        Arg::new("wait-time")
+            .short('w')
+            .long("wait-time")
+            .help("Wait time in seconds between batches [default: 60]"),
+    ]
+}
+
+// 网页的转换和保存
+fn page_output_args() -> Vec<Arg> {
+    vec![
+        Arg::new("converter")
+            .long("converter")
+            .help("HTML to Markdown backend: jina (r.jina.ai) or local")
+            .value_parser(["jina", "local"]),
+        Arg::new("layout")
+            .long("layout")
+            .help("Output layout: flat (timestamped names) or mirror (URL path hierarchy)")
+            .value_parser(["flat", "mirror"]),
+        Arg::new("no-rewrite-links")
+            .long("no-rewrite-links")
+            .help("Keep links to downloaded pages pointing at the live site")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("download-assets")
+            .long("download-assets")
+            .help("Download images and other media into the site's assets folder")
+            .action(clap::ArgAction::SetTrue),
+    ]
+}
+
+fn output_args() -> Vec<Arg> {
+    vec![
+        Arg::new("front-matter")
+            .long("front-matter")
+            .help("Prepend YAML front matter with page metadata to saved files")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("on-existing")
+            .long("on-existing")
+            .help("What to do when an output file already exists: overwrite or version")
+            .value_parser(["overwrite", "version"]),
+    ]
+}
+
+fn translation_args() -> Vec<Arg> {
+    vec![
+        Arg::new("translate")
+            .short('t')
+            .long("translate")
+            .help("Enable translation")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("source-lang")
+            .long("source-lang")
+            .help("Source language for translation"),
+        Arg::new("target-lang")
+            .long("target-lang")
+            .help("Target language(s) for translation, comma-separated (e.g. zh,ja,ko)"),
+        Arg::new("translation-backend")
+            .long("translation-backend")
+            .help("Translation backend: deeplx, dptrans, libretranslate, deepl or openai")
+            .value_parser(["deeplx", "dptrans", "libretranslate", "deepl", "openai"]),
+        Arg::new("no-translation-memory")
+            .long("no-translation-memory")
+            .help("Bypass the translation memory (neither read nor write cached translations)")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("glossary")
+            .long("glossary")
+            .value_name("FILE")
+            .help("Glossary TOML file with fixed term translations and protected terms"),
+        Arg::new("deeplx-url")
+            .long("deeplx-url")
+            .help("Translation API URL (DeepLX or the selected backend)"),
+    ]
+}
+
+fn failure_filter_args() -> Vec<Arg> {
+    vec![
+        Arg::new("error-type")
+            .long("error-type")
+            .help("Only retry these error kinds (e.g. timeout,http_status) or retryable/permanent")
+            .value_name("TYPE")
+            .action(clap::ArgAction::Append),
+        Arg::new("min-age")
+            .long("min-age")
+            .help("Only retry failures recorded at least HOURS ago")
+            .value_name("HOURS"),
+        Arg::new("max-age")
+            .long("max-age")
+            .help("Only retry failures recorded within the last HOURS")
+            .value_name("HOURS"),
+        Arg::new("max-attempts")
+            .long("max-attempts")
+            .help(format!(
+                "Skip URLs that have already been attempted this many times [default: {}]",
+                FailureFilter::default().max_attempts
+            ))
+            .value_name("N"),
+    ]
+}
+
+// 各子命令只定义了部分参数，读取未定义的参数视为未提供
+fn value<'a>(matches: &'a ArgMatches, id: &str) -> Option<&'a String> {
+    matches.try_get_one::<String>(id).ok().flatten()
+}
+
+fn flag(matches: &ArgMatches, id: &str) -> bool {
+    matches
+        .try_get_one::<bool>(id)
+        .ok()
+        .flatten()
+        .copied()
+        .unwrap_or(false)
+}
+
+fn required_value(matches: &ArgMatches, id: &str) -> Result<String> {
+    matches
+        .get_one::<String>(id)
+        .cloned()
+        .ok_or_else(|| Error::Custom(format!("Missing required argument: {}", id)))
+}
+
+fn required_url(matches: &ArgMatches) -> Result<String> {
+    Ok(normalize_url(&required_value(matches, "url")?))
+}
+
+fn prune_days(matches: &ArgMatches) -> Result<u64> {
+    let value = required_value(matches, "days")?;
+    value
+        .parse::<u64>()
+        .ok()
+        .filter(|days| TranslationMemory::cutoff(*days).is_ok())
+        .ok_or_else(|| {
+            Error::Custom(format!(
+                "Invalid number of days for prune-memory: {}",
+                value
+            ))
+        })
+}
+
+fn load_config_file(matches: &ArgMatches) -> Result<ConfigFile> {
+    match matches.get_one::<String>("config") {
+        Some(config_path) => ConfigFile::load_from_file(config_path),
+        None => Ok(ConfigFile::load_default()),
     }
+}
 
-    // 如果没有提供 URL，显示帮助信息
-    let mut url = matches.get_one::<String>("url")
-        .ok_or_else(|| crate::error::Error::Custom("URL is required".to_string()))?
-        .clone();
-    
-    // 如果 URL 没有协议前缀，添加 https://
-    if !url.starts_with("http://") && !url.starts_with("https://") {
-        url = format!("https://{}", url);
+// 命令行参数优先，其次是配置文件中的输出目录
+fn output_dir(matches: &ArgMatches) -> Result<String> {
+    match matches.get_one::<String>("output") {
+        Some(output_dir) => Ok(output_dir.clone()),
+        None => Ok(load_config_file(matches)?.general.output_dir),
     }
+}
 
-    // 加载配置文件
-    let config_file = if let Some(config_path) = matches.get_one::<String>("config") {
-        ConfigFile::load_from_file(config_path)?
-    } else {
-        ConfigFile::load_default()
+fn run_config(matches: &ArgMatches) -> Result<Config> {
+    let page_urls = value(matches, "urls-file")
+        .map(|path| read_urls_file(path))
+        .transpose()?;
+
+    // 只给出链接列表时，以列表所在的站点记录处理状态；跨多个站点时需要给出 URL 指定状态文件
+    let url = match (matches.get_one::<String>("url"), &page_urls) {
+        (Some(url), _) => normalize_url(url),
+        (None, Some(urls)) => single_site_url(urls)?,
+        (None, None) => return Err(Error::Custom("URL is required".to_string())),
     };
 
-    // 从命令行参数覆盖配置文件的设置
-    let output_dir = matches.get_one::<String>("output")
-        .map(|s| s.clone())
+    let config = build_config(matches, url)?;
+    if let Some(urls) = page_urls {
+        Ok(config.with_page_urls(urls))
+    } else if flag(matches, "no-crawl") {
+        let url = config.url.clone();
+        Ok(config.with_page_urls(vec![url]))
+    } else {
+        Ok(config)
+    }
+}
+
+// 列表中的链接都属于同一个站点时返回第一个链接
+fn single_site_url(urls: &[String]) -> Result<String> {
+    let first = urls
+        .first()
+        .ok_or_else(|| Error::Custom("The URL list is empty".to_string()))?;
+
+    let mut hosts: Vec<String> = Vec::new();
+    for url in urls {
+        let host = url::Url::parse(url)?
+            .host_str()
+            .unwrap_or_default()
+            .to_lowercase();
+        if !hosts.contains(&host) {
+            hosts.push(host);
+        }
+    }
+    if hosts.len() > 1 {
+        return Err(Error::Custom(format!(
+            "The URL list spans several sites ({}); pass a URL to choose which site's state file to use",
+            hosts.join(", ")
+        )));
+    }
+    Ok(first.clone())
+}
+
+// 每行一个 URL，忽略空行和 # 开头的注释，重复的 URL 只保留第一个
+fn read_urls_file(path: &str) -> Result<Vec<String>> {
+    if path == "-" {
+        return read_urls(std::io::stdin(), "stdin");
+    }
+    let file =
+        std::fs::File::open(path).map_err(|e| Error::filesystem(std::path::Path::new(path), e))?;
+    read_urls(file, path)
+}
+
+// 每行一个 URL，跳过空行和 # 注释，重复的只保留第一个；source 用于错误信息
+fn read_urls(reader: impl std::io::Read, source: &str) -> Result<Vec<String>> {
+    let content = std::io::read_to_string(reader)?;
+
+    let mut urls: Vec<String> = Vec::new();
+    for (line_number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let url = normalize_url(line);
+        url::Url::parse(&url).map_err(|e| {
+            Error::Custom(format!(
+                "Invalid URL on line {} of {}: {} ({})",
+                line_number + 1,
+                source,
+                line,
+                e
+            ))
+        })?;
+        if !urls.contains(&url) {
+            urls.push(url);
+        }
+    }
+    Ok(urls)
+}
+
+// 加载配置文件，再用命令行参数覆盖其中的设置
+fn build_config(matches: &ArgMatches, url: String) -> Result<Config> {
+    let config_file = load_config_file(matches)?;
+
+    let output_dir = value(matches, "output")
+        .cloned()
         .unwrap_or(config_file.general.output_dir.clone());
-    
-    let max_pages: u32 = matches.get_one::<String>("max-pages")
+
+    let max_pages: u32 = value(matches, "max-pages")
         .map(|s| s.parse())
         .unwrap_or(Ok(config_file.general.max_pages))?;
-    
-    let batch_size: usize = matches.get_one::<String>("batch-size")
+
+    let batch_size: usize = value(matches, "batch-size")
         .map(|s| s.parse())
         .unwrap_or(Ok(config_file.general.batch_size))?;
-    
-    let wait_time: u64 = matches.get_one::<String>("wait-time")
+
+    let wait_time: u64 = value(matches, "wait-time")
         .map(|s| s.parse())
         .unwrap_or(Ok(config_file.general.wait_time))?;
 
+    let max_depth: usize = value(matches, "max-depth")
+        .map(|s| s.parse())
+        .unwrap_or(Ok(config_file.general.max_depth))?;
+
+    let stay_under_start_path =
+        flag(matches, "stay-under-start-path") || config_file.general.stay_under_start_path;
+
     // 翻译配置：命令行参数优先
-    let translate_enabled = if matches.get_flag("translate") {
+    let translate_enabled = if flag(matches, "translate") {
         true
     } else {
         config_file.translation.enabled
     };
 
-    let source_lang = matches.get_one::<String>("source-lang")
-        .map(|s| s.clone())
-        .unwrap_or(config_file.translation.source_lang.clone());
-    
-    let target_lang = matches.get_one::<String>("target-lang")
-        .map(|s| s.clone())
-        .unwrap_or(config_file.translation.target_lang.clone());
-    
-    let deeplx_url = matches.get_one::<String>("deeplx-url")
-        .map(|s| s.clone())
-        .unwrap_or(config_file.translation.deeplx_api_url.clone());
-
-    let translation_config = TranslationConfig {
-        enabled: translate_enabled,
-        source_lang,
-        target_lang,
-        deeplx_api_url: deeplx_url,
-        max_requests_per_second: config_file.translation.max_requests_per_second,
-        max_text_length: config_file.translation.max_text_length,
-        max_paragraphs_per_request: config_file.translation.max_paragraphs_per_request,
+    let mut translation_config = config_file.to_translation_config()?;
+    translation_config.enabled = translate_enabled;
+    if let Some(backend) = value(matches, "translation-backend") {
+        translation_config.backend = TranslationBackendKind::from_name(backend)?;
+        // 配置文件没有指定接口地址时，使用新后端的默认地址
+        if config_file.translation.deeplx_api_url.is_none() {
+            translation_config.deeplx_api_url =
+                translation_config.backend.default_api_url().to_string();
+        }
+    }
+    if let Some(source_lang) = value(matches, "source-lang") {
+        translation_config.source_lang = source_lang.clone();
+    }
+    if let Some(target_lang) = value(matches, "target-lang") {
+        translation_config.target_langs = LanguageList::One(target_lang.clone()).to_vec();
+    }
+    if let Some(deeplx_url) = value(matches, "deeplx-url") {
+        translation_config.deeplx_api_url = deeplx_url.clone();
+    }
+    if let Some(glossary) = value(matches, "glossary") {
+        translation_config.glossary = Some(glossary.clone());
+    }
+    if flag(matches, "no-translation-memory") {
+        translation_config.memory = false;
+    }
+
+    // 范围规则：命令行参数追加到配置文件的规则之后
+    let mut url_filter = config_file.to_url_filter_config();
+    let append_values = |target: &mut Vec<String>, id: &str| {
+        if let Ok(Some(values)) = matches.try_get_many::<String>(id) {
+            target.extend(values.cloned());
+        }
     };
+    append_values(&mut url_filter.include_patterns, "include");
+    append_values(&mut url_filter.exclude_patterns, "exclude");
+    append_values(&mut url_filter.include_regex, "include-regex");
+    append_values(&mut url_filter.exclude_regex, "exclude-regex");
+
+    let mut sitemap = config_file.to_sitemap_config();
+    if let Some(sitemap_url) = value(matches, "sitemap-url") {
+        sitemap.sitemap_url = Some(sitemap_url.clone());
+        sitemap.enabled = true;
+    }
+    if flag(matches, "sitemap") {
+        sitemap.enabled = true;
+    }
+    if flag(matches, "sitemap-lastmod") {
+        sitemap.use_lastmod = true;
+    }
+
+    let config = Config::from_config_file(url, &config_file)?
+        .with_translation(translation_config)
+        .with_url_filter(url_filter)
+        .with_sitemap(sitemap);
 
-    let config = Config::from_config_file(url, &config_file)
-        .with_translation(translation_config);
-    
     // 用命令行参数覆盖
     let mut config = config;
     config.output_dir = output_dir;
     config.max_pages = max_pages;
     config.batch_size = batch_size;
     config.wait_time = wait_time;
-    
+    config.max_depth = max_depth;
+    config.stay_under_start_path = stay_under_start_path;
+    if flag(matches, "front-matter") {
+        config.output.front_matter = true;
+    }
+    if flag(matches, "download-assets") {
+        config.assets.enabled = true;
+    }
+    if flag(matches, "no-rewrite-links") {
+        config.output.rewrite_links = false;
+    }
+    if let Some(layout) = value(matches, "layout") {
+        config.output.layout = OutputLayout::from_name(layout)?;
+    }
+    if let Some(policy) = value(matches, "on-existing") {
+        config.output.on_existing = ExistingFilePolicy::from_name(policy)?;
+    }
+    if let Some(converter) = value(matches, "converter") {
+        config.converter = ConverterBackend::from_name(converter)?;
+    }
+
     config.validate()?;
-    
+
     Ok(config)
-}
\ No newline at end of file
+}
+
+fn failure_filter(matches: &ArgMatches) -> Result<FailureFilter> {
+    let hours = |id: &str| -> Result<Option<chrono::Duration>> {
+        matches
+            .get_one::<String>(id)
+            .map(|value| {
+                let invalid =
+                    || Error::Custom(format!("Invalid number of hours for --{}: {}", id, value));
+                let hours: f64 = value.parse().map_err(|_| invalid())?;
+                if !hours.is_finite() || hours < 0.0 {
+                    return Err(invalid());
+                }
+                chrono::Duration::try_seconds((hours * 3600.0) as i64).ok_or_else(invalid)
+            })
+            .transpose()
+    };
+
+    Ok(FailureFilter {
+        error_types: matches
+            .get_many::<String>("error-type")
+            .map(|values| {
+                values
+                    .flat_map(|value| value.split(','))
+                    .map(|value| value.trim().to_lowercase())
+                    .filter(|value| !value.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default(),
+        min_age: hours("min-age")?,
+        max_age: hours("max-age")?,
+        max_attempts: matches
+            .get_one::<String>("max-attempts")
+            .map(|value| value.parse())
+            .transpose()?
+            .unwrap_or(FailureFilter::default().max_attempts),
+    })
+}
+
+// 如果 URL 没有协议前缀，添加 https://
+fn normalize_url(url: &str) -> String {
+    if url.starts_with("http://") || url.starts_with("https://") {
+        url.to_string()
+    } else {
+        format!("https://{}", url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn urls(list: &[&str]) -> Vec<String> {
+        list.iter().map(|url| url.to_string()).collect()
+    }
+
+    #[test]
+    fn url_list_on_one_site_uses_the_first_url() {
+        let list = urls(&["https://example.com/a", "https://EXAMPLE.com/b"]);
+        assert_eq!(single_site_url(&list).unwrap(), "https://example.com/a");
+    }
+
+    #[test]
+    fn url_list_across_sites_is_rejected() {
+        let list = urls(&["https://example.com/a", "https://docs.example.org/b"]);
+        let error = single_site_url(&list).unwrap_err().to_string();
+        assert!(error.contains("example.com, docs.example.org"), "{}", error);
+        assert!(single_site_url(&[]).is_err());
+    }
+
+    #[test]
+    fn url_lists_skip_comments_and_duplicates() {
+        let content = "# docs\n\nhttps://example.com/a\n  example.com/b  \nhttps://example.com/a\n# https://example.com/c\n";
+        assert_eq!(
+            read_urls(content.as_bytes(), "stdin").unwrap(),
+            urls(&["https://example.com/a", "https://example.com/b"])
+        );
+    }
+
+    #[test]
+    fn invalid_urls_name_their_line_and_source() {
+        let error = read_urls(
+            "https://example.com/a\nhttps://exa mple.com/b\n".as_bytes(),
+            "stdin",
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(error.contains("line 2 of stdin"), "{}", error);
+    }
+
+    #[test]
+    fn url_lists_are_read_from_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("urls.txt");
+        std::fs::write(&path, "https://example.com/a\n").unwrap();
+
+        assert_eq!(
+            read_urls_file(path.to_str().unwrap()).unwrap(),
+            urls(&["https://example.com/a"])
+        );
+        assert!(matches!(
+            read_urls_file(dir.path().join("missing.txt").to_str().unwrap()),
+            Err(Error::Filesystem { .. })
+        ));
+    }
+
+    fn parse_filter(args: &[&str]) -> Result<FailureFilter> {
+        let matches = Command::new("retry-failed")
+            .args(failure_filter_args())
+            .try_get_matches_from(std::iter::once("retry-failed").chain(args.iter().copied()))
+            .unwrap();
+        failure_filter(&matches)
+    }
+
+    #[test]
+    fn failure_filter_defaults() {
+        let filter = parse_filter(&[]).unwrap();
+        assert!(filter.error_types.is_empty());
+        assert_eq!(filter.min_age, None);
+        assert_eq!(filter.max_age, None);
+        assert_eq!(filter.max_attempts, FailureFilter::default().max_attempts);
+        assert_eq!(
+            parse_filter(&["--max-attempts", "2"]).unwrap().max_attempts,
+            2
+        );
+        assert!(parse_filter(&["--max-attempts", "many"]).is_err());
+    }
+
+    #[test]
+    fn failure_filter_splits_error_types() {
+        let filter = parse_filter(&[
+            "--error-type",
+            "Timeout, http_status",
+            "--error-type",
+            "retryable,",
+        ])
+        .unwrap();
+        assert_eq!(filter.error_types, ["timeout", "http_status", "retryable"]);
+    }
+
+    #[test]
+    fn failure_filter_parses_ages_in_hours() {
+        let filter = parse_filter(&["--min-age", "1.5", "--max-age", "48"]).unwrap();
+        assert_eq!(filter.min_age, Some(chrono::Duration::minutes(90)));
+        assert_eq!(filter.max_age, Some(chrono::Duration::hours(48)));
+
+        for invalid in [
+            "--min-age=-1",
+            "--min-age=soon",
+            "--min-age=NaN",
+            "--max-age=inf",
+        ] {
+            assert!(parse_filter(&[invalid]).is_err(), "{}", invalid);
+        }
+    }
+}
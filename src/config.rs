@@ -1,6 +1,14 @@
-use crate::error::Result;
-use crate::types::TranslationConfig;
+use crate::assets::AssetConfig;
 use crate::config_file::ConfigFile;
+use crate::converter::ConverterBackend;
+use crate::error::Result;
+use crate::extractor::ExtractionConfig;
+use crate::links_manager::FailureFilter;
+use crate::rate_limiter::RateLimitConfig;
+use crate::sitemap::SitemapConfig;
+use crate::types::{FRONT_MATTER_FIELDS, OutputConfig, RetryPolicies, TranslationConfig};
+use crate::url_filter::UrlFilterConfig;
+use std::path::PathBuf;
 
 #[derive(Debug, Clone)]
 pub struct Config {
@@ -12,7 +20,23 @@ pub struct Config {
     pub request_delay: u64,
     pub timeout: u64,
     pub user_agent: String,
+    pub max_depth: usize,
+    pub stay_under_start_path: bool,
+    pub converter: ConverterBackend,
+    pub extraction: ExtractionConfig,
+    pub output: OutputConfig,
+    pub assets: AssetConfig,
+    pub rate_limit: RateLimitConfig,
+    pub retry: RetryPolicies,
     pub translation: TranslationConfig,
+    pub url_filter: UrlFilterConfig,
+    pub sitemap: SitemapConfig,
+    // 设置后不爬取网站，只重新处理状态文件中符合条件的失败链接
+    pub retry_failed: Option<FailureFilter>,
+    // 设置后不爬取网站，只处理这些页面
+    pub page_urls: Option<Vec<String>>,
+    // 设置后不爬取网站，翻译这个目录中的 Markdown 文件（url 为空）
+    pub source_dir: Option<PathBuf>,
 }
 
 impl Config {
@@ -32,12 +56,25 @@ impl Config {
             request_delay: 500,
             timeout: 30,
             user_agent: "Mozilla/5.0 (compatible; MarkdownDownloader/1.0)".to_string(),
+            max_depth: 0,
+            stay_under_start_path: false,
+            converter: ConverterBackend::default(),
+            extraction: ExtractionConfig::default(),
+            output: OutputConfig::default(),
+            assets: AssetConfig::default(),
+            rate_limit: RateLimitConfig::default(),
+            retry: RetryPolicies::default(),
             translation: TranslationConfig::default(),
+            url_filter: UrlFilterConfig::default(),
+            sitemap: SitemapConfig::default(),
+            retry_failed: None,
+            page_urls: None,
+            source_dir: None,
         }
     }
 
-    pub fn from_config_file(url: String, config_file: &ConfigFile) -> Self {
-        Self {
+    pub fn from_config_file(url: String, config_file: &ConfigFile) -> Result<Self> {
+        Ok(Self {
             url,
             output_dir: config_file.general.output_dir.clone(),
             max_pages: config_file.general.max_pages,
@@ -46,8 +83,21 @@ impl Config {
             request_delay: config_file.general.request_delay,
             timeout: config_file.general.timeout,
             user_agent: config_file.general.user_agent.clone(),
-            translation: config_file.to_translation_config(),
-        }
+            max_depth: config_file.general.max_depth,
+            stay_under_start_path: config_file.general.stay_under_start_path,
+            converter: ConverterBackend::from_name(&config_file.converter.backend)?,
+            extraction: config_file.to_extraction_config(),
+            output: config_file.to_output_config()?,
+            assets: config_file.to_asset_config(),
+            rate_limit: config_file.to_rate_limit_config(),
+            retry: config_file.to_retry_policies(),
+            translation: config_file.to_translation_config()?,
+            url_filter: config_file.to_url_filter_config(),
+            sitemap: config_file.to_sitemap_config(),
+            retry_failed: None,
+            page_urls: None,
+            source_dir: None,
+        })
     }
 
     pub fn with_translation(mut self, translation: TranslationConfig) -> Self {
@@ -55,23 +105,109 @@ impl Config {
         self
     }
 
+    pub fn with_url_filter(mut self, url_filter: UrlFilterConfig) -> Self {
+        self.url_filter = url_filter;
+        self
+    }
+
+    pub fn with_sitemap(mut self, sitemap: SitemapConfig) -> Self {
+        self.sitemap = sitemap;
+        self
+    }
+
+    pub fn with_retry_failed(mut self, filter: FailureFilter) -> Self {
+        self.retry_failed = Some(filter);
+        self
+    }
+
+    pub fn with_page_urls(mut self, urls: Vec<String>) -> Self {
+        self.page_urls = Some(urls);
+        self
+    }
+
+    pub fn with_source_dir(mut self, source_dir: PathBuf) -> Self {
+        self.source_dir = Some(source_dir);
+        self
+    }
+
     pub fn validate(&self) -> Result<()> {
-        if self.url.is_empty() {
+        if self.url.is_empty() && self.source_dir.is_none() {
             return Err("URL cannot be empty".into());
         }
-        
+
         if self.output_dir.is_empty() {
             return Err("Output directory cannot be empty".into());
         }
-        
+
         if self.max_pages == 0 {
             return Err("Max pages must be greater than 0".into());
         }
-        
+
         if self.batch_size == 0 {
             return Err("Batch size must be greater than 0".into());
         }
-        
+
+        if self.translation.enabled && self.translation.target_langs.is_empty() {
+            return Err("At least one target language is required".into());
+        }
+
+        // 缺少密钥时每个页面都会失败，在开始前报错
+        if self.translation.enabled
+            && self.translation.backend.requires_api_key()
+            && self.translation.resolved_api_key().is_none()
+        {
+            let env = self.translation.backend.api_key_env().unwrap_or_default();
+            return Err(format!(
+                "The {} translation backend requires an api_key (set translation.api_key or {})",
+                self.translation.backend_name(),
+                env
+            )
+            .into());
+        }
+
+        if self.rate_limit.requests_per_second <= 0.0 {
+            return Err("Rate limit must be greater than 0 requests per second".into());
+        }
+
+        for (stage, retry) in [
+            ("crawl", &self.retry.crawl),
+            ("convert", &self.retry.convert),
+            ("translate", &self.retry.translate),
+            ("assets", &self.retry.assets),
+        ] {
+            if !(0.0..=1.0).contains(&retry.jitter) {
+                return Err(format!("Retry jitter for {} must be between 0 and 1", stage).into());
+            }
+            if retry.backoff_multiplier < 1.0 {
+                return Err(
+                    format!("Retry backoff multiplier for {} must be at least 1", stage).into(),
+                );
+            }
+        }
+
+        if self
+            .retry_failed
+            .as_ref()
+            .is_some_and(|filter| filter.max_attempts == 0)
+        {
+            return Err("Max attempts must be greater than 0".into());
+        }
+
+        if self.assets.enabled && self.assets.max_size_mb == 0 {
+            return Err("Asset size limit must be greater than 0".into());
+        }
+
+        for field in &self.output.front_matter_fields {
+            if !FRONT_MATTER_FIELDS.contains(&field.as_str()) {
+                return Err(format!(
+                    "Unknown front matter field: {} (expected one of: {})",
+                    field,
+                    FRONT_MATTER_FIELDS.join(", ")
+                )
+                .into());
+            }
+        }
+
         Ok(())
     }
-}
\ No newline at end of file
+}
@@ -1,15 +1,40 @@
-use crate::types::TranslationConfig;
+use crate::assets::{AssetConfig, default_allowed_types};
 use crate::error::Result;
+use crate::extractor::{ExtractionConfig, SiteSelectors};
+use crate::rate_limiter::RateLimitConfig;
+use crate::sitemap::SitemapConfig;
+use crate::types::{
+    ExistingFilePolicy, FRONT_MATTER_FIELDS, OutputConfig, OutputLayout, RetryConfig,
+    RetryPolicies, TranslationBackendKind, TranslationConfig,
+};
+use crate::url_filter::UrlFilterConfig;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ConfigFile {
     #[serde(default)]
     pub general: GeneralConfig,
     #[serde(default)]
     pub translation: TranslationFileConfig,
+    #[serde(default)]
+    pub filter: FilterFileConfig,
+    #[serde(default)]
+    pub sitemap: SitemapFileConfig,
+    #[serde(default)]
+    pub converter: ConverterFileConfig,
+    #[serde(default)]
+    pub extraction: ExtractionFileConfig,
+    #[serde(default)]
+    pub output: OutputFileConfig,
+    #[serde(default)]
+    pub assets: AssetsFileConfig,
+    #[serde(default)]
+    pub rate_limit: RateLimitFileConfig,
+    #[serde(default)]
+    pub retry: RetryFileConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,40 +53,265 @@ pub struct GeneralConfig {
     pub timeout: u64,
     #[serde(default = "default_user_agent")]
     pub user_agent: String,
+    #[serde(default)]
+    pub max_depth: usize,
+    #[serde(default)]
+    pub stay_under_start_path: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum LanguageList {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl LanguageList {
+    // 也接受逗号分隔的写法 "zh,ja"；去掉空项和重复项
+    pub fn to_vec(&self) -> Vec<String> {
+        let values: Vec<&str> = match self {
+            LanguageList::One(value) => value.split(',').collect(),
+            LanguageList::Many(values) => {
+                values.iter().flat_map(|value| value.split(',')).collect()
+            }
+        };
+
+        let mut languages: Vec<String> = Vec::new();
+        for value in values.into_iter().map(str::trim).filter(|v| !v.is_empty()) {
+            if !languages
+                .iter()
+                .any(|lang| lang.eq_ignore_ascii_case(value))
+            {
+                languages.push(value.to_string());
+            }
+        }
+        languages
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TranslationFileConfig {
     #[serde(default)]
     pub enabled: bool,
+    // 未设置时使用 deeplx
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend: Option<String>,
     #[serde(default = "default_source_lang")]
     pub source_lang: String,
+    // 单个语言或语言列表："zh" 或 ["zh", "ja", "ko"]
     #[serde(default = "default_target_lang")]
-    pub target_lang: String,
-    #[serde(default = "default_deeplx_url")]
-    pub deeplx_api_url: String,
+    pub target_lang: LanguageList,
+    // 未设置时使用所选后端的默认地址
+    #[serde(default, alias = "api_url", skip_serializing_if = "Option::is_none")]
+    pub deeplx_api_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    #[serde(default = "default_translation_model")]
+    pub model: String,
+    #[serde(default = "default_translation_memory")]
+    pub memory: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub glossary: Option<String>,
     #[serde(default = "default_max_requests_per_second")]
     pub max_requests_per_second: f64,
     #[serde(default = "default_max_text_length")]
     pub max_text_length: usize,
     #[serde(default = "default_max_paragraphs_per_request")]
     pub max_paragraphs_per_request: usize,
+    #[serde(default = "default_max_concurrent_requests")]
+    pub max_concurrent_requests: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FilterFileConfig {
+    #[serde(default)]
+    pub include_patterns: Vec<String>,
+    #[serde(default)]
+    pub exclude_patterns: Vec<String>,
+    #[serde(default)]
+    pub include_regex: Vec<String>,
+    #[serde(default)]
+    pub exclude_regex: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SitemapFileConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sitemap_url: Option<String>,
+    #[serde(default)]
+    pub use_lastmod: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConverterFileConfig {
+    #[serde(default = "default_converter_backend")]
+    pub backend: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExtractionFileConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub sites: HashMap<String, SiteSelectors>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutputFileConfig {
+    #[serde(default)]
+    pub front_matter: bool,
+    #[serde(default = "default_front_matter_fields")]
+    pub front_matter_fields: Vec<String>,
+    #[serde(default = "default_output_layout")]
+    pub layout: String,
+    #[serde(default = "default_on_existing")]
+    pub on_existing: String,
+    #[serde(default = "default_rewrite_links")]
+    pub rewrite_links: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssetsFileConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_max_asset_size_mb")]
+    pub max_size_mb: u64,
+    #[serde(default = "default_allowed_types")]
+    pub allowed_types: Vec<String>,
+}
+
+// 转换器和翻译服务共用的限流设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitFileConfig {
+    // 未设置时沿用 [translation] 的 max_requests_per_second
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requests_per_second: Option<f64>,
+    #[serde(default = "default_rate_limit_burst")]
+    pub burst: u32,
+}
+
+// 单个阶段的重试策略，未设置的键使用默认值
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryPolicyFileConfig {
+    #[serde(default = "default_max_retries")]
+    pub max_retries: usize,
+    #[serde(default = "default_initial_delay_ms")]
+    pub initial_delay_ms: u64,
+    #[serde(default = "default_max_delay_ms")]
+    pub max_delay_ms: u64,
+    #[serde(default = "default_backoff_multiplier")]
+    pub backoff_multiplier: f64,
+    #[serde(default = "default_retry_jitter")]
+    pub jitter: f64,
+    #[serde(default = "default_max_retry_after_secs")]
+    pub max_retry_after_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryFileConfig {
+    // 运行结束前重新处理失败页面的轮数
+    #[serde(default = "default_page_retries")]
+    pub page_retries: usize,
+    #[serde(default)]
+    pub crawl: RetryPolicyFileConfig,
+    #[serde(default)]
+    pub convert: RetryPolicyFileConfig,
+    #[serde(default)]
+    pub translate: RetryPolicyFileConfig,
+    #[serde(default)]
+    pub assets: RetryPolicyFileConfig,
 }
 
 // Default value functions
-fn default_output_dir() -> String { "./downloads".to_string() }
-fn default_max_pages() -> u32 { 50 }
-fn default_batch_size() -> usize { 10 }
-fn default_wait_time() -> u64 { 60 }
-fn default_request_delay() -> u64 { 500 }
-fn default_timeout() -> u64 { 30 }
-fn default_user_agent() -> String { "Mozilla/5.0 (compatible; MarkdownDownloader/1.0)".to_string() }
-fn default_source_lang() -> String { "auto".to_string() }
-fn default_target_lang() -> String { "zh".to_string() }
-fn default_deeplx_url() -> String { "http://localhost:1188/translate".to_string() }
-fn default_max_requests_per_second() -> f64 { 2.0 }
-fn default_max_text_length() -> usize { 3000 }
-fn default_max_paragraphs_per_request() -> usize { 10 }
+fn default_output_dir() -> String {
+    "./downloads".to_string()
+}
+fn default_max_pages() -> u32 {
+    50
+}
+fn default_batch_size() -> usize {
+    10
+}
+fn default_wait_time() -> u64 {
+    60
+}
+fn default_request_delay() -> u64 {
+    500
+}
+fn default_timeout() -> u64 {
+    30
+}
+fn default_user_agent() -> String {
+    "Mozilla/5.0 (compatible; MarkdownDownloader/1.0)".to_string()
+}
+fn default_converter_backend() -> String {
+    "jina".to_string()
+}
+fn default_front_matter_fields() -> Vec<String> {
+    FRONT_MATTER_FIELDS.iter().map(|f| f.to_string()).collect()
+}
+fn default_output_layout() -> String {
+    "flat".to_string()
+}
+fn default_on_existing() -> String {
+    "overwrite".to_string()
+}
+fn default_rewrite_links() -> bool {
+    true
+}
+fn default_max_asset_size_mb() -> u64 {
+    10
+}
+fn default_rate_limit_burst() -> u32 {
+    2
+}
+fn default_max_retries() -> usize {
+    RetryConfig::default().max_retries
+}
+fn default_initial_delay_ms() -> u64 {
+    RetryConfig::default().initial_delay_ms
+}
+fn default_max_delay_ms() -> u64 {
+    RetryConfig::default().max_delay_ms
+}
+fn default_backoff_multiplier() -> f64 {
+    RetryConfig::default().backoff_multiplier
+}
+fn default_retry_jitter() -> f64 {
+    RetryConfig::default().jitter
+}
+fn default_max_retry_after_secs() -> u64 {
+    RetryConfig::default().max_retry_after_secs
+}
+fn default_page_retries() -> usize {
+    RetryPolicies::default().page_retries
+}
+fn default_source_lang() -> String {
+    "auto".to_string()
+}
+fn default_target_lang() -> LanguageList {
+    LanguageList::One("zh".to_string())
+}
+fn default_translation_model() -> String {
+    "gpt-4o-mini".to_string()
+}
+fn default_translation_memory() -> bool {
+    true
+}
+fn default_max_requests_per_second() -> f64 {
+    2.0
+}
+fn default_max_text_length() -> usize {
+    3000
+}
+fn default_max_paragraphs_per_request() -> usize {
+    10
+}
+fn default_max_concurrent_requests() -> usize {
+    4
+}
 
 impl Default for GeneralConfig {
     fn default() -> Self {
@@ -73,29 +323,105 @@ impl Default for GeneralConfig {
             request_delay: default_request_delay(),
             timeout: default_timeout(),
             user_agent: default_user_agent(),
+            max_depth: 0,
+            stay_under_start_path: false,
         }
     }
 }
 
-impl Default for TranslationFileConfig {
+impl Default for ConverterFileConfig {
+    fn default() -> Self {
+        Self {
+            backend: default_converter_backend(),
+        }
+    }
+}
+
+impl Default for OutputFileConfig {
+    fn default() -> Self {
+        Self {
+            front_matter: false,
+            front_matter_fields: default_front_matter_fields(),
+            layout: default_output_layout(),
+            on_existing: default_on_existing(),
+            rewrite_links: default_rewrite_links(),
+        }
+    }
+}
+
+impl Default for AssetsFileConfig {
     fn default() -> Self {
         Self {
             enabled: false,
-            source_lang: default_source_lang(),
-            target_lang: default_target_lang(),
-            deeplx_api_url: default_deeplx_url(),
-            max_requests_per_second: default_max_requests_per_second(),
-            max_text_length: default_max_text_length(),
-            max_paragraphs_per_request: default_max_paragraphs_per_request(),
+            max_size_mb: default_max_asset_size_mb(),
+            allowed_types: default_allowed_types(),
+        }
+    }
+}
+
+impl Default for RateLimitFileConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_second: None,
+            burst: default_rate_limit_burst(),
+        }
+    }
+}
+
+impl Default for RetryPolicyFileConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: default_max_retries(),
+            initial_delay_ms: default_initial_delay_ms(),
+            max_delay_ms: default_max_delay_ms(),
+            backoff_multiplier: default_backoff_multiplier(),
+            jitter: default_retry_jitter(),
+            max_retry_after_secs: default_max_retry_after_secs(),
         }
     }
 }
 
-impl Default for ConfigFile {
+impl Default for RetryFileConfig {
     fn default() -> Self {
         Self {
-            general: GeneralConfig::default(),
-            translation: TranslationFileConfig::default(),
+            page_retries: default_page_retries(),
+            crawl: RetryPolicyFileConfig::default(),
+            convert: RetryPolicyFileConfig::default(),
+            translate: RetryPolicyFileConfig::default(),
+            assets: RetryPolicyFileConfig::default(),
+        }
+    }
+}
+
+impl RetryPolicyFileConfig {
+    pub fn to_retry_config(&self) -> RetryConfig {
+        RetryConfig {
+            max_retries: self.max_retries,
+            initial_delay_ms: self.initial_delay_ms,
+            max_delay_ms: self.max_delay_ms,
+            backoff_multiplier: self.backoff_multiplier,
+            jitter: self.jitter,
+            max_retry_after_secs: self.max_retry_after_secs,
+        }
+    }
+}
+
+impl Default for TranslationFileConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            backend: None,
+            source_lang: default_source_lang(),
+            target_lang: default_target_lang(),
+            deeplx_api_url: None,
+            api_key: None,
+            model: default_translation_model(),
+            memory: default_translation_memory(),
+            glossary: None,
+            max_requests_per_second: default_max_requests_per_second(),
+            max_text_length: default_max_text_length(),
+            max_paragraphs_per_request: default_max_paragraphs_per_request(),
+            max_concurrent_requests: default_max_concurrent_requests(),
         }
     }
 }
@@ -103,14 +429,16 @@ impl Default for ConfigFile {
 impl ConfigFile {
     pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
         let content = fs::read_to_string(path)?;
-        let config: ConfigFile = toml::from_str(&content)
-            .map_err(|e| crate::error::Error::Custom(format!("Failed to parse config file: {}", e)))?;
+        let config: ConfigFile = toml::from_str(&content).map_err(|e| {
+            crate::error::Error::Custom(format!("Failed to parse config file: {}", e))
+        })?;
         Ok(config)
     }
 
     pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
-        let content = toml::to_string_pretty(self)
-            .map_err(|e| crate::error::Error::Custom(format!("Failed to serialize config: {}", e)))?;
+        let content = toml::to_string_pretty(self).map_err(|e| {
+            crate::error::Error::Custom(format!("Failed to serialize config: {}", e))
+        })?;
         fs::write(path, content)?;
         Ok(())
     }
@@ -130,7 +458,10 @@ impl ConfigFile {
 
         // Check in home directory
         if let Some(home) = dirs::home_dir() {
-            let home_config = home.join(".config").join("markdown-downloader").join("config.toml");
+            let home_config = home
+                .join(".config")
+                .join("markdown-downloader")
+                .join("config.toml");
             if home_config.exists() {
                 return Some(home_config);
             }
@@ -155,15 +486,107 @@ impl ConfigFile {
         Self::default()
     }
 
-    pub fn to_translation_config(&self) -> TranslationConfig {
-        TranslationConfig {
+    pub fn to_translation_config(&self) -> Result<TranslationConfig> {
+        let backend = match &self.translation.backend {
+            Some(name) => TranslationBackendKind::from_name(name)?,
+            None => {
+                // 旧版本按接口地址推断 dptrans，现在需要显式设置 backend
+                if self
+                    .translation
+                    .deeplx_api_url
+                    .as_deref()
+                    .is_some_and(|url| url.contains("dptrans"))
+                {
+                    eprintln!(
+                        "警告: 接口地址看起来是 dptrans，但未设置 backend，将按 deeplx 处理；请设置 backend = \"dptrans\""
+                    );
+                }
+                TranslationBackendKind::default()
+            }
+        };
+
+        let api_url = self
+            .translation
+            .deeplx_api_url
+            .clone()
+            .unwrap_or_else(|| backend.default_api_url().to_string());
+
+        Ok(TranslationConfig {
             enabled: self.translation.enabled,
+            backend,
             source_lang: self.translation.source_lang.clone(),
-            target_lang: self.translation.target_lang.clone(),
-            deeplx_api_url: self.translation.deeplx_api_url.clone(),
+            target_langs: self.translation.target_lang.to_vec(),
+            deeplx_api_url: api_url,
+            api_key: self.translation.api_key.clone(),
+            model: self.translation.model.clone(),
+            memory: self.translation.memory,
+            glossary: self.translation.glossary.clone(),
             max_requests_per_second: self.translation.max_requests_per_second,
             max_text_length: self.translation.max_text_length,
             max_paragraphs_per_request: self.translation.max_paragraphs_per_request,
+            max_concurrent_requests: self.translation.max_concurrent_requests,
+        })
+    }
+
+    pub fn to_url_filter_config(&self) -> UrlFilterConfig {
+        UrlFilterConfig {
+            include_patterns: self.filter.include_patterns.clone(),
+            exclude_patterns: self.filter.exclude_patterns.clone(),
+            include_regex: self.filter.include_regex.clone(),
+            exclude_regex: self.filter.exclude_regex.clone(),
+        }
+    }
+
+    pub fn to_sitemap_config(&self) -> SitemapConfig {
+        SitemapConfig {
+            enabled: self.sitemap.enabled,
+            sitemap_url: self.sitemap.sitemap_url.clone(),
+            use_lastmod: self.sitemap.use_lastmod,
+        }
+    }
+
+    pub fn to_extraction_config(&self) -> ExtractionConfig {
+        ExtractionConfig {
+            enabled: self.extraction.enabled,
+            sites: self.extraction.sites.clone(),
+        }
+    }
+
+    pub fn to_output_config(&self) -> Result<OutputConfig> {
+        Ok(OutputConfig {
+            front_matter: self.output.front_matter,
+            front_matter_fields: self.output.front_matter_fields.clone(),
+            layout: OutputLayout::from_name(&self.output.layout)?,
+            on_existing: ExistingFilePolicy::from_name(&self.output.on_existing)?,
+            rewrite_links: self.output.rewrite_links,
+        })
+    }
+
+    pub fn to_asset_config(&self) -> AssetConfig {
+        AssetConfig {
+            enabled: self.assets.enabled,
+            max_size_mb: self.assets.max_size_mb,
+            allowed_types: self.assets.allowed_types.clone(),
+        }
+    }
+
+    pub fn to_rate_limit_config(&self) -> RateLimitConfig {
+        RateLimitConfig {
+            requests_per_second: self
+                .rate_limit
+                .requests_per_second
+                .unwrap_or(self.translation.max_requests_per_second),
+            burst: self.rate_limit.burst,
+        }
+    }
+
+    pub fn to_retry_policies(&self) -> RetryPolicies {
+        RetryPolicies {
+            crawl: self.retry.crawl.to_retry_config(),
+            convert: self.retry.convert.to_retry_config(),
+            translate: self.retry.translate.to_retry_config(),
+            assets: self.retry.assets.to_retry_config(),
+            page_retries: self.retry.page_retries,
         }
     }
 
@@ -177,19 +600,75 @@ impl ConfigFile {
                 request_delay: 500,
                 timeout: 30,
                 user_agent: "Mozilla/5.0 (compatible; MarkdownDownloader/1.0)".to_string(),
+                max_depth: 0,
+                stay_under_start_path: false,
             },
             translation: TranslationFileConfig {
                 enabled: false,
+                backend: Some("deeplx".to_string()),
                 source_lang: "auto".to_string(),
-                target_lang: "zh".to_string(),
-                deeplx_api_url: "http://localhost:1188/translate".to_string(),
+                target_lang: default_target_lang(),
+                deeplx_api_url: Some("http://localhost:1188/translate".to_string()),
+                api_key: None,
+                model: default_translation_model(),
+                memory: default_translation_memory(),
+                glossary: None,
                 max_requests_per_second: 2.0,
                 max_text_length: 3000,
                 max_paragraphs_per_request: 10,
+                max_concurrent_requests: default_max_concurrent_requests(),
             },
+            filter: FilterFileConfig::default(),
+            sitemap: SitemapFileConfig::default(),
+            converter: ConverterFileConfig::default(),
+            extraction: ExtractionFileConfig::default(),
+            output: OutputFileConfig::default(),
+            assets: AssetsFileConfig::default(),
+            rate_limit: RateLimitFileConfig::default(),
+            retry: RetryFileConfig::default(),
         };
 
         example_config.save_to_file(path)?;
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn translation_config(toml: &str) -> TranslationConfig {
+        let config: ConfigFile = toml::from_str(toml).unwrap();
+        config.to_translation_config().unwrap()
+    }
+
+    #[test]
+    fn backend_defaults_to_deeplx_without_url_sniffing() {
+        let config = translation_config(
+            "[translation]\ndeeplx_api_url = \"https://dptrans.example.com/translate\"\n",
+        );
+        assert_eq!(config.backend, TranslationBackendKind::DeepLX);
+        assert_eq!(
+            config.deeplx_api_url,
+            "https://dptrans.example.com/translate"
+        );
+    }
+
+    #[test]
+    fn missing_url_uses_the_backend_default() {
+        let config = translation_config("[translation]\nbackend = \"deepl\"\n");
+        assert_eq!(
+            config.deeplx_api_url,
+            TranslationBackendKind::DeepL.default_api_url()
+        );
+    }
+
+    #[test]
+    fn explicit_url_is_kept_even_when_it_equals_the_deeplx_default() {
+        let config = translation_config(
+            "[translation]\nbackend = \"openai\"\ndeeplx_api_url = \"http://localhost:1188/translate\"\n",
+        );
+        assert_eq!(config.backend, TranslationBackendKind::OpenAI);
+        assert_eq!(config.deeplx_api_url, "http://localhost:1188/translate");
+    }
+}
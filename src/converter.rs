@@ -1,36 +1,260 @@
+use crate::config::Config;
+use crate::error::{Error, Result};
+use crate::extractor::ContentExtractor;
+use crate::html_to_markdown::HtmlToMarkdown;
+use crate::rate_limiter::{RateLimiter, check_rate_limited, retry_with_backoff};
+use crate::types::RetryConfig;
+use futures::future::BoxFuture;
 use reqwest::Client;
+use std::sync::Arc;
 use std::time::Duration;
-use crate::config::Config;
-use crate::error::Result;
 
-#[derive(Clone)]
-pub struct MarkdownConverter {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConverterBackend {
+    #[default]
+    Jina,
+    Local,
+}
+
+impl ConverterBackend {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "jina" => Ok(ConverterBackend::Jina),
+            "local" => Ok(ConverterBackend::Local),
+            other => Err(format!(
+                "Unknown converter backend: {} (expected \"jina\" or \"local\")",
+                other
+            )
+            .into()),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ConverterBackend::Jina => "jina",
+            ConverterBackend::Local => "local",
+        }
+    }
+}
+
+// 网页 -> Markdown 的转换后端。html 为爬虫已经下载好的页面内容，
+// 后端可以直接复用它，避免再次请求
+pub trait Converter: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn convert<'a>(&'a self, url: &'a str, html: Option<&'a str>) -> BoxFuture<'a, Result<String>>;
+
+    // 本次转换是否会发出网络请求，发出请求的转换才占用限流器的令牌
+    fn fetches(&self, _html: Option<&str>) -> bool {
+        true
+    }
+}
+
+// 通过 r.jina.ai 转换，总是由服务端抓取页面
+pub struct JinaConverter {
     client: Client,
     config: Config,
 }
 
-impl MarkdownConverter {
-    pub fn new(config: Config) -> Self {
-        let client = Client::new();
+impl JinaConverter {
+    pub fn new(client: Client, config: Config) -> Self {
         Self { client, config }
     }
+}
 
-    pub async fn convert_url_to_markdown(&self, url: &str) -> Result<String> {
-        let jina_url = format!("https://r.jina.ai/{}", url);
+impl Converter for JinaConverter {
+    fn name(&self) -> &'static str {
+        "jina"
+    }
 
+    fn convert<'a>(
+        &'a self,
+        url: &'a str,
+        _html: Option<&'a str>,
+    ) -> BoxFuture<'a, Result<String>> {
+        Box::pin(async move {
+            let jina_url = format!("https://r.jina.ai/{}", url);
+
+            let response = self
+                .client
+                .get(&jina_url)
+                .header("User-Agent", &self.config.user_agent)
+                .timeout(Duration::from_secs(self.config.timeout))
+                .send()
+                .await
+                .map_err(|e| Error::network("r.jina.ai", e))?;
+
+            check_rate_limited(&response, "r.jina.ai")?;
+            let status = response.status();
+            if !status.is_success() {
+                let body = response.text().await.unwrap_or_default();
+                return Err(Error::http_status("r.jina.ai", status, &body));
+            }
+
+            let markdown_content = response
+                .text()
+                .await
+                .map_err(|e| Error::network("r.jina.ai", e))?;
+            Ok(markdown_content)
+        })
+    }
+}
+
+// 本地转换：抓取（或复用）页面 HTML，可选地提取正文，再在进程内转换为 Markdown
+pub struct LocalConverter {
+    client: Client,
+    extractor: ContentExtractor,
+    config: Config,
+}
+
+impl LocalConverter {
+    pub fn new(client: Client, config: Config) -> Self {
+        let extractor = ContentExtractor::new(config.extraction.clone());
+        Self {
+            client,
+            extractor,
+            config,
+        }
+    }
+
+    async fn fetch_html(&self, url: &str) -> Result<(String, bool)> {
         let response = self
             .client
-            .get(&jina_url)
+            .get(url)
             .header("User-Agent", &self.config.user_agent)
             .timeout(Duration::from_secs(self.config.timeout))
             .send()
-            .await?;
+            .await
+            .map_err(|e| Error::network(url, e))?;
 
-        if !response.status().is_success() {
-            return Err(format!("HTTP错误: {}", response.status()).into());
+        check_rate_limited(&response, url)?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::http_status(url, status, &body));
         }
 
-        let markdown_content = response.text().await?;
-        Ok(markdown_content)
+        let is_html = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.contains("html"))
+            .unwrap_or(true);
+
+        Ok((
+            response.text().await.map_err(|e| Error::network(url, e))?,
+            is_html,
+        ))
     }
-}
\ No newline at end of file
+}
+
+impl Converter for LocalConverter {
+    fn name(&self) -> &'static str {
+        "local"
+    }
+
+    fn fetches(&self, html: Option<&str>) -> bool {
+        html.is_none_or(|html| html.trim().is_empty())
+    }
+
+    fn convert<'a>(&'a self, url: &'a str, html: Option<&'a str>) -> BoxFuture<'a, Result<String>> {
+        Box::pin(async move {
+            let (content, is_html) = match html {
+                Some(html) if !html.trim().is_empty() => (html.to_string(), true),
+                _ => self.fetch_html(url).await?,
+            };
+
+            // 纯文本 / Markdown 资源原样返回
+            if !is_html {
+                return Ok(content);
+            }
+
+            let content = if self.extractor.is_enabled() {
+                self.extractor.extract(url, &content)
+            } else {
+                content
+            };
+
+            let markdown = HtmlToMarkdown::new(url).convert(&content);
+            if markdown.trim().is_empty() {
+                return Err(Error::rejected(
+                    "local",
+                    format!("no convertible content in {}", url),
+                ));
+            }
+            Ok(markdown)
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct MarkdownConverter {
+    backend: Arc<dyn Converter>,
+    rate_limiter: Option<RateLimiter>,
+    retry_config: RetryConfig,
+}
+
+impl MarkdownConverter {
+    pub fn new(config: Config) -> Self {
+        let client = Client::new();
+        let retry_config = config.retry.convert.clone();
+        let backend: Arc<dyn Converter> = match config.converter {
+            ConverterBackend::Jina => {
+                // r.jina.ai 在服务端抓取并转换页面，正文提取只在 local 后端生效
+                if config.extraction.enabled || !config.extraction.sites.is_empty() {
+                    eprintln!("[extraction] 配置只对 local 转换后端生效，当前后端 jina 会忽略它");
+                }
+                Arc::new(JinaConverter::new(client, config))
+            }
+            ConverterBackend::Local => Arc::new(LocalConverter::new(client, config)),
+        };
+        Self {
+            backend,
+            rate_limiter: None,
+            retry_config,
+        }
+    }
+
+    pub fn with_backend(backend: Arc<dyn Converter>) -> Self {
+        Self {
+            backend,
+            rate_limiter: None,
+            retry_config: RetryConfig::default(),
+        }
+    }
+
+    pub fn with_retry_config(mut self, retry_config: RetryConfig) -> Self {
+        self.retry_config = retry_config;
+        self
+    }
+
+    // 与翻译服务共用的限流器；设置后遇到 429 会按 Retry-After 暂停并重试
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    pub fn backend_name(&self) -> &'static str {
+        self.backend.name()
+    }
+
+    pub async fn convert_url_to_markdown(&self, url: &str) -> Result<String> {
+        self.convert_page(url, None).await
+    }
+
+    pub async fn convert_page(&self, url: &str, html: Option<&str>) -> Result<String> {
+        // 只有需要发起请求时才占用限流令牌
+        let rate_limiter = self
+            .rate_limiter
+            .as_ref()
+            .filter(|_| self.backend.fetches(html));
+
+        // 只重试可重试的错误（限流、超时、5xx 等），404 之类直接返回
+        retry_with_backoff(
+            || self.backend.convert(url, html),
+            &self.retry_config,
+            rate_limiter,
+        )
+        .await
+    }
+}
@@ -1,30 +1,336 @@
-use spider::website::Website;
 use crate::config::Config;
+use crate::converter::ConverterBackend;
 use crate::error::Result;
+use crate::sitemap::{SitemapDiscovery, SitemapEntry};
+use crate::url_filter::UrlFilter;
+use spider::compact_str::CompactString;
+use spider::website::Website;
+use std::collections::{HashSet, VecDeque};
+use tokio::sync::{broadcast, mpsc};
+use url::Url;
+
+// 爬取范围：URL 过滤规则 + 可选的起始路径限制
+#[derive(Clone)]
+struct CrawlScope {
+    url_filter: UrlFilter,
+    start_prefix: Option<String>,
+}
+
+impl CrawlScope {
+    fn contains(&self, url: &str) -> bool {
+        let under_start = match &self.start_prefix {
+            Some(prefix) => Self::is_under_prefix(url, prefix),
+            None => true,
+        };
+        under_start && self.url_filter.is_allowed(url)
+    }
+
+    // prefix 以 / 结尾；不带结尾斜杠的目录本身（https://site/docs/v2）也算在范围内
+    fn is_under_prefix(url: &str, prefix: &str) -> bool {
+        if url.starts_with(prefix) {
+            return true;
+        }
+        let dir = prefix.trim_end_matches('/');
+        url.strip_prefix(dir)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(['?', '#']))
+    }
+}
+
+// 爬虫发现的页面；html 为爬虫已下载的页面内容，供本地转换后端复用
+#[derive(Debug, Clone)]
+pub struct CrawledPage {
+    pub url: String,
+    pub html: Option<String>,
+}
+
+impl CrawledPage {
+    pub fn from_url(url: String) -> Self {
+        Self { url, html: None }
+    }
+}
 
 pub struct Crawler {
     website: Website,
+    scope: CrawlScope,
+    config: Config,
 }
 
 impl Crawler {
     pub fn new(config: &Config) -> Result<Self> {
+        let url_filter = UrlFilter::new(&config.url_filter)?;
+
+        // 排除规则在爬取阶段就剪枝；包含规则只在结果上过滤，
+        // 这样范围外的导航页仍可用于发现范围内的链接
+        let blacklist: Vec<CompactString> = url_filter
+            .exclude_sources()
+            .into_iter()
+            .map(CompactString::from)
+            .collect();
+
+        let start_prefix = if config.stay_under_start_path {
+            Some(Self::start_path_prefix(&config.url)?)
+        } else {
+            None
+        };
+
+        // 限制在起始路径下时，用白名单阻止爬虫离开该路径
+        let whitelist: Option<Vec<CompactString>> = start_prefix.as_ref().map(|prefix| {
+            let dir = regex::escape(prefix.trim_end_matches('/'));
+            vec![CompactString::from(format!("^{}(/|[?#]|$)", dir))]
+        });
+
         let website = Website::new(&config.url)
             .with_respect_robots_txt(true)
             .with_delay(1000) // 1秒延迟
             .with_user_agent(Some(&config.user_agent))
             .with_limit(config.max_pages)
+            // spider 的深度按 URL 路径段计数（/a/b/c 为 3），不是距起始页的链接跳数
+            .with_depth(config.max_depth)
+            .with_retry(config.retry.crawl.max_retries.min(u8::MAX as usize) as u8)
+            .with_blacklist_url(if blacklist.is_empty() {
+                None
+            } else {
+                Some(blacklist)
+            })
+            .with_whitelist_url(whitelist)
             .build()
             .map_err(|e| crate::error::Error::Spider(Box::new(e)))?;
 
-        Ok(Self { website })
+        Ok(Self {
+            website,
+            scope: CrawlScope {
+                url_filter,
+                start_prefix,
+            },
+            config: config.clone(),
+        })
+    }
+
+    // 起始 URL 所在的目录，例如 https://site/docs/v2/intro.html -> https://site/docs/v2/。
+    // 最后一段没有扩展名时按目录处理：https://site/docs/v2 -> https://site/docs/v2/
+    fn start_path_prefix(url: &str) -> Result<String> {
+        let mut parsed = Url::parse(url)?;
+        parsed.set_query(None);
+        parsed.set_fragment(None);
+
+        let path = parsed.path().to_string();
+        let last_segment = path.rsplit('/').next().unwrap_or_default();
+        let dir = if !last_segment.is_empty() && !last_segment.contains('.') {
+            format!("{}/", path)
+        } else {
+            match path.rfind('/') {
+                Some(index) => path[..=index].to_string(),
+                None => "/".to_string(),
+            }
+        };
+        parsed.set_path(&dir);
+
+        Ok(parsed.to_string())
     }
 
     pub async fn crawl(&mut self) -> Result<Vec<String>> {
         self.website.scrape().await;
-        
+
         let links = self.website.get_links();
         let urls: Vec<String> = links.iter().map(|link| link.as_ref().to_string()).collect();
-        
-        Ok(urls)
+
+        Ok(urls
+            .into_iter()
+            .filter(|url| self.scope.contains(url))
+            .collect())
+    }
+
+    // 边爬取边输出：每发现一个页面就通过有界通道交给下游处理，
+    // 爬取结束（或下游关闭接收端）时通道关闭
+    pub fn crawl_stream(self, capacity: usize) -> Result<mpsc::Receiver<CrawledPage>> {
+        let Crawler {
+            mut website,
+            scope,
+            config,
+        } = self;
+        // 只有本地转换后端会用到页面 HTML
+        let keep_html = config.converter == ConverterBackend::Local;
+
+        let mut pages = website.subscribe(capacity).ok_or_else(|| {
+            crate::error::Error::Custom("Failed to subscribe to crawler".to_string())
+        })?;
+
+        let crawl_task = tokio::spawn(async move {
+            website.crawl().await;
+            website.unsubscribe();
+            website.get_links()
+        });
+
+        let (tx, rx) = mpsc::channel(capacity);
+
+        tokio::spawn(async move {
+            let mut seen = HashSet::new();
+            // spider 的广播通道不等待慢速接收者，下游积压时先暂存在这里，避免丢失页面；
+            // 最多暂存 capacity 个，超出的页面不记入 seen，由爬取结束后的补发处理
+            let mut pending = VecDeque::with_capacity(capacity);
+            let mut deferred = 0usize;
+            let mut crawl_finished = false;
+
+            loop {
+                tokio::select! {
+                    page = pages.recv(), if !crawl_finished => match page {
+                        Ok(page) => {
+                            let url = page.get_url().to_string();
+                            if !scope.contains(&url) || seen.contains(&url) {
+                                continue;
+                            }
+                            if pending.len() >= capacity {
+                                deferred += 1;
+                                continue;
+                            }
+                            seen.insert(url.clone());
+                            let html = keep_html.then(|| page.get_html());
+                            pending.push_back(CrawledPage { url, html });
+                        }
+                        Err(broadcast::error::RecvError::Lagged(count)) => {
+                            eprintln!("爬虫结果积压，{} 个页面将在爬取结束后补发", count);
+                        }
+                        Err(broadcast::error::RecvError::Closed) => crawl_finished = true,
+                    },
+                    permit = tx.reserve(), if !pending.is_empty() => match permit {
+                        Ok(permit) => {
+                            if let Some(page) = pending.pop_front() {
+                                permit.send(page);
+                            }
+                        }
+                        Err(_) => return,
+                    },
+                    else => break,
+                }
+            }
+
+            if deferred > 0 {
+                eprintln!("处理队列已满，{} 个页面将在爬取结束后补发", deferred);
+            }
+
+            // 补发广播中遗漏或因队列已满暂缓的链接
+            match crawl_task.await {
+                Ok(links) => {
+                    for link in links {
+                        let url = link.as_ref().to_string();
+                        if scope.contains(&url)
+                            && seen.insert(url.clone())
+                            && tx.send(CrawledPage::from_url(url)).await.is_err()
+                        {
+                            return;
+                        }
+                    }
+                }
+                Err(e) => eprintln!("爬虫任务异常结束: {}", e),
+            }
+        });
+
+        Ok(rx)
     }
-}
\ No newline at end of file
+
+    // 从 robots.txt / sitemap.xml 获取页面列表，只保留同一主机且在范围内的页面
+    pub async fn crawl_sitemap(&self) -> Result<Vec<SitemapEntry>> {
+        let discovery = SitemapDiscovery::new(&self.config);
+        let entries = discovery
+            .discover(&self.config.url, self.config.sitemap.sitemap_url.as_deref())
+            .await?;
+
+        let host = Url::parse(&self.config.url)?
+            .host_str()
+            .map(|h| h.to_string());
+
+        let entries: Vec<SitemapEntry> = entries
+            .into_iter()
+            .filter(|entry| {
+                Url::parse(&entry.url)
+                    .map(|url| url.host_str().map(|h| h.to_string()) == host)
+                    .unwrap_or(false)
+            })
+            .filter(|entry| self.scope.contains(&entry.url))
+            .take(self.config.max_pages as usize)
+            .collect();
+
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn start_prefix_of_a_file_is_its_directory() {
+        assert_eq!(
+            Crawler::start_path_prefix("https://site.com/docs/v2/intro.html?x=1#top").unwrap(),
+            "https://site.com/docs/v2/"
+        );
+        assert_eq!(
+            Crawler::start_path_prefix("https://site.com/docs/v2/").unwrap(),
+            "https://site.com/docs/v2/"
+        );
+        assert_eq!(
+            Crawler::start_path_prefix("https://site.com").unwrap(),
+            "https://site.com/"
+        );
+    }
+
+    #[test]
+    fn start_prefix_without_trailing_slash_is_treated_as_a_directory() {
+        assert_eq!(
+            Crawler::start_path_prefix("https://site.com/docs/v2").unwrap(),
+            "https://site.com/docs/v2/"
+        );
+    }
+
+    #[test]
+    fn scope_stays_under_the_start_directory() {
+        let scope = CrawlScope {
+            url_filter: UrlFilter::new(&Default::default()).unwrap(),
+            start_prefix: Some(Crawler::start_path_prefix("https://site.com/docs/v2").unwrap()),
+        };
+
+        assert!(scope.contains("https://site.com/docs/v2"));
+        assert!(scope.contains("https://site.com/docs/v2?lang=en"));
+        assert!(scope.contains("https://site.com/docs/v2/guide/setup"));
+        assert!(!scope.contains("https://site.com/docs/v3/guide"));
+        assert!(!scope.contains("https://site.com/docs/v2beta"));
+        assert!(!scope.contains("https://site.com/docs/"));
+    }
+
+    fn config(url: &str) -> Config {
+        Config::new(url.to_string(), "./downloads".to_string(), 50, 10, 60)
+    }
+
+    #[test]
+    fn max_depth_is_passed_to_spider_as_path_depth() {
+        let mut config = config("https://site.com/docs/");
+        config.max_depth = 3;
+        let crawler = Crawler::new(&config).unwrap();
+
+        assert_eq!(crawler.website.configuration.depth, 3);
+    }
+
+    #[test]
+    fn stay_under_start_path_restricts_the_crawl_and_the_results() {
+        let mut config = config("https://site.com/docs/v2/intro.html");
+        let unrestricted = Crawler::new(&config).unwrap();
+        assert!(unrestricted.website.configuration.whitelist_url.is_none());
+        assert!(unrestricted.scope.contains("https://site.com/blog/"));
+
+        config.stay_under_start_path = true;
+        let restricted = Crawler::new(&config).unwrap();
+        let whitelist = restricted
+            .website
+            .configuration
+            .whitelist_url
+            .as_ref()
+            .unwrap();
+        assert_eq!(whitelist.len(), 1);
+
+        let pattern = regex::Regex::new(whitelist[0].as_str()).unwrap();
+        assert!(pattern.is_match("https://site.com/docs/v2/guide"));
+        assert!(!pattern.is_match("https://site.com/docs/v2beta"));
+        assert!(restricted.scope.contains("https://site.com/docs/v2/guide"));
+        assert!(!restricted.scope.contains("https://site.com/blog/"));
+    }
+}
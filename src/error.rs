@@ -1,4 +1,9 @@
 use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+// 错误响应正文在错误信息中最多保留的字符数
+const MAX_BODY_CHARS: usize = 200;
 
 #[derive(Debug)]
 pub enum Error {
@@ -8,6 +13,137 @@ pub enum Error {
     Parse(url::ParseError),
     ParseInt(std::num::ParseIntError),
     Custom(String),
+    // 非 2xx 响应（429 除外）
+    HttpStatus {
+        service: String,
+        status: u16,
+        body: String,
+    },
+    // HTTP 429，retry_after 来自响应的 Retry-After 头
+    RateLimited {
+        service: String,
+        retry_after: Option<Duration>,
+    },
+    Timeout {
+        service: String,
+    },
+    // 响应无法解析或不符合接口约定
+    Protocol {
+        service: String,
+        message: String,
+    },
+    // 请求成功但没有返回内容
+    EmptyResult {
+        service: String,
+    },
+    // 内容不符合要求（类型不允许、超过大小限制、没有可转换的内容），重试结果相同
+    Rejected {
+        service: String,
+        reason: String,
+    },
+    Filesystem {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+}
+
+impl Error {
+    pub fn http_status(service: &str, status: reqwest::StatusCode, body: &str) -> Self {
+        Error::HttpStatus {
+            service: service.to_string(),
+            status: status.as_u16(),
+            body: body.chars().take(MAX_BODY_CHARS).collect(),
+        }
+    }
+
+    pub fn protocol(service: &str, message: impl Into<String>) -> Self {
+        Error::Protocol {
+            service: service.to_string(),
+            message: message.into(),
+        }
+    }
+
+    // 响应体无法解析；与 http_status 一样只保留响应体开头
+    pub fn invalid_response(service: &str, error: impl std::fmt::Display, body: &str) -> Self {
+        let body: String = body.chars().take(MAX_BODY_CHARS).collect();
+        Error::protocol(service, format!("{} - {}", error, body))
+    }
+
+    pub fn empty_result(service: &str) -> Self {
+        Error::EmptyResult {
+            service: service.to_string(),
+        }
+    }
+
+    pub fn rejected(service: &str, reason: impl Into<String>) -> Self {
+        Error::Rejected {
+            service: service.to_string(),
+            reason: reason.into(),
+        }
+    }
+
+    pub fn filesystem(path: &Path, source: std::io::Error) -> Self {
+        Error::Filesystem {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+
+    // 网络错误按服务名归类，超时单独区分
+    pub fn network(service: &str, error: reqwest::Error) -> Self {
+        if error.is_timeout() {
+            Error::Timeout {
+                service: service.to_string(),
+            }
+        } else {
+            Error::Http(error)
+        }
+    }
+
+    // 重试可能成功的错误：限流、超时、连接失败、服务端 5xx 以及空结果；
+    // 4xx、解析错误、被拒绝的内容、配置错误和文件系统错误重试也不会改变结果
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::RateLimited { .. } | Error::Timeout { .. } | Error::EmptyResult { .. } => true,
+            Error::HttpStatus { status, .. } => Self::is_retryable_status(*status),
+            Error::Http(e) => {
+                !e.is_builder()
+                    && !e.is_decode()
+                    && e.status()
+                        .is_none_or(|status| Self::is_retryable_status(status.as_u16()))
+            }
+            Error::Io(_)
+            | Error::Spider(_)
+            | Error::Parse(_)
+            | Error::ParseInt(_)
+            | Error::Custom(_)
+            | Error::Protocol { .. }
+            | Error::Rejected { .. }
+            | Error::Filesystem { .. } => false,
+        }
+    }
+
+    // 错误类型名，记录在链接状态中，用于按类型筛选和统计失败
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::Io(_) => "io",
+            Error::Http(_) => "network",
+            Error::Spider(_) => "crawl",
+            Error::Parse(_) | Error::ParseInt(_) => "parse",
+            Error::Custom(_) => "other",
+            Error::HttpStatus { .. } => "http_status",
+            Error::RateLimited { .. } => "rate_limited",
+            Error::Timeout { .. } => "timeout",
+            Error::Protocol { .. } => "protocol",
+            Error::EmptyResult { .. } => "empty_result",
+            Error::Rejected { .. } => "rejected",
+            Error::Filesystem { .. } => "filesystem",
+        }
+    }
+
+    fn is_retryable_status(status: u16) -> bool {
+        matches!(status, 408 | 425 | 429) || (500..600).contains(&status)
+    }
 }
 
 impl fmt::Display for Error {
@@ -19,11 +155,57 @@ impl fmt::Display for Error {
             Error::Parse(e) => write!(f, "URL parse error: {}", e),
             Error::ParseInt(e) => write!(f, "Parse int error: {}", e),
             Error::Custom(msg) => write!(f, "{}", msg),
+            Error::HttpStatus {
+                service,
+                status,
+                body,
+            } if body.is_empty() => {
+                write!(f, "{} returned HTTP {}", service, status)
+            }
+            Error::HttpStatus {
+                service,
+                status,
+                body,
+            } => write!(f, "{} returned HTTP {}: {}", service, status, body),
+            Error::RateLimited {
+                service,
+                retry_after: Some(delay),
+            } => {
+                write!(
+                    f,
+                    "{} rate limited (HTTP 429), retry after {}s",
+                    service,
+                    delay.as_secs()
+                )
+            }
+            Error::RateLimited {
+                service,
+                retry_after: None,
+            } => write!(f, "{} rate limited (HTTP 429)", service),
+            Error::Timeout { service } => write!(f, "{} request timed out", service),
+            Error::Protocol { service, message } => {
+                write!(f, "Unexpected {} response: {}", service, message)
+            }
+            Error::EmptyResult { service } => write!(f, "{} returned an empty result", service),
+            Error::Rejected { service, reason } => write!(f, "{} rejected: {}", service, reason),
+            Error::Filesystem { path, source } => {
+                write!(f, "Filesystem error at {}: {}", path.display(), source)
+            }
         }
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) | Error::Filesystem { source: e, .. } => Some(e),
+            Error::Http(e) => Some(e),
+            Error::Parse(e) => Some(e),
+            Error::ParseInt(e) => Some(e),
+            _ => None,
+        }
+    }
+}
 
 impl From<std::io::Error> for Error {
     fn from(error: std::io::Error) -> Self {
@@ -67,4 +249,88 @@ impl From<&str> for Error {
     }
 }
 
-pub type Result<T> = std::result::Result<T, Error>;
\ No newline at end of file
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::StatusCode;
+
+    fn io_error() -> std::io::Error {
+        std::io::Error::new(std::io::ErrorKind::NotFound, "missing")
+    }
+
+    #[test]
+    fn every_variant_has_a_kind_and_retry_class() {
+        let builder_error = reqwest::Client::new().get("not a url").build().unwrap_err();
+        let service = || "svc".to_string();
+
+        let cases: Vec<(Error, &str, bool)> = vec![
+            (Error::Io(io_error()), "io", false),
+            (Error::Http(builder_error), "network", false),
+            (Error::Spider("crawl failed".into()), "crawl", false),
+            (Error::Parse(url::ParseError::EmptyHost), "parse", false),
+            (
+                Error::ParseInt("x".parse::<u32>().unwrap_err()),
+                "parse",
+                false,
+            ),
+            (Error::Custom("bad config".into()), "other", false),
+            (
+                Error::http_status("svc", StatusCode::NOT_FOUND, ""),
+                "http_status",
+                false,
+            ),
+            (
+                Error::http_status("svc", StatusCode::REQUEST_TIMEOUT, ""),
+                "http_status",
+                true,
+            ),
+            (
+                Error::http_status("svc", StatusCode::BAD_GATEWAY, ""),
+                "http_status",
+                true,
+            ),
+            (
+                Error::RateLimited {
+                    service: service(),
+                    retry_after: None,
+                },
+                "rate_limited",
+                true,
+            ),
+            (Error::Timeout { service: service() }, "timeout", true),
+            (Error::protocol("svc", "bad json"), "protocol", false),
+            (Error::empty_result("svc"), "empty_result", true),
+            (Error::rejected("svc", "too large"), "rejected", false),
+            (
+                Error::filesystem(Path::new("out"), io_error()),
+                "filesystem",
+                false,
+            ),
+        ];
+
+        for (error, kind, retryable) in cases {
+            assert_eq!(error.kind(), kind, "{}", error);
+            assert_eq!(error.is_retryable(), retryable, "{}", error);
+        }
+    }
+
+    #[test]
+    fn response_bodies_are_truncated() {
+        let body = "x".repeat(1000);
+        match Error::http_status("svc", StatusCode::BAD_REQUEST, &body) {
+            Error::HttpStatus { body, .. } => assert_eq!(body.chars().count(), MAX_BODY_CHARS),
+            other => panic!("unexpected error: {:?}", other),
+        }
+        match Error::invalid_response("svc", "expected value", &body) {
+            Error::Protocol { message, .. } => {
+                assert_eq!(
+                    message,
+                    format!("expected value - {}", "x".repeat(MAX_BODY_CHARS))
+                )
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+}
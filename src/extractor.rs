@@ -0,0 +1,446 @@
+use ego_tree::NodeId;
+use regex::Regex;
+use scraper::{ElementRef, Html, Node, Selector};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use url::Url;
+
+// 无论哪种模式都会删除的元素
+const ALWAYS_DROP: &[&str] = &["script", "style", "noscript", "template", "iframe"];
+
+// 可读性评分模式下默认删除的页面框架元素：导航、侧边栏、页脚、Cookie 提示等
+const BOILERPLATE_DROP: &[&str] = &[
+    "nav",
+    "footer",
+    "aside",
+    "form",
+    "[role=navigation]",
+    "[role=banner]",
+    "[role=contentinfo]",
+    "[role=complementary]",
+    "[aria-hidden=true]",
+    "[id*=cookie]",
+    "[class*=cookie]",
+    "[id*=consent]",
+    "[class*=consent]",
+];
+
+// <header> 在这些正文容器里通常是文章自己的标题区，只删除容器之外的
+const CONTENT_ROOTS: &[&str] = &["article", "main", "[role=main]"];
+
+// 会被打分的正文段落类元素
+const SCORED_TAGS: &[&str] = &["p", "pre", "td", "blockquote", "li"];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SiteSelectors {
+    #[serde(default)]
+    pub keep: Vec<String>,
+    #[serde(default)]
+    pub drop: Vec<String>,
+}
+
+// sites 的键为域名；"example.com" 同时匹配其子域名，"*" 对所有站点生效
+#[derive(Debug, Clone, Default)]
+pub struct ExtractionConfig {
+    pub enabled: bool,
+    pub sites: HashMap<String, SiteSelectors>,
+}
+
+#[derive(Clone)]
+pub struct ContentExtractor {
+    config: ExtractionConfig,
+    positive: Regex,
+    negative: Regex,
+}
+
+impl ContentExtractor {
+    pub fn new(config: ExtractionConfig) -> Self {
+        Self {
+            config,
+            positive: Regex::new(r"(?i)article|body|content|entry|main|page|post|text|blog|story|doc")
+                .expect("valid regex"),
+            negative: Regex::new(r"(?i)banner|breadcrumb|combx|comment|community|cookie|consent|footer|footnote|header|menu|meta|nav|pager|pagination|popup|related|share|shoutbox|sidebar|skyscraper|social|sponsor|toc|tool|widget")
+                .expect("valid regex"),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    // 返回只包含正文的 HTML 文档（保留 <title>）
+    pub fn extract(&self, url: &str, html: &str) -> String {
+        let mut document = Html::parse_document(html);
+        let selectors = self.selectors_for(url);
+
+        Self::drop_matching(&mut document, ALWAYS_DROP.iter().copied());
+        Self::drop_matching(&mut document, selectors.drop.iter().map(|s| s.as_str()));
+
+        let matched: Vec<NodeId> = selectors
+            .keep
+            .iter()
+            .filter_map(|selector| Self::parse_selector(selector))
+            .flat_map(|selector| {
+                document
+                    .root_element()
+                    .select(&selector)
+                    .map(|e| e.id())
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        // 去掉重复的匹配，以及嵌套在其他匹配里的节点，避免同一段内容输出两次
+        let matched_set: HashSet<NodeId> = matched.iter().copied().collect();
+        let mut seen = HashSet::new();
+        let mut content: Vec<NodeId> = matched
+            .into_iter()
+            .filter(|id| {
+                let nested = document.tree.get(*id).is_some_and(|node| {
+                    node.ancestors()
+                        .any(|ancestor| matched_set.contains(&ancestor.id()))
+                });
+                !nested && seen.insert(*id)
+            })
+            .collect();
+
+        // 没有配置 keep 选择器（或都没有匹配）时按可读性评分选择正文
+        if content.is_empty() {
+            Self::drop_matching(&mut document, BOILERPLATE_DROP.iter().copied());
+            Self::drop_site_headers(&mut document);
+            content = self.score_content(&document);
+        }
+
+        let body: String = content
+            .iter()
+            .filter_map(|id| document.tree.get(*id).and_then(ElementRef::wrap))
+            .map(|element| element.html())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        if body.trim().is_empty() {
+            return html.to_string();
+        }
+
+        let title = Selector::parse("title")
+            .ok()
+            .and_then(|selector| document.select(&selector).next().map(|t| t.html()))
+            .unwrap_or_default();
+
+        format!("<html><head>{}</head><body>{}</body></html>", title, body)
+    }
+
+    fn selectors_for(&self, url: &str) -> SiteSelectors {
+        let host = Url::parse(url)
+            .ok()
+            .and_then(|u| u.host_str().map(|h| h.to_lowercase()))
+            .unwrap_or_default();
+
+        let mut merged = SiteSelectors::default();
+        for (domain, selectors) in &self.config.sites {
+            let domain = domain.to_lowercase();
+            let matches =
+                domain == "*" || host == domain || host.ends_with(&format!(".{}", domain));
+            if matches {
+                merged.keep.extend(selectors.keep.iter().cloned());
+                merged.drop.extend(selectors.drop.iter().cloned());
+            }
+        }
+        merged
+    }
+
+    fn parse_selector(selector: &str) -> Option<Selector> {
+        match Selector::parse(selector) {
+            Ok(selector) => Some(selector),
+            Err(e) => {
+                eprintln!("无效的 CSS 选择器 '{}': {}", selector, e);
+                None
+            }
+        }
+    }
+
+    fn drop_matching<'a>(document: &mut Html, selectors: impl Iterator<Item = &'a str>) {
+        for selector in selectors {
+            let Some(selector) = Self::parse_selector(selector) else {
+                continue;
+            };
+            // 只在仍挂在文档上的节点中查找，已删除的节点不再匹配
+            let ids: Vec<NodeId> = document
+                .root_element()
+                .select(&selector)
+                .map(|e| e.id())
+                .collect();
+            for id in ids {
+                if let Some(mut node) = document.tree.get_mut(id) {
+                    node.detach();
+                }
+            }
+        }
+    }
+
+    // 删除正文容器之外的 <header>（站点页眉），保留文章自己的标题区
+    fn drop_site_headers(document: &mut Html) {
+        let roots: Vec<Selector> = CONTENT_ROOTS
+            .iter()
+            .filter_map(|s| Self::parse_selector(s))
+            .collect();
+        let ids: Vec<NodeId> = Selector::parse("header")
+            .ok()
+            .map(|selector| {
+                document
+                    .root_element()
+                    .select(&selector)
+                    .filter(|header| {
+                        !header
+                            .ancestors()
+                            .filter_map(ElementRef::wrap)
+                            .any(|ancestor| roots.iter().any(|root| root.matches(&ancestor)))
+                    })
+                    .map(|header| header.id())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        for id in ids {
+            if let Some(mut node) = document.tree.get_mut(id) {
+                node.detach();
+            }
+        }
+    }
+
+    // Readability 风格的打分：段落的分数累加到父元素（祖父元素得一半），
+    // 再按链接密度折减，取得分最高的元素及其高分兄弟元素
+    fn score_content(&self, document: &Html) -> Vec<NodeId> {
+        let mut scores: HashMap<NodeId, f64> = HashMap::new();
+
+        for node in document.root_element().descendants() {
+            let Some(element) = ElementRef::wrap(node) else {
+                continue;
+            };
+            if !SCORED_TAGS.contains(&element.value().name()) {
+                continue;
+            }
+
+            let text: String = element.text().collect();
+            let text = text.trim();
+            if text.chars().count() < 25 {
+                continue;
+            }
+
+            let content_score = 1.0
+                + text.matches([',', '，']).count() as f64
+                + (text.chars().count() as f64 / 100.0).min(3.0);
+
+            let parent = node.parent().and_then(ElementRef::wrap);
+            let grandparent = parent.and_then(|p| p.parent()).and_then(ElementRef::wrap);
+
+            for (ancestor, share) in [(parent, 1.0), (grandparent, 0.5)] {
+                if let Some(ancestor) = ancestor {
+                    let entry = scores
+                        .entry(ancestor.id())
+                        .or_insert_with(|| self.initial_score(ancestor));
+                    *entry += content_score * share;
+                }
+            }
+        }
+
+        let scored: Vec<(NodeId, f64)> = scores
+            .into_iter()
+            .filter_map(|(id, score)| {
+                let element = document.tree.get(id).and_then(ElementRef::wrap)?;
+                Some((id, score * (1.0 - Self::link_density(element))))
+            })
+            .collect();
+
+        let Some(&(top_id, top_score)) = scored
+            .iter()
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+        else {
+            return Self::fallback_body(document);
+        };
+
+        // 与最佳候选同级、分数足够高的兄弟元素一并保留
+        let threshold = (top_score * 0.2).max(10.0);
+        let Some(top) = document.tree.get(top_id) else {
+            return Self::fallback_body(document);
+        };
+        let Some(parent) = top.parent() else {
+            return vec![top_id];
+        };
+
+        parent
+            .children()
+            .filter(|sibling| {
+                sibling.id() == top_id
+                    || scored
+                        .iter()
+                        .any(|(id, score)| *id == sibling.id() && *score >= threshold)
+            })
+            .map(|sibling| sibling.id())
+            .collect()
+    }
+
+    fn initial_score(&self, element: ElementRef) -> f64 {
+        let mut score = match element.value().name() {
+            "article" | "main" => 10.0,
+            "div" | "section" => 5.0,
+            "pre" | "td" | "blockquote" => 3.0,
+            "address" | "ol" | "ul" | "dl" | "dd" | "dt" | "li" | "form" => -3.0,
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th" => -5.0,
+            _ => 0.0,
+        };
+
+        for value in [element.value().attr("class"), element.value().id()]
+            .into_iter()
+            .flatten()
+        {
+            if self.negative.is_match(value) {
+                score -= 25.0;
+            }
+            if self.positive.is_match(value) {
+                score += 25.0;
+            }
+        }
+
+        score
+    }
+
+    fn link_density(element: ElementRef) -> f64 {
+        let text_length = element.text().map(|t| t.trim().len()).sum::<usize>();
+        if text_length == 0 {
+            return 0.0;
+        }
+
+        let link_length: usize = element
+            .descendants()
+            .filter_map(ElementRef::wrap)
+            .filter(|e| e.value().name() == "a")
+            .map(|a| a.text().map(|t| t.trim().len()).sum::<usize>())
+            .sum();
+
+        (link_length as f64 / text_length as f64).min(1.0)
+    }
+
+    fn fallback_body(document: &Html) -> Vec<NodeId> {
+        document
+            .root_element()
+            .descendants()
+            .find(|node| matches!(node.value(), Node::Element(e) if e.name() == "body"))
+            .map(|node| vec![node.id()])
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARTICLE: &str =
+        "This paragraph is long enough to be scored, and it has commas, too, so it counts.";
+
+    fn extractor(sites: &[(&str, &[&str], &[&str])]) -> ContentExtractor {
+        let sites = sites
+            .iter()
+            .map(|(domain, keep, drop)| {
+                let selectors = SiteSelectors {
+                    keep: keep.iter().map(|s| s.to_string()).collect(),
+                    drop: drop.iter().map(|s| s.to_string()).collect(),
+                };
+                (domain.to_string(), selectors)
+            })
+            .collect();
+        ContentExtractor::new(ExtractionConfig {
+            enabled: true,
+            sites,
+        })
+    }
+
+    fn page(body: &str) -> String {
+        format!(
+            "<html><head><title>Page</title></head><body>{}</body></html>",
+            body
+        )
+    }
+
+    #[test]
+    fn scoring_picks_the_article_over_navigation() {
+        let html = page(&format!(
+            r#"<header><a href="/">Site name</a></header>
+               <nav><ul><li><a href="/a">A long navigation link, with commas, here</a></li></ul></nav>
+               <div class="sidebar"><p>{0}</p></div>
+               <div class="content"><p>{0}</p><p>{0}</p><p>{0}</p></div>
+               <footer><p>{0}</p></footer>"#,
+            ARTICLE
+        ));
+
+        let extracted = extractor(&[]).extract("https://example.com/post", &html);
+        assert!(extracted.contains(r#"<div class="content">"#));
+        assert!(extracted.contains("<title>Page</title>"));
+        assert!(!extracted.contains("sidebar"));
+        assert!(!extracted.contains("Site name"));
+        assert!(!extracted.contains("<nav>"));
+        assert!(!extracted.contains("<footer>"));
+    }
+
+    #[test]
+    fn link_heavy_blocks_lose_to_prose() {
+        let links = format!(r#"<p><a href="/x">{}</a></p>"#, ARTICLE).repeat(4);
+        let html = page(&format!(
+            r#"<div id="links">{}</div><div id="story"><p>{1}</p><p>{1}</p></div>"#,
+            links, ARTICLE
+        ));
+
+        let extracted = extractor(&[]).extract("https://example.com/post", &html);
+        assert!(extracted.contains(r#"id="story""#));
+        assert!(!extracted.contains(r#"id="links""#));
+    }
+
+    #[test]
+    fn article_header_is_kept() {
+        let html = page(&format!(
+            "<header>Site banner</header><article><header><h1>Article title</h1></header><p>{0}</p><p>{0}</p></article>",
+            ARTICLE
+        ));
+
+        let extracted = extractor(&[]).extract("https://example.com/post", &html);
+        assert!(extracted.contains("<h1>Article title</h1>"));
+        assert!(!extracted.contains("Site banner"));
+    }
+
+    #[test]
+    fn site_keep_and_drop_selectors_apply_to_the_domain_and_subdomains() {
+        let html = page(
+            r#"<div class="doc"><p>Kept text</p><span class="edit">Edit this page</span></div><p>Other</p>"#,
+        );
+        let extractor = extractor(&[("example.com", &[".doc"], &[".edit"])]);
+
+        let extracted = extractor.extract("https://docs.example.com/page", &html);
+        assert!(extracted.contains("Kept text"));
+        assert!(!extracted.contains("Edit this page"));
+        assert!(!extracted.contains("Other"));
+
+        // 其他站点不使用这些选择器
+        let other = extractor.extract("https://example.org/page", &html);
+        assert!(other.contains("Edit this page"));
+    }
+
+    #[test]
+    fn nested_keep_matches_are_emitted_once() {
+        let html = page(r#"<main><section class="body"><p>Only once</p></section></main>"#);
+        let extractor = extractor(&[("*", &["main", ".body", "main"], &[])]);
+
+        let extracted = extractor.extract("https://example.com/page", &html);
+        assert_eq!(extracted.matches("Only once").count(), 1);
+    }
+
+    #[test]
+    fn unmatched_keep_selectors_fall_back_to_scoring() {
+        let html = page(&format!(
+            r#"<div class="post"><p>{0}</p><p>{0}</p></div>"#,
+            ARTICLE
+        ));
+        let extractor = extractor(&[("example.com", &["#missing"], &[])]);
+
+        let extracted = extractor.extract("https://example.com/page", &html);
+        assert!(extracted.contains(r#"<div class="post">"#));
+    }
+}
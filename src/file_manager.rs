@@ -1,8 +1,8 @@
+use crate::error::Result;
+use chrono::Utc;
 use std::fs;
 use std::path::Path;
 use url::Url;
-use chrono::Utc;
-use crate::error::Result;
 
 pub struct FileManager {
     output_dir: String,
@@ -17,7 +17,7 @@ impl FileManager {
     pub fn save_markdown(&self, url: &str, content: &str) -> Result<String> {
         let filename = self.generate_filename(url)?;
         let filepath = Path::new(&self.output_dir).join(&filename);
-        
+
         fs::write(&filepath, content)?;
         Ok(filename)
     }
@@ -32,13 +32,7 @@ impl FileManager {
         let date = now.format("%Y%m%d").to_string();
 
         // 清理路径，替换特殊字符
-        let temp_path = path
-            .replace('/', "_")
-            .replace('\\', "_")
-            .replace('?', "_")
-            .replace('&', "_")
-            .replace('=', "_")
-            .replace('#', "_");
+        let temp_path = path.replace(['/', '\\', '?', '&', '=', '#'], "_");
 
         let clean_path = temp_path.trim_matches('_');
 
@@ -66,4 +60,4 @@ impl FileManager {
             }
         ))
     }
-}
\ No newline at end of file
+}
@@ -1,13 +1,23 @@
-use crate::types::{FileType, TranslationConfig};
-use crate::error::Result;
+use crate::assets::LocalAssets;
+use crate::error::{Error, Result};
+use crate::link_rewriter::LinkRewriter;
+use crate::types::{
+    ExistingFilePolicy, FileType, OutputConfig, OutputLayout, PageMetadata, TranslationConfig,
+};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use url::Url;
 
 #[derive(Clone)]
 pub struct FolderManager {
     base_dir: PathBuf,
     translation_config: TranslationConfig,
+    output_config: OutputConfig,
+    // 之前运行保存的页面的链接改写规则，用于判断已改写过链接的旧文件内容是否变化
+    link_rewriter: Option<Arc<LinkRewriter>>,
 }
 
 impl FolderManager {
@@ -15,35 +25,79 @@ impl FolderManager {
         Self {
             base_dir,
             translation_config,
+            output_config: OutputConfig::default(),
+            link_rewriter: None,
         }
     }
 
-    pub fn get_folder_path(&self, url: &str, file_type: FileType) -> Result<PathBuf> {
-        let domain = self.extract_domain(url)?;
-        
-        let folder_name = if self.translation_config.enabled {
-            format!("{}_{}-{}", 
-                domain, 
-                self.translation_config.source_lang, 
-                self.translation_config.target_lang
-            )
-        } else {
-            domain
-        };
+    pub fn with_output_config(mut self, output_config: OutputConfig) -> Self {
+        self.output_config = output_config;
+        self
+    }
+
+    pub fn with_link_rewriter(mut self, link_rewriter: Arc<LinkRewriter>) -> Self {
+        self.link_rewriter = Some(link_rewriter);
+        self
+    }
+
+    fn get_site_folder(&self, url: &str) -> Result<PathBuf> {
+        Ok(self.output_folder(&self.extract_domain(url)?))
+    }
+
+    // 输出文件夹：<base>/<name> 或翻译模式下的 <base>/<name>_<源语言>-<目标语言>；
+    // 多个目标语言时为 <base>/<name>_<源语言>，各语言的译文在 translated/<语言> 下。
+    // name 为站点域名或本地目录名
+    fn output_folder(&self, name: &str) -> PathBuf {
+        let folder_name =
+            if self.translation_config.enabled && self.translation_config.is_multilingual() {
+                format!("{}_{}", name, self.translation_config.source_lang)
+            } else if self.translation_config.enabled {
+                format!(
+                    "{}_{}-{}",
+                    name,
+                    self.translation_config.source_lang,
+                    self.translation_config.target_lang()
+                )
+            } else {
+                name.to_string()
+            };
 
         let mut path = self.base_dir.clone();
         path.push(folder_name);
-        
+        path
+    }
+
+    pub fn get_folder_path(&self, url: &str, file_type: FileType) -> Result<PathBuf> {
+        self.get_language_folder_path(url, file_type, self.translation_config.target_lang())
+    }
+
+    pub fn get_language_folder_path(
+        &self,
+        url: &str,
+        file_type: FileType,
+        lang: &str,
+    ) -> Result<PathBuf> {
+        Ok(self.type_folder(self.get_site_folder(url)?, file_type, lang))
+    }
+
+    fn type_folder(&self, mut path: PathBuf, file_type: FileType, lang: &str) -> PathBuf {
         if self.translation_config.enabled {
             path.push(file_type.folder_name());
+            if self.translation_config.is_multilingual() && file_type != FileType::Original {
+                path.push(lang);
+            }
         }
+        path
+    }
 
-        Ok(path)
+    // 图片等页面资源保存在站点文件夹下的 assets 目录，原文、译文和双语文件共用
+    pub fn get_assets_folder(&self, url: &str) -> Result<PathBuf> {
+        Ok(self.get_site_folder(url)?.join("assets"))
     }
 
     pub fn ensure_folder_exists(&self, folder_path: &Path) -> Result<()> {
         if !folder_path.exists() {
-            fs::create_dir_all(folder_path)?;
+            fs::create_dir_all(folder_path).map_err(|e| Error::filesystem(folder_path, e))?;
             println!("创建文件夹: {}", folder_path.display());
         }
         Ok(())
@@ -51,9 +105,12 @@ impl FolderManager {
 
     pub fn create_all_folders(&self, url: &str) -> Result<()> {
         if self.translation_config.enabled {
-            for file_type in [FileType::Original, FileType::Translated, FileType::Bilingual] {
-                let folder_path = self.get_folder_path(url, file_type)?;
-                self.ensure_folder_exists(&folder_path)?;
+            self.ensure_folder_exists(&self.get_folder_path(url, FileType::Original)?)?;
+            for lang in &self.translation_config.target_langs {
+                for file_type in [FileType::Translated, FileType::Bilingual] {
+                    let folder_path = self.get_language_folder_path(url, file_type, lang)?;
+                    self.ensure_folder_exists(&folder_path)?;
+                }
             }
         } else {
             let folder_path = self.get_folder_path(url, FileType::Original)?;
@@ -63,23 +120,38 @@ impl FolderManager {
     }
 
     pub fn get_file_path(&self, url: &str, file_type: FileType) -> Result<PathBuf> {
-        let folder_path = self.get_folder_path(url, file_type)?;
-        self.ensure_folder_exists(&folder_path)?;
-        
-        let filename = self.generate_filename(url)?;
-        let mut file_path = folder_path;
-        file_path.push(filename);
-        
+        self.get_language_file_path(url, file_type, self.translation_config.target_lang())
+    }
+
+    pub fn get_language_file_path(
+        &self,
+        url: &str,
+        file_type: FileType,
+        lang: &str,
+    ) -> Result<PathBuf> {
+        let folder_path = self.get_language_folder_path(url, file_type, lang)?;
+
+        let relative_path = match self.output_config.layout {
+            OutputLayout::Flat => PathBuf::from(self.generate_filename(url)?),
+            OutputLayout::Mirror => self.mirror_path(url)?,
+        };
+        let file_path = folder_path.join(relative_path);
+
+        if let Some(parent) = file_path.parent() {
+            self.ensure_folder_exists(parent)?;
+        }
+
         Ok(file_path)
     }
 
     fn extract_domain(&self, url: &str) -> Result<String> {
         let parsed_url = Url::parse(url)
             .map_err(|e| crate::error::Error::Custom(format!("Invalid URL: {}", e)))?;
-        
-        let domain = parsed_url.host_str()
+
+        let domain = parsed_url
+            .host_str()
             .ok_or_else(|| crate::error::Error::Custom("No host in URL".to_string()))?;
-        
+
         Ok(domain.to_string())
     }
 
@@ -89,9 +161,9 @@ impl FolderManager {
 
         let host = parsed_url.host_str().unwrap_or("unknown");
         let path = parsed_url.path();
-        
+
         let date = chrono::Utc::now().format("%Y%m%d").to_string();
-        
+
         let path_part = if path == "/" || path.is_empty() {
             "index".to_string()
         } else {
@@ -105,13 +177,14 @@ impl FolderManager {
                 .to_string()
         };
 
-        let filename = format!("{}_{}_{}_{}.md", 
-            host.replace('.', "_"), 
+        let filename = format!(
+            "{}_{}_{}_{}.md",
+            host.replace('.', "_"),
             date,
-            if path_part.len() > 100 { 
+            if path_part.len() > 100 {
                 format!("{}...", &path_part[..97])
-            } else { 
-                path_part 
+            } else {
+                path_part
             },
             chrono::Utc::now().timestamp()
         );
@@ -119,32 +192,126 @@ impl FolderManager {
         Ok(filename)
     }
 
+    // 按 URL 路径生成相对路径：/docs/guide/intro -> docs/guide/intro.md，
+    // 以 / 结尾的目录 URL -> docs/guide/index.md。同一路径的不同查询参数
+    // 通过查询串的短哈希区分，因此同一 URL 每次都映射到同一个文件
+    fn mirror_path(&self, url: &str) -> Result<PathBuf> {
+        let parsed_url = Url::parse(url)
+            .map_err(|e| crate::error::Error::Custom(format!("Invalid URL: {}", e)))?;
+
+        let mut segments: Vec<&str> = parsed_url
+            .path_segments()
+            .map(|segments| segments.collect())
+            .unwrap_or_default();
+
+        let last = segments.pop().unwrap_or_default();
+        let mut stem = if last.is_empty() {
+            "index".to_string()
+        } else {
+            Self::page_stem(last)
+        };
+
+        if let Some(query) = parsed_url.query().filter(|q| !q.is_empty()) {
+            stem = format!("{}-{}", stem, Self::short_hash(query));
+        }
+
+        let mut path = PathBuf::new();
+        for segment in segments.into_iter().filter(|s| !s.is_empty()) {
+            path.push(Self::sanitize_segment(segment));
+        }
+        path.push(format!("{}.md", stem));
+
+        Ok(path)
+    }
+
+    fn sanitize_segment(segment: &str) -> String {
+        let decoded = percent_encoding::percent_decode_str(segment).decode_utf8_lossy();
+        Self::safe_name(&decoded, &decoded)
+    }
+
+    // 页面文件名去掉 .html 等扩展名：/docs/intro.html -> intro
+    fn page_stem(segment: &str) -> String {
+        let decoded = percent_encoding::percent_decode_str(segment).decode_utf8_lossy();
+        Self::safe_name(&decoded, &Self::strip_page_extension(&decoded))
+    }
+
+    // 只保留文件系统安全的字符，过长的名字截断。结果与解码后的原片段不同时附加原片段的哈希，
+    // 避免 /a%20b 与 /a_b、/page.html 与 /page 映射到同一个文件
+    fn safe_name(original: &str, name: &str) -> String {
+        let mut sanitized: String = name
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+
+        if !sanitized.is_empty() && sanitized.chars().all(|c| c == '.') {
+            sanitized = "_".to_string();
+        }
+
+        if sanitized.chars().count() > 100 {
+            sanitized = sanitized.chars().take(90).collect();
+        }
+
+        if sanitized == original {
+            sanitized
+        } else {
+            format!("{}-{}", sanitized, Self::short_hash(original))
+        }
+    }
+
+    fn strip_page_extension(name: &str) -> String {
+        const PAGE_EXTENSIONS: &[&str] = &[
+            "html", "htm", "xhtml", "shtml", "php", "asp", "aspx", "jsp", "md", "markdown",
+        ];
+
+        match name.rsplit_once('.') {
+            Some((stem, ext))
+                if !stem.is_empty() && PAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str()) =>
+            {
+                stem.to_string()
+            }
+            _ => name.to_string(),
+        }
+    }
+
+    fn short_hash(value: &str) -> String {
+        let digest = format!("{:x}", Sha256::digest(value.as_bytes()));
+        digest[..8].to_string()
+    }
+
     pub fn create_bilingual_content(&self, original: &str, translated: &str) -> String {
         let mut bilingual = String::new();
-        
+
         // 按段落分割（双换行符）
         let original_paragraphs: Vec<&str> = original.split("\n\n").collect();
         let translated_paragraphs: Vec<&str> = translated.split("\n\n").collect();
-        
+
         // 如果段落数量匹配，采用段落对应模式
         if original_paragraphs.len() == translated_paragraphs.len() {
-            for (orig_para, trans_para) in original_paragraphs.iter().zip(translated_paragraphs.iter()) {
+            for (orig_para, trans_para) in
+                original_paragraphs.iter().zip(translated_paragraphs.iter())
+            {
                 let orig_para = orig_para.trim();
                 let trans_para = trans_para.trim();
-                
+
                 if !orig_para.is_empty() || !trans_para.is_empty() {
                     // 译文在上
                     if !trans_para.is_empty() {
                         bilingual.push_str(trans_para);
                         bilingual.push_str("\n\n");
                     }
-                    
+
                     // 原文在下
                     if !orig_para.is_empty() {
                         bilingual.push_str(orig_para);
                         bilingual.push_str("\n\n");
                     }
-                    
+
                     // 段落间额外空行
                     bilingual.push('\n');
                 }
@@ -153,51 +320,450 @@ impl FolderManager {
             // 段落数量不匹配，采用整体对比模式
             let original = original.trim();
             let translated = translated.trim();
-            
+
             if !translated.is_empty() {
                 bilingual.push_str("**译文:**\n\n");
                 bilingual.push_str(translated);
                 bilingual.push_str("\n\n---\n\n");
             }
-            
+
             if !original.is_empty() {
                 bilingual.push_str("**原文:**\n\n");
                 bilingual.push_str(original);
                 bilingual.push('\n');
             }
         }
-        
+
         bilingual
     }
 
-    pub fn save_content(&self, url: &str, original: &str, translated: Option<&str>) -> Result<Vec<String>> {
+    // 生成 YAML front matter，字段顺序和取舍由 front_matter_fields 决定
+    fn front_matter(&self, metadata: &PageMetadata, file_type: &FileType, lang: &str) -> String {
+        let mut lines = vec!["---".to_string()];
+
+        for field in &self.output_config.front_matter_fields {
+            let value = match field.as_str() {
+                "source_url" => Some(metadata.source_url.clone()),
+                "title" => metadata.title.clone(),
+                "fetched_at" => Some(
+                    metadata
+                        .fetched_at
+                        .to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+                ),
+                "content_hash" => Some(format!("sha256:{}", metadata.content_hash)),
+                "file_type" => Some(file_type.folder_name().to_string()),
+                "source_lang" if self.translation_config.enabled => {
+                    Some(self.translation_config.source_lang.clone())
+                }
+                "target_lang"
+                    if self.translation_config.enabled && *file_type != FileType::Original =>
+                {
+                    Some(lang.to_string())
+                }
+                "translation_backend" if self.translation_config.enabled => {
+                    Some(self.translation_config.backend_name().to_string())
+                }
+                _ => None,
+            };
+
+            if let Some(value) = value {
+                lines.push(format!("{}: {}", field, Self::yaml_string(&value)));
+            }
+        }
+
+        lines.push("---".to_string());
+        lines.join("\n") + "\n\n"
+    }
+
+    fn yaml_string(value: &str) -> String {
+        let mut escaped = String::with_capacity(value.len() + 2);
+        escaped.push('"');
+        for ch in value.chars() {
+            match ch {
+                '"' => escaped.push_str("\\\""),
+                '\\' => escaped.push_str("\\\\"),
+                '\n' => escaped.push_str("\\n"),
+                '\r' => escaped.push_str("\\r"),
+                '\t' => escaped.push_str("\\t"),
+                c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+                c => escaped.push(c),
+            }
+        }
+        escaped.push('"');
+        escaped
+    }
+
+    fn write_file(
+        &self,
+        path: &Path,
+        content: &str,
+        metadata: &PageMetadata,
+        file_type: FileType,
+        lang: &str,
+    ) -> Result<()> {
+        // 内容自带 front matter（如本地 Markdown 源文件的）时原样写入，不再添加一块
+        let add_front_matter =
+            self.output_config.front_matter && split_front_matter(content).0.is_empty();
+
+        if path.exists() && self.output_config.on_existing == ExistingFilePolicy::Version {
+            let existing = fs::read_to_string(path).unwrap_or_default();
+            let existing = if add_front_matter {
+                split_front_matter(&existing).1
+            } else {
+                existing.as_str()
+            };
+            // 内容没有变化时保留原文件，不产生新版本；旧文件的站内链接在上次运行结束时已改写为本地路径，
+            // 新内容也要按同样的规则改写后再比较
+            let unchanged = existing == content
+                || self.link_rewriter.as_ref().is_some_and(|rewriter| {
+                    rewriter
+                        .rewrite_content(&metadata.source_url, path, content)
+                        .0
+                        == existing
+                });
+            if unchanged {
+                return Ok(());
+            }
+            let version_path = Self::version_path(path)?;
+            fs::rename(path, &version_path).map_err(|e| Error::filesystem(path, e))?;
+            println!("保留旧版本: {}", version_path.display());
+        }
+
+        if add_front_matter {
+            let mut document = self.front_matter(metadata, &file_type, lang);
+            document.push_str(content);
+            fs::write(path, document).map_err(|e| Error::filesystem(path, e))?;
+        } else {
+            fs::write(path, content).map_err(|e| Error::filesystem(path, e))?;
+        }
+        Ok(())
+    }
+
+    // intro.md -> intro.20250715093000.md，时间取旧文件的修改时间
+    fn version_path(path: &Path) -> Result<PathBuf> {
+        let modified: DateTime<Utc> = fs::metadata(path)?
+            .modified()
+            .map(DateTime::from)
+            .unwrap_or_else(|_| Utc::now());
+
+        let stem = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let timestamp = modified.format("%Y%m%d%H%M%S").to_string();
+
+        let mut version_path = path.with_file_name(format!("{}.{}.md", stem, timestamp));
+        let mut counter = 1;
+        while version_path.exists() {
+            version_path = path.with_file_name(format!("{}.{}-{}.md", stem, timestamp, counter));
+            counter += 1;
+        }
+        Ok(version_path)
+    }
+
+    pub fn save_content(
+        &self,
+        url: &str,
+        original: &str,
+        translated: Option<&str>,
+    ) -> Result<Vec<String>> {
+        let translations: Vec<(String, String)> = translated
+            .map(|content| {
+                (
+                    self.translation_config.target_lang().to_string(),
+                    content.to_string(),
+                )
+            })
+            .into_iter()
+            .collect();
+        self.save_content_with_assets(url, original, &translations, &LocalAssets::default())
+    }
+
+    // translations 为 (目标语言, 译文)；保存时把已下载资源的引用改写为相对每个文件的本地路径
+    pub fn save_content_with_assets(
+        &self,
+        url: &str,
+        original: &str,
+        translations: &[(String, String)],
+        assets: &LocalAssets,
+    ) -> Result<Vec<String>> {
         let mut saved_files = Vec::new();
+        let metadata = PageMetadata::new(url, original);
+        let source_lang = self.translation_config.source_lang.as_str();
 
         if self.translation_config.enabled {
             let original_path = self.get_file_path(url, FileType::Original)?;
-            fs::write(&original_path, original)?;
+            self.write_file(
+                &original_path,
+                &assets.rewrite(original, &original_path),
+                &metadata,
+                FileType::Original,
+                source_lang,
+            )?;
             saved_files.push(original_path.to_string_lossy().to_string());
             println!("保存原文: {}", original_path.display());
 
-            if let Some(translated_content) = translated {
-                let translated_path = self.get_file_path(url, FileType::Translated)?;
-                fs::write(&translated_path, translated_content)?;
+            for (lang, translated_content) in translations {
+                let translated_path =
+                    self.get_language_file_path(url, FileType::Translated, lang)?;
+                self.write_file(
+                    &translated_path,
+                    &assets.rewrite(translated_content, &translated_path),
+                    &metadata,
+                    FileType::Translated,
+                    lang,
+                )?;
                 saved_files.push(translated_path.to_string_lossy().to_string());
-                println!("保存译文: {}", translated_path.display());
+                println!("保存译文 ({}): {}", lang, translated_path.display());
 
                 let bilingual_content = self.create_bilingual_content(original, translated_content);
-                let bilingual_path = self.get_file_path(url, FileType::Bilingual)?;
-                fs::write(&bilingual_path, bilingual_content)?;
+                let bilingual_path = self.get_language_file_path(url, FileType::Bilingual, lang)?;
+                self.write_file(
+                    &bilingual_path,
+                    &assets.rewrite(&bilingual_content, &bilingual_path),
+                    &metadata,
+                    FileType::Bilingual,
+                    lang,
+                )?;
                 saved_files.push(bilingual_path.to_string_lossy().to_string());
-                println!("保存双语对照: {}", bilingual_path.display());
+                println!("保存双语对照 ({}): {}", lang, bilingual_path.display());
             }
         } else {
             let file_path = self.get_file_path(url, FileType::Original)?;
-            fs::write(&file_path, original)?;
+            self.write_file(
+                &file_path,
+                &assets.rewrite(original, &file_path),
+                &metadata,
+                FileType::Original,
+                source_lang,
+            )?;
             saved_files.push(file_path.to_string_lossy().to_string());
             println!("保存文件: {}", file_path.display());
         }
 
         Ok(saved_files)
     }
-}
\ No newline at end of file
+
+    // 本地 Markdown 目录的译文按原目录结构保存，如 <base>/docs_en-zh/translated/guide/intro.md。
+    // 原文就在源目录中，不再复制；原文自带的 front matter 不翻译，原样放在译文和双语文件开头
+    pub fn save_local_translations(
+        &self,
+        name: &str,
+        relative_path: &Path,
+        original: &str,
+        translations: &[(String, String)],
+    ) -> Result<Vec<String>> {
+        let folder = self.output_folder(name);
+        let metadata = PageMetadata::new(&relative_path.to_string_lossy(), original);
+        let mut saved_files = Vec::new();
+        let (source_front_matter, body) = split_front_matter(original);
+
+        for (lang, translated_content) in translations {
+            let translated_path = self
+                .type_folder(folder.clone(), FileType::Translated, lang)
+                .join(relative_path);
+            if let Some(parent) = translated_path.parent() {
+                self.ensure_folder_exists(parent)?;
+            }
+            let translated_document = format!("{}{}", source_front_matter, translated_content);
+            self.write_file(
+                &translated_path,
+                &translated_document,
+                &metadata,
+                FileType::Translated,
+                lang,
+            )?;
+            saved_files.push(translated_path.to_string_lossy().to_string());
+            println!("保存译文 ({}): {}", lang, translated_path.display());
+
+            let bilingual_content = format!(
+                "{}{}",
+                source_front_matter,
+                self.create_bilingual_content(body, translated_content)
+            );
+            let bilingual_path = self
+                .type_folder(folder.clone(), FileType::Bilingual, lang)
+                .join(relative_path);
+            if let Some(parent) = bilingual_path.parent() {
+                self.ensure_folder_exists(parent)?;
+            }
+            self.write_file(
+                &bilingual_path,
+                &bilingual_content,
+                &metadata,
+                FileType::Bilingual,
+                lang,
+            )?;
+            saved_files.push(bilingual_path.to_string_lossy().to_string());
+            println!("保存双语对照 ({}): {}", lang, bilingual_path.display());
+        }
+
+        Ok(saved_files)
+    }
+}
+
+// 拆分文档开头的 YAML front matter，返回 (front matter 及其后的空行, 正文)；没有时 front matter 为空。
+// 兼容没有字段的空块 "---\n---\n"
+pub fn split_front_matter(document: &str) -> (&str, &str) {
+    let Some(rest) = document.strip_prefix("---\n") else {
+        return ("", document);
+    };
+    let end = if rest.starts_with("---\n") {
+        Some(8)
+    } else {
+        rest.find("\n---\n").map(|index| 4 + index + 5)
+    };
+
+    match end {
+        Some(end) if document[end..].starts_with('\n') => document.split_at(end + 1),
+        Some(end) => document.split_at(end),
+        None => ("", document),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mirror(url: &str) -> String {
+        let manager = FolderManager::new(PathBuf::from("out"), TranslationConfig::default());
+        manager
+            .mirror_path(url)
+            .unwrap()
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+
+    #[test]
+    fn directory_urls_map_to_index() {
+        assert_eq!(mirror("https://site.com/"), "index.md");
+        assert_eq!(mirror("https://site.com/docs/"), "docs/index.md");
+        assert_eq!(
+            mirror("https://site.com/docs/guide/intro"),
+            "docs/guide/intro.md"
+        );
+    }
+
+    #[test]
+    fn query_strings_get_a_stable_hash() {
+        let first = mirror("https://site.com/docs/search?q=rust");
+        assert!(first.starts_with("docs/search-") && first.ends_with(".md"));
+        assert_eq!(first, mirror("https://site.com/docs/search?q=rust"));
+        assert_ne!(first, mirror("https://site.com/docs/search?q=go"));
+        assert_eq!(mirror("https://site.com/docs/search?"), "docs/search.md");
+    }
+
+    #[test]
+    fn changed_names_do_not_collide() {
+        assert_eq!(mirror("https://site.com/a_b"), "a_b.md");
+        assert_ne!(
+            mirror("https://site.com/a%20b"),
+            mirror("https://site.com/a_b")
+        );
+        assert!(mirror("https://site.com/a%20b").starts_with("a_b-"));
+
+        assert_eq!(mirror("https://site.com/page"), "page.md");
+        assert_ne!(
+            mirror("https://site.com/page.html"),
+            mirror("https://site.com/page")
+        );
+        assert!(mirror("https://site.com/page.html").starts_with("page-"));
+
+        assert_ne!(
+            mirror("https://site.com/x%20y/page"),
+            mirror("https://site.com/x_y/page")
+        );
+    }
+
+    #[test]
+    fn long_names_are_truncated_with_a_hash() {
+        let long = "a".repeat(150);
+        let other = format!("{}b", "a".repeat(149));
+        let path = mirror(&format!("https://site.com/{}", long));
+
+        assert!(path.chars().count() < 110);
+        assert!(path.starts_with(&"a".repeat(90)));
+        assert_ne!(path, mirror(&format!("https://site.com/{}", other)));
+    }
+
+    #[test]
+    fn dot_segments_never_leave_the_site_folder() {
+        assert_eq!(mirror("https://site.com/docs/../intro"), "intro.md");
+
+        let encoded = mirror("https://site.com/%2E%2E/%2E%2E/intro");
+        assert!(
+            !encoded
+                .split('/')
+                .any(|segment| segment == ".." || segment == ".")
+        );
+        assert!(encoded.ends_with("intro.md"));
+    }
+
+    fn with_fields(fields: &[&str]) -> FolderManager {
+        let output_config = OutputConfig {
+            front_matter: true,
+            front_matter_fields: fields.iter().map(|f| f.to_string()).collect(),
+            ..OutputConfig::default()
+        };
+        FolderManager::new(PathBuf::from("out"), TranslationConfig::default())
+            .with_output_config(output_config)
+    }
+
+    #[test]
+    fn front_matter_follows_the_configured_fields() {
+        let manager = with_fields(&["title", "source_url", "target_lang"]);
+        let metadata = PageMetadata::new("https://site.com/intro", "# Intro\n\nBody");
+
+        assert_eq!(
+            manager.front_matter(&metadata, &FileType::Original, "zh"),
+            "---\ntitle: \"Intro\"\nsource_url: \"https://site.com/intro\"\n---\n\n"
+        );
+        assert_eq!(
+            with_fields(&[]).front_matter(&metadata, &FileType::Original, "zh"),
+            "---\n---\n\n"
+        );
+    }
+
+    #[test]
+    fn yaml_strings_are_escaped() {
+        assert_eq!(FolderManager::yaml_string("plain"), "\"plain\"");
+        assert_eq!(
+            FolderManager::yaml_string("say \"hi\"\\ok"),
+            "\"say \\\"hi\\\"\\\\ok\""
+        );
+        assert_eq!(
+            FolderManager::yaml_string("a\nb\tc\u{7}"),
+            "\"a\\nb\\tc\\u0007\""
+        );
+        assert_eq!(FolderManager::yaml_string("key: 值 #1"), "\"key: 值 #1\"");
+    }
+
+    #[test]
+    fn front_matter_is_split_from_the_body() {
+        let manager = with_fields(&["title"]);
+        let metadata = PageMetadata::new("https://site.com/intro", "# Intro");
+        let block = manager.front_matter(&metadata, &FileType::Original, "zh");
+        let document = format!("{}# Intro\n\n---\n\nMore", block);
+
+        assert_eq!(
+            split_front_matter(&document),
+            (block.as_str(), "# Intro\n\n---\n\nMore")
+        );
+        assert_eq!(
+            split_front_matter("---\n---\n\nBody"),
+            ("---\n---\n\n", "Body")
+        );
+        assert_eq!(
+            split_front_matter("---\ntitle: x\n---\nBody"),
+            ("---\ntitle: x\n---\n", "Body")
+        );
+        assert_eq!(
+            split_front_matter("# No front matter\n---\n"),
+            ("", "# No front matter\n---\n")
+        );
+        assert_eq!(
+            split_front_matter("---\nunterminated"),
+            ("", "---\nunterminated")
+        );
+    }
+}
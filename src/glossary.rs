@@ -0,0 +1,442 @@
+use crate::error::{Error, Result};
+use regex::{Regex, RegexBuilder};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+use std::sync::{LazyLock, Mutex};
+
+// 译文中的术语占位符，与 markdown_segments 的 ⟦n⟧ 区分开
+static TERM_PLACEHOLDER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"⟦\s*T\s*(\d+)\s*⟧").expect("valid regex"));
+
+// 术语表文件格式（TOML）：
+//
+//   protected = ["Tokio", "serde_json"]   # 保持原样、不翻译的术语
+//   ignore_case = false                   # 匹配时是否忽略大小写
+//
+//   [terms]                               # 原文术语 = 固定译法
+//   "pull request" = "拉取请求"
+//   "crate" = { zh = "包", ja = "クレート" } # 按目标语言分别指定
+#[derive(Debug, Default, Deserialize)]
+struct GlossaryFile {
+    #[serde(default)]
+    protected: Vec<String>,
+    #[serde(default)]
+    ignore_case: bool,
+    #[serde(default)]
+    terms: BTreeMap<String, TermFileValue>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum TermFileValue {
+    Fixed(String),
+    PerLanguage(BTreeMap<String, String>),
+}
+
+// 术语的处理方式
+#[derive(Debug, Clone)]
+enum TermTarget {
+    Protected,
+    Fixed(String),
+    // 目标语言（小写） -> 译法；没有列出的语言不处理该术语
+    PerLanguage(HashMap<String, String>),
+}
+
+impl TermTarget {
+    fn replacement(&self, matched: &str, lang: &str) -> Option<String> {
+        match self {
+            TermTarget::Protected => Some(matched.to_string()),
+            TermTarget::Fixed(target) => Some(target.clone()),
+            TermTarget::PerLanguage(targets) => targets.get(&lang.to_lowercase()).cloned(),
+        }
+    }
+}
+
+// 一处未按术语表翻译的记录
+#[derive(Debug, Clone)]
+pub struct GlossaryIssue {
+    pub page: String,
+    pub lang: String,
+    pub term: String,
+    pub expected: String,
+    pub segment: String,
+}
+
+// 翻译前被替换为 ⟦Tn⟧ 的文本，以及每个占位符对应的原文术语和应还原的内容
+#[derive(Debug, Clone)]
+pub struct MaskedText {
+    original: String,
+    text: String,
+    terms: Vec<(String, String)>,
+}
+
+impl MaskedText {
+    pub fn unmasked(text: &str) -> Self {
+        Self {
+            original: text.to_string(),
+            text: text.to_string(),
+            terms: Vec::new(),
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn original(&self) -> &str {
+        &self.original
+    }
+
+    // 把占位符还原为固定译法；返回还原后的文本和在译文中丢失的术语
+    pub fn restore(&self, translated: &str) -> (String, Vec<(String, String)>) {
+        if self.terms.is_empty() {
+            return (translated.to_string(), Vec::new());
+        }
+
+        let mut seen = vec![false; self.terms.len()];
+        let restored = TERM_PLACEHOLDER.replace_all(translated, |caps: &regex::Captures| {
+            let index: usize = caps[1].parse().unwrap_or(usize::MAX);
+            match self.terms.get(index) {
+                Some((_, replacement)) => {
+                    seen[index] = true;
+                    replacement.clone()
+                }
+                // 翻译服务编造的占位符直接去掉
+                None => String::new(),
+            }
+        });
+
+        let missing = self
+            .terms
+            .iter()
+            .zip(&seen)
+            .filter(|(_, seen)| !**seen)
+            .map(|(term, _)| term.clone())
+            .collect();
+        (restored.into_owned(), missing)
+    }
+}
+
+// 术语表：翻译前把术语替换为占位符，译文返回后再换成固定译法，
+// 保证同一个术语在所有页面中的译法一致；占位符被翻译服务丢掉时记录下来
+pub struct Glossary {
+    pattern: Option<Regex>,
+    // 原文术语（忽略大小写时为小写） -> 处理方式
+    targets: HashMap<String, TermTarget>,
+    ignore_case: bool,
+    issues: Mutex<Vec<GlossaryIssue>>,
+}
+
+impl Glossary {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path).map_err(|e| Error::filesystem(path, e))?;
+        let file: GlossaryFile = toml::from_str(&content).map_err(|e| {
+            Error::Custom(format!(
+                "Failed to parse glossary file {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+
+        let glossary = Self::from_file(file)?;
+        println!(
+            "已加载术语表: {} ({} 个术语)",
+            path.display(),
+            glossary.len()
+        );
+        Ok(glossary)
+    }
+
+    fn from_file(file: GlossaryFile) -> Result<Self> {
+        let normalize = |term: &str| {
+            if file.ignore_case {
+                term.to_lowercase()
+            } else {
+                term.to_string()
+            }
+        };
+
+        let mut targets = HashMap::new();
+        for term in file
+            .protected
+            .iter()
+            .map(|t| t.trim())
+            .filter(|t| !t.is_empty())
+        {
+            targets.insert(normalize(term), TermTarget::Protected);
+        }
+        for (term, value) in &file.terms {
+            let term = term.trim();
+            if term.is_empty() {
+                continue;
+            }
+            let target = match value {
+                TermFileValue::Fixed(target) => TermTarget::Fixed(target.clone()),
+                TermFileValue::PerLanguage(targets) => TermTarget::PerLanguage(
+                    targets
+                        .iter()
+                        .map(|(lang, target)| (lang.to_lowercase(), target.clone()))
+                        .collect(),
+                ),
+            };
+            targets.insert(normalize(term), target);
+        }
+
+        // 长术语优先匹配，避免 "pull request" 被 "request" 截断
+        let mut terms: Vec<&String> = targets.keys().collect();
+        terms.sort_by_key(|term| std::cmp::Reverse(term.chars().count()));
+
+        let pattern = if terms.is_empty() {
+            None
+        } else {
+            let alternatives: Vec<String> =
+                terms.iter().map(|term| Self::term_pattern(term)).collect();
+            let regex = RegexBuilder::new(&alternatives.join("|"))
+                .case_insensitive(file.ignore_case)
+                .build()
+                .map_err(|e| Error::Custom(format!("Invalid glossary term: {}", e)))?;
+            Some(regex)
+        };
+
+        Ok(Self {
+            pattern,
+            targets,
+            ignore_case: file.ignore_case,
+            issues: Mutex::new(Vec::new()),
+        })
+    }
+
+    // 以字母数字开头或结尾的术语要求完整单词匹配
+    fn term_pattern(term: &str) -> String {
+        let escaped = regex::escape(term);
+        let is_word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+        let start = if is_word(term.chars().next()) {
+            r"\b"
+        } else {
+            ""
+        };
+        let end = if is_word(term.chars().last()) {
+            r"\b"
+        } else {
+            ""
+        };
+        format!("{}{}{}", start, escaped, end)
+    }
+
+    pub fn len(&self) -> usize {
+        self.targets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.targets.is_empty()
+    }
+
+    pub fn mask(&self, text: &str, lang: &str) -> MaskedText {
+        let Some(pattern) = &self.pattern else {
+            return MaskedText::unmasked(text);
+        };
+
+        let mut terms = Vec::new();
+        let masked = pattern.replace_all(text, |caps: &regex::Captures| {
+            let matched = &caps[0];
+            let key = if self.ignore_case {
+                matched.to_lowercase()
+            } else {
+                matched.to_string()
+            };
+            let Some(replacement) = self
+                .targets
+                .get(&key)
+                .and_then(|target| target.replacement(matched, lang))
+            else {
+                return matched.to_string();
+            };
+            terms.push((matched.to_string(), replacement));
+            format!("⟦T{}⟧", terms.len() - 1)
+        });
+
+        MaskedText {
+            original: text.to_string(),
+            text: masked.into_owned(),
+            terms,
+        }
+    }
+
+    pub fn record_issues(
+        &self,
+        page: &str,
+        lang: &str,
+        segment: &str,
+        missing: Vec<(String, String)>,
+    ) {
+        if missing.is_empty() {
+            return;
+        }
+        let mut issues = self
+            .issues
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        for (term, expected) in missing {
+            eprintln!(
+                "术语未按术语表翻译: {} -> {} ({}, {})",
+                term, expected, page, lang
+            );
+            issues.push(GlossaryIssue {
+                page: page.to_string(),
+                lang: lang.to_string(),
+                term,
+                expected,
+                segment: segment.to_string(),
+            });
+        }
+    }
+
+    pub fn issues(&self) -> Vec<GlossaryIssue> {
+        self.issues
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    // 把不一致的术语按页面写成 Markdown 报告，没有问题时不生成文件
+    pub fn write_report(&self, path: &Path) -> Result<usize> {
+        let issues = self.issues();
+        if issues.is_empty() {
+            return Ok(0);
+        }
+
+        fs::write(path, Self::render_report(&issues)).map_err(|e| Error::filesystem(path, e))?;
+        Ok(issues.len())
+    }
+
+    fn render_report(issues: &[GlossaryIssue]) -> String {
+        let mut by_page: BTreeMap<&str, Vec<&GlossaryIssue>> = BTreeMap::new();
+        for issue in issues {
+            by_page.entry(issue.page.as_str()).or_default().push(issue);
+        }
+
+        let mut report = String::from("# 术语不一致报告\n\n");
+        for (page, issues) in by_page {
+            report.push_str(&format!("## {}\n\n", page));
+            report.push_str("| 语言 | 术语 | 期望译法 | 片段 |\n|---|---|---|---|\n");
+            for issue in issues {
+                report.push_str(&format!(
+                    "| {} | {} | {} | {} |\n",
+                    Self::table_cell(&issue.lang),
+                    Self::table_cell(&issue.term),
+                    Self::table_cell(&issue.expected),
+                    Self::table_cell(&issue.segment)
+                ));
+            }
+            report.push('\n');
+        }
+        report
+    }
+
+    fn table_cell(text: &str) -> String {
+        text.replace('|', "\\|").replace('\n', " ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glossary(toml: &str) -> Glossary {
+        Glossary::from_file(toml::from_str(toml).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn masked_terms_are_restored_with_fixed_translations() {
+        let glossary = glossary(
+            r#"
+            [terms]
+            "pull request" = "拉取请求"
+            "request" = "请求"
+            "#,
+        );
+
+        let masked = glossary.mask("Open a pull request; each request is reviewed.", "zh");
+        assert_eq!(masked.text(), "Open a ⟦T0⟧; each ⟦T1⟧ is reviewed.");
+        assert_eq!(
+            masked.original(),
+            "Open a pull request; each request is reviewed."
+        );
+
+        let (restored, missing) = masked.restore("打开一个 ⟦ T0 ⟧；每个⟦T1⟧都会被审阅。⟦T9⟧");
+        assert_eq!(restored, "打开一个 拉取请求；每个请求都会被审阅。");
+        assert!(missing.is_empty());
+    }
+
+    #[test]
+    fn protected_terms_keep_their_original_spelling() {
+        let glossary = glossary(
+            r#"
+            protected = ["Tokio", "C++"]
+            "#,
+        );
+
+        let masked = glossary.mask("Tokio and C++ but not Tokios.", "zh");
+        assert_eq!(masked.text(), "⟦T0⟧ and ⟦T1⟧ but not Tokios.");
+        assert_eq!(masked.restore("⟦T0⟧ 和 ⟦T1⟧").0, "Tokio 和 C++");
+    }
+
+    #[test]
+    fn case_is_ignored_only_when_configured() {
+        let strict = glossary(r#"terms = { "Crate" = "包" }"#);
+        assert_eq!(strict.mask("a crate", "zh").text(), "a crate");
+
+        let relaxed = glossary(
+            r#"
+            ignore_case = true
+            protected = ["Tokio"]
+            terms = { "Crate" = "包" }
+            "#,
+        );
+        let masked = relaxed.mask("a CRATE built on tokio", "zh");
+        assert_eq!(masked.text(), "a ⟦T0⟧ built on ⟦T1⟧");
+        assert_eq!(masked.restore("⟦T0⟧ ⟦T1⟧").0, "包 tokio");
+    }
+
+    #[test]
+    fn per_language_terms_only_apply_to_listed_languages() {
+        let glossary = glossary(r#"terms = { "crate" = { zh = "包", JA = "クレート" } }"#);
+
+        assert_eq!(glossary.mask("crate", "zh").restore("⟦T0⟧").0, "包");
+        assert_eq!(glossary.mask("crate", "ja").restore("⟦T0⟧").0, "クレート");
+        assert_eq!(glossary.mask("crate", "fr").text(), "crate");
+    }
+
+    #[test]
+    fn dropped_placeholders_are_reported() {
+        let glossary = glossary(r#"terms = { "crate" = "包" }"#);
+        let masked = glossary.mask("Publish the crate.", "zh");
+        let (restored, missing) = masked.restore("发布它。");
+
+        assert_eq!(restored, "发布它。");
+        assert_eq!(missing, [("crate".to_string(), "包".to_string())]);
+
+        glossary.record_issues("https://site.com/b", "zh", "a | b\nc", missing);
+        glossary.record_issues(
+            "https://site.com/a",
+            "zh",
+            masked.original(),
+            vec![("crate".into(), "包".into())],
+        );
+        glossary.record_issues("https://site.com/a", "zh", "unused", Vec::new());
+        assert_eq!(glossary.issues().len(), 2);
+
+        assert_eq!(
+            Glossary::render_report(&glossary.issues()),
+            "# 术语不一致报告\n\n\
+             ## https://site.com/a\n\n\
+             | 语言 | 术语 | 期望译法 | 片段 |\n|---|---|---|---|\n\
+             | zh | crate | 包 | Publish the crate. |\n\n\
+             ## https://site.com/b\n\n\
+             | 语言 | 术语 | 期望译法 | 片段 |\n|---|---|---|---|\n\
+             | zh | crate | 包 | a \\| b c |\n\n"
+        );
+    }
+}
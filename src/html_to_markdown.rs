@@ -0,0 +1,596 @@
+use ego_tree::NodeRef;
+use scraper::{Html, Node, Selector};
+use url::Url;
+
+// 不输出任何内容的元素
+const SKIPPED_TAGS: &[&str] = &[
+    "head", "script", "style", "noscript", "template", "svg", "canvas", "iframe", "object",
+    "embed", "button", "input", "select", "textarea", "option",
+];
+
+// 块级元素：前后需要断开段落
+const BLOCK_TAGS: &[&str] = &[
+    "html",
+    "body",
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "dd",
+    "details",
+    "dialog",
+    "div",
+    "dl",
+    "dt",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hgroup",
+    "hr",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "summary",
+    "table",
+    "ul",
+];
+
+// 本地 HTML -> Markdown 转换，支持标题、列表、表格、带语言标记的代码块和链接
+pub struct HtmlToMarkdown {
+    base_url: Option<Url>,
+}
+
+impl HtmlToMarkdown {
+    pub fn new(page_url: &str) -> Self {
+        Self {
+            base_url: Url::parse(page_url).ok(),
+        }
+    }
+
+    pub fn convert(&self, html: &str) -> String {
+        let document = Html::parse_document(html);
+        let converter = self.with_document_base(&document);
+
+        let mut blocks = Vec::new();
+        converter.render_blocks(*document.root_element(), &mut blocks);
+        let body = blocks.join("\n\n");
+
+        // 正文没有一级标题时，用 <title> 补一个
+        let has_h1 = body.lines().any(|line| line.starts_with("# "));
+        match Self::document_title(&document) {
+            Some(title) if !has_h1 => format!("# {}\n\n{}\n", title, body),
+            _ => format!("{}\n", body),
+        }
+    }
+
+    pub fn document_title(document: &Html) -> Option<String> {
+        let selector = Selector::parse("title").ok()?;
+        let title = document
+            .select(&selector)
+            .next()?
+            .text()
+            .collect::<String>();
+        let title = Self::collapse_whitespace(&title).trim().to_string();
+        if title.is_empty() { None } else { Some(title) }
+    }
+
+    // <base href> 会改变相对链接的解析基准
+    fn with_document_base(&self, document: &Html) -> Self {
+        let base_href = Selector::parse("base[href]")
+            .ok()
+            .and_then(|selector| document.select(&selector).next())
+            .and_then(|base| base.value().attr("href").map(|href| href.to_string()));
+
+        let base_url = match (&self.base_url, base_href) {
+            (Some(page), Some(href)) => page.join(&href).ok().or_else(|| Some(page.clone())),
+            (page, _) => page.clone(),
+        };
+
+        Self { base_url }
+    }
+
+    fn render_blocks(&self, node: NodeRef<Node>, blocks: &mut Vec<String>) {
+        let mut inline = String::new();
+
+        for child in node.children() {
+            match child.value() {
+                Node::Text(text) => {
+                    inline.push_str(&Self::escape_text(&Self::collapse_whitespace(text)))
+                }
+                Node::Element(element) => {
+                    let tag = element.name();
+                    if SKIPPED_TAGS.contains(&tag) {
+                        continue;
+                    }
+                    if BLOCK_TAGS.contains(&tag) {
+                        Self::flush_paragraph(&mut inline, blocks);
+                        self.render_block_element(child, tag, blocks);
+                    } else {
+                        self.render_inline(child, &mut inline);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Self::flush_paragraph(&mut inline, blocks);
+    }
+
+    fn render_block_element(&self, node: NodeRef<Node>, tag: &str, blocks: &mut Vec<String>) {
+        match tag {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = tag[1..].parse::<usize>().unwrap_or(1);
+                let text = self.inline_text(node);
+                if !text.is_empty() {
+                    blocks.push(format!("{} {}", "#".repeat(level), text));
+                }
+            }
+            "p" | "dt" | "summary" | "figcaption" => {
+                let text = self.inline_text(node);
+                if !text.is_empty() {
+                    blocks.push(text);
+                }
+            }
+            "hr" => blocks.push("---".to_string()),
+            "pre" => blocks.push(self.render_code_block(node)),
+            "blockquote" => {
+                let mut inner = Vec::new();
+                self.render_blocks(node, &mut inner);
+                if !inner.is_empty() {
+                    let quoted = inner
+                        .join("\n\n")
+                        .lines()
+                        .map(|line| {
+                            if line.is_empty() {
+                                ">".to_string()
+                            } else {
+                                format!("> {}", line)
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    blocks.push(quoted);
+                }
+            }
+            "ul" | "ol" => {
+                let list = self.render_list(node, tag == "ol");
+                if !list.is_empty() {
+                    blocks.push(list);
+                }
+            }
+            "table" => {
+                let table = self.render_table(node);
+                if !table.is_empty() {
+                    blocks.push(table);
+                }
+            }
+            _ => self.render_blocks(node, blocks),
+        }
+    }
+
+    fn render_list(&self, node: NodeRef<Node>, ordered: bool) -> String {
+        let mut start = 1;
+        if let Node::Element(element) = node.value()
+            && let Some(value) = element.attr("start")
+        {
+            start = value.trim().parse().unwrap_or(1);
+        }
+
+        let mut items = Vec::new();
+        for child in node.children() {
+            let Node::Element(element) = child.value() else {
+                continue;
+            };
+            if element.name() != "li" {
+                continue;
+            }
+
+            let marker = if ordered {
+                format!("{}. ", start + items.len())
+            } else {
+                "- ".to_string()
+            };
+            let indent = " ".repeat(marker.len());
+
+            let mut inner = Vec::new();
+            self.render_blocks(child, &mut inner);
+            let content = inner.join("\n\n");
+
+            let mut item = String::new();
+            for (i, line) in content.lines().enumerate() {
+                if i == 0 {
+                    item.push_str(&marker);
+                    item.push_str(line);
+                } else {
+                    item.push('\n');
+                    if !line.is_empty() {
+                        item.push_str(&indent);
+                        item.push_str(line);
+                    }
+                }
+            }
+            if item.is_empty() {
+                item.push_str(marker.trim_end());
+            }
+            items.push(item);
+        }
+
+        items.join("\n")
+    }
+
+    fn render_table(&self, node: NodeRef<Node>) -> String {
+        let mut rows: Vec<Vec<String>> = Vec::new();
+        self.collect_table_rows(node, &mut rows);
+        if rows.is_empty() {
+            return String::new();
+        }
+
+        let columns = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+        if columns == 0 {
+            return String::new();
+        }
+
+        let format_row = |row: &Vec<String>| {
+            let mut cells: Vec<String> = row.clone();
+            cells.resize(columns, String::new());
+            format!("| {} |", cells.join(" | "))
+        };
+
+        let mut lines = vec![format_row(&rows[0])];
+        lines.push(format!("|{}", " --- |".repeat(columns)));
+        for row in &rows[1..] {
+            lines.push(format_row(row));
+        }
+        lines.join("\n")
+    }
+
+    fn collect_table_rows(&self, node: NodeRef<Node>, rows: &mut Vec<Vec<String>>) {
+        for child in node.children() {
+            let Node::Element(element) = child.value() else {
+                continue;
+            };
+            match element.name() {
+                "thead" | "tbody" | "tfoot" => self.collect_table_rows(child, rows),
+                "tr" => {
+                    let cells: Vec<String> = child
+                        .children()
+                        .filter(|cell| {
+                            matches!(
+                                cell.value(),
+                                Node::Element(e) if e.name() == "td" || e.name() == "th"
+                            )
+                        })
+                        .map(|cell| {
+                            self.inline_text(cell)
+                                .replace('|', "\\|")
+                                .replace('\n', " ")
+                        })
+                        .collect();
+                    if !cells.is_empty() {
+                        rows.push(cells);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn render_code_block(&self, node: NodeRef<Node>) -> String {
+        let mut code = String::new();
+        Self::raw_text(node, &mut code);
+        let code = code.trim_end_matches('\n').trim_start_matches('\n');
+
+        let language = Self::code_language(node)
+            .or_else(|| {
+                node.children()
+                    .find(|child| matches!(child.value(), Node::Element(e) if e.name() == "code"))
+                    .and_then(Self::code_language)
+            })
+            .unwrap_or_default();
+
+        // 代码本身包含 ``` 时换用更长的围栏
+        let mut fence = "```".to_string();
+        while code.contains(&fence) {
+            fence.push('`');
+        }
+
+        format!("{}{}\n{}\n{}", fence, language, code, fence)
+    }
+
+    // 常见的语言标记写法：language-rust、lang-rust、highlight-source-rust、data-lang="rust"
+    fn code_language(node: NodeRef<Node>) -> Option<String> {
+        let Node::Element(element) = node.value() else {
+            return None;
+        };
+
+        if let Some(lang) = element
+            .attr("data-lang")
+            .or_else(|| element.attr("data-language"))
+        {
+            let lang = lang.trim();
+            if !lang.is_empty() {
+                return Some(lang.to_lowercase());
+            }
+        }
+
+        element.classes().find_map(|class| {
+            ["language-", "lang-", "highlight-source-", "highlight-"]
+                .iter()
+                .find_map(|prefix| class.strip_prefix(prefix))
+                .filter(|lang| !lang.is_empty())
+                .map(|lang| lang.to_lowercase())
+        })
+    }
+
+    fn raw_text(node: NodeRef<Node>, out: &mut String) {
+        for child in node.children() {
+            match child.value() {
+                Node::Text(text) => out.push_str(text),
+                Node::Element(element) if element.name() == "br" => out.push('\n'),
+                Node::Element(_) => Self::raw_text(child, out),
+                _ => {}
+            }
+        }
+    }
+
+    fn inline_text(&self, node: NodeRef<Node>) -> String {
+        let mut text = String::new();
+        for child in node.children() {
+            match child.value() {
+                Node::Text(t) => text.push_str(&Self::escape_text(&Self::collapse_whitespace(t))),
+                Node::Element(element) if SKIPPED_TAGS.contains(&element.name()) => {}
+                Node::Element(_) => self.render_inline(child, &mut text),
+                _ => {}
+            }
+        }
+        Self::tidy_inline(&text)
+    }
+
+    fn render_inline(&self, node: NodeRef<Node>, out: &mut String) {
+        let Node::Element(element) = node.value() else {
+            if let Node::Text(text) = node.value() {
+                out.push_str(&Self::escape_text(&Self::collapse_whitespace(text)));
+            }
+            return;
+        };
+
+        match element.name() {
+            "br" => out.push_str("  \n"),
+            "img" => {
+                let src = element
+                    .attr("src")
+                    .or_else(|| element.attr("data-src"))
+                    .unwrap_or_default();
+                if !src.is_empty() && !src.starts_with("data:") {
+                    let alt = element
+                        .attr("alt")
+                        .unwrap_or_default()
+                        .replace(['[', ']'], "");
+                    out.push_str(&format!("![{}]({})", alt.trim(), self.resolve_url(src)));
+                }
+            }
+            "a" => {
+                let text = self.inline_text(node);
+                let href = element.attr("href").unwrap_or_default().trim();
+                if href.is_empty() || href.starts_with("javascript:") {
+                    out.push_str(&text);
+                } else if !text.is_empty() {
+                    out.push_str(&format!("[{}]({})", text, self.resolve_url(href)));
+                }
+            }
+            "code" | "kbd" | "samp" | "tt" => {
+                let mut code = String::new();
+                Self::raw_text(node, &mut code);
+                let code = Self::collapse_whitespace(&code);
+                let code = code.trim();
+                if !code.is_empty() {
+                    if code.contains('`') {
+                        out.push_str(&format!("`` {} ``", code));
+                    } else {
+                        out.push_str(&format!("`{}`", code));
+                    }
+                }
+            }
+            "strong" | "b" => Self::wrap_inline(out, &self.inline_text(node), "**"),
+            "em" | "i" | "cite" => Self::wrap_inline(out, &self.inline_text(node), "*"),
+            "del" | "s" | "strike" => Self::wrap_inline(out, &self.inline_text(node), "~~"),
+            tag if SKIPPED_TAGS.contains(&tag) => {}
+            _ => {
+                for child in node.children() {
+                    self.render_inline(child, out);
+                }
+            }
+        }
+    }
+
+    fn wrap_inline(out: &mut String, text: &str, marker: &str) {
+        if text.is_empty() {
+            return;
+        }
+        out.push_str(marker);
+        out.push_str(text);
+        out.push_str(marker);
+    }
+
+    fn resolve_url(&self, href: &str) -> String {
+        match &self.base_url {
+            Some(base) => base
+                .join(href)
+                .map(|url| url.to_string())
+                .unwrap_or_else(|_| href.to_string()),
+            None => href.to_string(),
+        }
+    }
+
+    fn flush_paragraph(inline: &mut String, blocks: &mut Vec<String>) {
+        let text = Self::tidy_inline(inline);
+        if !text.is_empty() {
+            blocks.push(text);
+        }
+        inline.clear();
+    }
+
+    // 去掉每行首尾多余空格，保留 <br> 产生的硬换行；行首的 # 会被当成标题，需要转义
+    fn tidy_inline(text: &str) -> String {
+        text.split("  \n")
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                if line.starts_with('#') {
+                    format!("\\{}", line)
+                } else {
+                    line.to_string()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("  \n")
+    }
+
+    // 转义正文里会改变格式的 Markdown 字符
+    fn escape_text(text: &str) -> String {
+        let mut result = String::with_capacity(text.len());
+        for ch in text.chars() {
+            if matches!(ch, '\\' | '*' | '_' | '[' | ']' | '`') {
+                result.push('\\');
+            }
+            result.push(ch);
+        }
+        result
+    }
+
+    fn collapse_whitespace(text: &str) -> String {
+        let mut result = String::with_capacity(text.len());
+        let mut last_was_space = false;
+        for ch in text.chars() {
+            if ch.is_whitespace() {
+                if !last_was_space {
+                    result.push(' ');
+                }
+                last_was_space = true;
+            } else {
+                result.push(ch);
+                last_was_space = false;
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert(html: &str) -> String {
+        HtmlToMarkdown::new("https://example.com/docs/guide/page.html").convert(html)
+    }
+
+    #[test]
+    fn headings_keep_their_level() {
+        let markdown = convert("<h1>Title</h1><h2>Section <em>one</em></h2><h6>Deep</h6>");
+        assert_eq!(markdown, "# Title\n\n## Section *one*\n\n###### Deep\n");
+    }
+
+    #[test]
+    fn title_is_used_when_there_is_no_h1() {
+        let markdown =
+            convert("<html><head><title>Page  Title</title></head><body><p>Text</p></body></html>");
+        assert_eq!(markdown, "# Page Title\n\nText\n");
+    }
+
+    #[test]
+    fn nested_lists_are_indented_under_their_marker() {
+        let markdown = convert("<ul><li>One<ul><li>Nested</li></ul></li><li>Two</li></ul>");
+        assert_eq!(markdown, "- One\n\n  - Nested\n- Two\n");
+    }
+
+    #[test]
+    fn ordered_lists_honor_start() {
+        let markdown =
+            convert(r#"<ol start="3"><li>Three</li><li>Four<ol><li>Inner</li></ol></li></ol>"#);
+        assert_eq!(markdown, "3. Three\n4. Four\n\n   1. Inner\n");
+    }
+
+    #[test]
+    fn paragraphs_inside_a_list_item_stay_separate() {
+        let markdown = convert("<ul><li><p>First</p><p>Second</p></li></ul>");
+        assert_eq!(markdown, "- First\n\n  Second\n");
+    }
+
+    #[test]
+    fn tables_use_the_first_row_as_header_and_escape_pipes() {
+        let markdown = convert(
+            "<table><thead><tr><th>Name</th><th>Value</th></tr></thead>\
+             <tbody><tr><td>a|b</td><td>1</td></tr><tr><td>c</td></tr></tbody></table>",
+        );
+        assert_eq!(
+            markdown,
+            "| Name | Value |\n| --- | --- |\n| a\\|b | 1 |\n| c |  |\n"
+        );
+    }
+
+    #[test]
+    fn code_blocks_keep_the_language() {
+        let markdown = convert(
+            r#"<pre><code class="language-rust">fn main() {
+    println!("hi");
+}
+</code></pre>"#,
+        );
+        assert_eq!(
+            markdown,
+            "```rust\nfn main() {\n    println!(\"hi\");\n}\n```\n"
+        );
+    }
+
+    #[test]
+    fn code_containing_a_fence_gets_a_longer_fence() {
+        let markdown =
+            convert("<pre><code class=\"language-markdown\">```\ncode\n```</code></pre>");
+        assert_eq!(markdown, "````markdown\n```\ncode\n```\n````\n");
+    }
+
+    #[test]
+    fn relative_links_resolve_against_the_page_url() {
+        let markdown =
+            convert(r#"<p><a href="../api/">API</a> and <a href="/about">About</a></p>"#);
+        assert_eq!(
+            markdown,
+            "[API](https://example.com/docs/api/) and [About](https://example.com/about)\n"
+        );
+    }
+
+    #[test]
+    fn base_element_changes_the_link_base() {
+        let markdown = convert(
+            r#"<html><head><base href="https://cdn.example.org/v2/"></head>
+               <body><p><a href="intro.html">Intro</a> <img src="img/a.png" alt="A"></p></body></html>"#,
+        );
+        assert_eq!(
+            markdown,
+            "[Intro](https://cdn.example.org/v2/intro.html) ![A](https://cdn.example.org/v2/img/a.png)\n"
+        );
+    }
+
+    #[test]
+    fn literal_markdown_characters_in_text_are_escaped() {
+        let markdown =
+            convert("<p>#1 uses *stars*, snake_case and [brackets]</p><p><code>a*b_c</code></p>");
+        assert_eq!(
+            markdown,
+            "\\#1 uses \\*stars\\*, snake\\_case and \\[brackets\\]\n\n`a*b_c`\n"
+        );
+    }
+}
@@ -1,22 +1,39 @@
+pub mod assets;
 pub mod cli;
 pub mod config;
 pub mod config_file;
-pub mod crawler;
 pub mod converter;
+pub mod crawler;
 pub mod error;
+pub mod extractor;
 pub mod file_manager;
-pub mod progress;
-pub mod types;
-pub mod translator;
 pub mod folder_manager;
+pub mod glossary;
+pub mod html_to_markdown;
+pub mod link_rewriter;
 pub mod links_manager;
+pub mod markdown_segments;
+pub mod progress;
+pub mod rate_limiter;
+pub mod sitemap;
+pub mod status;
+pub mod translation_backend;
+pub mod translation_memory;
+pub mod translator;
+pub mod types;
+pub mod url_filter;
 
+pub use assets::AssetDownloader;
 pub use config::Config;
+pub use converter::{Converter, MarkdownConverter};
 pub use crawler::Crawler;
-pub use converter::MarkdownConverter;
 pub use error::{Error, Result};
 pub use file_manager::FileManager;
+pub use folder_manager::FolderManager;
+pub use link_rewriter::LinkRewriter;
+pub use links_manager::LinksManager;
 pub use progress::ProgressTracker;
+pub use rate_limiter::RateLimiter;
+pub use translation_backend::TranslationBackend;
 pub use translator::TranslationService;
-pub use folder_manager::FolderManager;
-pub use links_manager::LinksManager;
\ No newline at end of file
+pub use url_filter::UrlFilter;
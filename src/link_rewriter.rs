@@ -0,0 +1,329 @@
+use crate::error::{Error, Result};
+use regex::{Captures, Regex};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use url::Url;
+
+// 爬取结束后的链接改写：把指向已下载页面的绝对链接替换为本地 Markdown 文件的相对路径，
+// 保留 #fragment；未下载的页面仍保留原始的绝对链接
+pub struct LinkRewriter {
+    pages: HashMap<String, Vec<PathBuf>>,
+    inline_link: Regex,
+    reference_link: Regex,
+}
+
+impl LinkRewriter {
+    // saved_files 为 URL -> 该页面保存的所有文件（原文 / 译文 / 双语）
+    pub fn new(saved_files: &HashMap<String, Vec<PathBuf>>) -> Self {
+        let pages = saved_files
+            .iter()
+            .filter_map(|(url, files)| {
+                let files: Vec<PathBuf> = files
+                    .iter()
+                    .filter(|file| file.is_file())
+                    .map(|file| fs::canonicalize(file).unwrap_or_else(|_| file.clone()))
+                    .collect();
+                let key = Self::normalize_url(url)?;
+                (!files.is_empty()).then_some((key, files))
+            })
+            .collect();
+
+        Self {
+            pages,
+            inline_link: Regex::new(r#"(\]\(\s*)(<?)([^)\s>]+)(>?)"#).expect("valid regex"),
+            reference_link: Regex::new(r#"^(\s{0,3}\[[^\]]+\]:\s*)(<?)([^\s>]+)(>?)"#)
+                .expect("valid regex"),
+        }
+    }
+
+    // 改写所有已保存的文件，返回被修改的文件数
+    pub fn rewrite_all(&self) -> usize {
+        let mut updated_files = 0;
+        let mut rewritten_links = 0;
+
+        for (page_url, files) in &self.pages {
+            for file in files {
+                match self.rewrite_file(page_url, file) {
+                    Ok(0) => {}
+                    Ok(count) => {
+                        updated_files += 1;
+                        rewritten_links += count;
+                    }
+                    Err(e) => eprintln!("改写链接失败: {} - {}", file.display(), e),
+                }
+            }
+        }
+
+        println!(
+            "🔗 链接改写完成: {} 个文件, {} 个链接指向本地文件",
+            updated_files, rewritten_links
+        );
+        updated_files
+    }
+
+    fn rewrite_file(&self, page_url: &str, file: &Path) -> Result<usize> {
+        let content = fs::read_to_string(file)?;
+        let (rewritten, count) = self.rewrite_content(page_url, file, &content);
+        if count > 0 {
+            fs::write(file, rewritten).map_err(|e| Error::filesystem(file, e))?;
+        }
+        Ok(count)
+    }
+
+    pub fn rewrite_content(&self, page_url: &str, file: &Path, content: &str) -> (String, usize) {
+        let mut output = String::with_capacity(content.len());
+        let mut count = 0;
+        let mut fence: Option<String> = None;
+
+        for line in content.split_inclusive('\n') {
+            let trimmed = line.trim_start();
+
+            // 代码块内的内容原样保留
+            if let Some(marker) = &fence {
+                if trimmed.starts_with(marker.as_str()) {
+                    fence = None;
+                }
+                output.push_str(line);
+                continue;
+            }
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                fence = Some(trimmed[..3].to_string());
+                output.push_str(line);
+                continue;
+            }
+
+            let line = self.reference_link.replace(line, |caps: &Captures| {
+                self.replace_link(page_url, file, caps, &mut count)
+            });
+
+            // 只改写行内代码（`...`）之外的部分
+            for (index, part) in line.split('`').enumerate() {
+                if index > 0 {
+                    output.push('`');
+                }
+                if index % 2 == 1 {
+                    output.push_str(part);
+                } else {
+                    let part = self.inline_link.replace_all(part, |caps: &Captures| {
+                        self.replace_link(page_url, file, caps, &mut count)
+                    });
+                    output.push_str(&part);
+                }
+            }
+        }
+
+        (output, count)
+    }
+
+    fn replace_link(
+        &self,
+        page_url: &str,
+        file: &Path,
+        caps: &Captures,
+        count: &mut usize,
+    ) -> String {
+        let target = &caps[3];
+        match self.local_target(page_url, file, target) {
+            Some(local) => {
+                *count += 1;
+                format!("{}{}{}{}", &caps[1], &caps[2], local, &caps[4])
+            }
+            None => caps[0].to_string(),
+        }
+    }
+
+    // 只处理绝对链接和以 / 开头的站内链接；其他相对链接可能已经是改写后的本地路径
+    fn local_target(&self, page_url: &str, file: &Path, target: &str) -> Option<String> {
+        let is_absolute = target.starts_with("http://") || target.starts_with("https://");
+        let is_root_relative = target.starts_with('/') && !target.starts_with("//");
+        if !(is_absolute || is_root_relative) {
+            return None;
+        }
+
+        let resolved = Url::parse(page_url).ok()?.join(target).ok()?;
+        let fragment = resolved
+            .fragment()
+            .map(|f| format!("#{}", f))
+            .unwrap_or_default();
+
+        let key = Self::normalize_url(resolved.as_str())?;
+        let candidates = self
+            .pages
+            .get(&key)
+            .or_else(|| self.pages.get(&Self::toggle_trailing_slash(&key)))?;
+
+        let source = fs::canonicalize(file).unwrap_or_else(|_| file.to_path_buf());
+        let target_file = Self::closest_file(&source, candidates)?;
+        let from_dir = source.parent()?;
+
+        Some(format!(
+            "{}{}",
+            relative_link(from_dir, target_file),
+            fragment
+        ))
+    }
+
+    // 同一页面有多个文件时（原文 / 译文 / 双语），选择与当前文件位于同一目录树的那个
+    fn closest_file<'a>(source: &Path, candidates: &'a [PathBuf]) -> Option<&'a PathBuf> {
+        candidates.iter().max_by_key(|candidate| {
+            source
+                .components()
+                .zip(candidate.components())
+                .take_while(|(a, b)| a == b)
+                .count()
+        })
+    }
+
+    fn normalize_url(url: &str) -> Option<String> {
+        let mut parsed = Url::parse(url).ok()?;
+        parsed.set_fragment(None);
+        Some(parsed.to_string())
+    }
+
+    fn toggle_trailing_slash(url: &str) -> String {
+        match url.strip_suffix('/') {
+            Some(stripped) => stripped.to_string(),
+            None => format!("{}/", url),
+        }
+    }
+}
+
+// from_dir 到 to 的相对路径，用作 Markdown 链接（统一使用 / 分隔）
+pub(crate) fn relative_link(from_dir: &Path, to: &Path) -> String {
+    let from: Vec<Component> = from_dir.components().collect();
+    let to: Vec<Component> = to.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+
+    let mut parts: Vec<String> = vec!["..".to_string(); from.len() - common];
+    parts.extend(
+        to[common..]
+            .iter()
+            .map(|c| c.as_os_str().to_string_lossy().to_string()),
+    );
+
+    parts.join("/").replace(' ', "%20")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Site {
+        dir: tempfile::TempDir,
+        rewriter: LinkRewriter,
+    }
+
+    impl Site {
+        // 已下载的页面：/docs/ -> docs/index.md，/docs/guide/setup -> docs/guide/setup.md，/api/ref -> api/ref.md
+        fn new() -> Self {
+            let dir = tempfile::tempdir().unwrap();
+
+            let mut saved_files = HashMap::new();
+            for (url, file) in [
+                ("https://example.com/docs/", "docs/index.md"),
+                (
+                    "https://example.com/docs/guide/setup",
+                    "docs/guide/setup.md",
+                ),
+                ("https://example.com/api/ref", "api/ref.md"),
+            ] {
+                let path = dir.path().join(file);
+                fs::create_dir_all(path.parent().unwrap()).unwrap();
+                fs::write(&path, "").unwrap();
+                saved_files.insert(url.to_string(), vec![path]);
+            }
+
+            let rewriter = LinkRewriter::new(&saved_files);
+            Self { dir, rewriter }
+        }
+
+        fn rewrite(&self, content: &str) -> (String, usize) {
+            let file = self.dir.path().join("docs/guide/setup.md");
+            self.rewriter
+                .rewrite_content("https://example.com/docs/guide/setup", &file, content)
+        }
+    }
+
+    #[test]
+    fn inline_links_point_to_local_files() {
+        let site = Site::new();
+        let (content, count) =
+            site.rewrite("See [home](https://example.com/docs/) and [API](/api/ref).\n");
+        assert_eq!(
+            content,
+            "See [home](../index.md) and [API](../../api/ref.md).\n"
+        );
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn reference_links_are_rewritten() {
+        let site = Site::new();
+        let (content, count) = site.rewrite("[api]: https://example.com/api/ref \"API\"\n");
+        assert_eq!(content, "[api]: ../../api/ref.md \"API\"\n");
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn angle_bracket_targets_keep_their_brackets() {
+        let site = Site::new();
+        let (content, _) = site
+            .rewrite("[API](<https://example.com/api/ref>)\n[api]: <https://example.com/docs>\n");
+        assert_eq!(content, "[API](<../../api/ref.md>)\n[api]: <../index.md>\n");
+    }
+
+    #[test]
+    fn fragments_are_kept() {
+        let site = Site::new();
+        let (content, _) = site.rewrite("[Usage](https://example.com/api/ref#usage)\n");
+        assert_eq!(content, "[Usage](../../api/ref.md#usage)\n");
+    }
+
+    #[test]
+    fn links_in_code_are_untouched() {
+        let site = Site::new();
+        let input = "```md\n[API](https://example.com/api/ref)\n```\n\n`[API](https://example.com/api/ref)`\n";
+        let (content, count) = site.rewrite(input);
+        assert_eq!(content, input);
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn links_to_pages_that_were_not_downloaded_stay_absolute() {
+        let site = Site::new();
+        let input = "[Blog](https://example.com/blog/) [Other](https://other.example.org/api/ref) [Local](setup.md)\n";
+        let (content, count) = site.rewrite(input);
+        assert_eq!(content, input);
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn relative_paths_between_nested_directories() {
+        assert_eq!(
+            relative_link(
+                Path::new("/out/docs/guide"),
+                Path::new("/out/docs/guide/setup.md")
+            ),
+            "setup.md"
+        );
+        assert_eq!(
+            relative_link(
+                Path::new("/out/docs/guide"),
+                Path::new("/out/docs/index.md")
+            ),
+            "../index.md"
+        );
+        assert_eq!(
+            relative_link(
+                Path::new("/out/docs/guide"),
+                Path::new("/out/api/v1/ref.md")
+            ),
+            "../../api/v1/ref.md"
+        );
+        assert_eq!(
+            relative_link(Path::new("/out"), Path::new("/out/docs/my page.md")),
+            "docs/my%20page.md"
+        );
+    }
+}
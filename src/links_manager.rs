@@ -1,123 +1,568 @@
-use crate::error::Result;
-use std::collections::HashSet;
+use crate::error::{Error, Result};
+use crate::sitemap::SitemapEntry;
+use crate::url_filter::UrlFilter;
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use url::Url;
 
+// 旧版文本记录中已完成翻译的目标语言字段
+const LANGUAGES_FIELD: &str = "langs: ";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkStatus {
+    // 已开始处理但还没有结果（例如运行中断）
+    Pending,
+    Processed,
+    Failed,
+}
+
+impl LinkStatus {
+    pub fn name(&self) -> &'static str {
+        match self {
+            LinkStatus::Pending => "pending",
+            LinkStatus::Processed => "processed",
+            LinkStatus::Failed => "failed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkError {
+    pub message: String,
+    // Error::kind 的取值；从旧记录迁移的错误为 unknown
+    #[serde(default = "default_error_kind")]
+    pub kind: String,
+    pub retryable: bool,
+    pub at: DateTime<Utc>,
+}
+
+fn default_error_kind() -> String {
+    "unknown".to_string()
+}
+
+// retry-failed 模式下选择要重新处理的失败链接
+#[derive(Debug, Clone)]
+pub struct FailureFilter {
+    // 错误类型（Error::kind）或 retryable / permanent；为空表示不限
+    pub error_types: Vec<String>,
+    // 只处理至少这么久之前失败的链接
+    pub min_age: Option<Duration>,
+    // 只处理最近这段时间内失败的链接
+    pub max_age: Option<Duration>,
+    // 累计处理次数达到上限的链接不再重试
+    pub max_attempts: u32,
+}
+
+impl Default for FailureFilter {
+    fn default() -> Self {
+        Self {
+            error_types: Vec::new(),
+            min_age: None,
+            max_age: None,
+            max_attempts: 5,
+        }
+    }
+}
+
+impl FailureFilter {
+    pub fn matches(&self, error: &LinkError, attempts: u32) -> bool {
+        if attempts >= self.max_attempts {
+            return false;
+        }
+
+        let age = Utc::now() - error.at;
+        if self.min_age.is_some_and(|min_age| age < min_age)
+            || self.max_age.is_some_and(|max_age| age > max_age)
+        {
+            return false;
+        }
+
+        self.error_types.is_empty()
+            || self
+                .error_types
+                .iter()
+                .any(|error_type| match error_type.as_str() {
+                    "retryable" => error.retryable,
+                    "permanent" => !error.retryable,
+                    kind => error.kind.eq_ignore_ascii_case(kind),
+                })
+    }
+}
+
+// 状态文件中一个链接的记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkRecord {
+    pub url: String,
+    pub status: LinkStatus,
+    #[serde(default)]
+    pub attempts: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<LinkError>,
+    #[serde(default)]
+    pub files: Vec<PathBuf>,
+    // 已完成翻译的目标语言（小写），不翻译时为空列表；None 只出现在从旧版文本记录迁移的记录中，视为全部完成
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub languages: Option<Vec<String>>,
+    // 原文 Markdown 的 SHA-256
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
+    // 处理时所用翻译设置的指纹，见 TranslationConfig::fingerprint
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_fingerprint: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub processed_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl LinkRecord {
+    fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            status: LinkStatus::Pending,
+            attempts: 0,
+            last_error: None,
+            files: Vec::new(),
+            languages: None,
+            content_hash: None,
+            config_fingerprint: None,
+            processed_at: None,
+            updated_at: Utc::now(),
+        }
+    }
+
+    pub fn is_processed(&self) -> bool {
+        self.status == LinkStatus::Processed
+    }
+}
+
+// 每个站点的处理状态，保存在输出目录下的 <域名>_state.jsonl
+// （每行一条完整记录，同一链接后出现的记录覆盖前面的）
 pub struct LinksManager {
-    links_file_path: PathBuf,
-    processed_links: HashSet<String>,
+    state_file_path: PathBuf,
+    records: HashMap<String, LinkRecord>,
+    // 本次运行的目标语言；为空表示不翻译
+    languages: Vec<String>,
+    config_fingerprint: Option<String>,
+    url_filter: UrlFilter,
 }
 
 impl LinksManager {
     pub fn new(base_dir: &Path, url: &str) -> Result<Self> {
-        let domain = Self::extract_domain(url)?;
-        let links_file_name = format!("{}_links.txt", domain.replace('.', "_"));
-        let mut links_file_path = base_dir.to_path_buf();
-        links_file_path.push(links_file_name);
-
-        let mut manager = Self {
-            links_file_path,
-            processed_links: HashSet::new(),
-        };
+        let (mut manager, legacy_path) = Self::empty(base_dir, url)?;
 
-        manager.load_processed_links()?;
+        if manager.state_file_path.exists() {
+            manager.load_state()?;
+        } else if legacy_path.exists() {
+            manager.migrate_legacy_file(&legacy_path)?;
+        }
         Ok(manager)
     }
 
+    // 只读方式打开（status 命令使用）：不压缩、不迁移文件，也不输出提示
+    pub fn open_read_only(base_dir: &Path, url: &str) -> Result<Self> {
+        let (mut manager, legacy_path) = Self::empty(base_dir, url)?;
+
+        if manager.state_file_path.exists() {
+            manager.read_state()?;
+        } else if legacy_path.exists() {
+            manager.read_legacy_file(&legacy_path)?;
+        }
+        Ok(manager)
+    }
+
+    // 本地 Markdown 目录的处理状态，保存在 <目录名>_local_state.jsonl，以文件的相对路径代替链接
+    pub fn for_directory(base_dir: &Path, name: &str) -> Result<Self> {
+        let mut manager =
+            Self::with_state_file(base_dir.join(format!("{}_local_state.jsonl", name)));
+        if manager.state_file_path.exists() {
+            manager.load_state()?;
+        }
+        Ok(manager)
+    }
+
+    // 返回空的管理器和旧版记录文件的路径
+    fn empty(base_dir: &Path, url: &str) -> Result<(Self, PathBuf)> {
+        let domain = Self::extract_domain(url)?.replace('.', "_");
+        let manager = Self::with_state_file(base_dir.join(format!("{}_state.jsonl", domain)));
+        Ok((manager, base_dir.join(format!("{}_links.txt", domain))))
+    }
+
+    fn with_state_file(state_file_path: PathBuf) -> Self {
+        Self {
+            state_file_path,
+            records: HashMap::new(),
+            languages: Vec::new(),
+            config_fingerprint: None,
+            url_filter: UrlFilter::default(),
+        }
+    }
+
+    pub fn with_url_filter(mut self, url_filter: UrlFilter) -> Self {
+        self.url_filter = url_filter;
+        self
+    }
+
+    pub fn with_languages(mut self, languages: Vec<String>) -> Self {
+        self.languages = languages;
+        self
+    }
+
+    pub fn with_config_fingerprint(mut self, fingerprint: Option<String>) -> Self {
+        self.config_fingerprint = fingerprint;
+        self
+    }
+
+    pub fn state_file_path(&self) -> &Path {
+        &self.state_file_path
+    }
+
+    pub fn record(&self, url: &str) -> Option<&LinkRecord> {
+        self.records.get(url)
+    }
+
+    pub fn records(&self) -> impl Iterator<Item = &LinkRecord> {
+        self.records.values()
+    }
+
+    // 所有目标语言都已完成才算处理过
     pub fn is_processed(&self, url: &str) -> bool {
-        self.processed_links.contains(url)
+        self.records.get(url).is_some_and(LinkRecord::is_processed)
+            && self.pending_languages(url).is_empty()
     }
 
-    pub fn mark_as_processed(&mut self, url: &str, filename: &str) -> Result<()> {
-        if self.processed_links.insert(url.to_string()) {
-            self.append_to_file(url, filename)?;
+    // 链接还需要翻译的目标语言。从旧版记录迁移、没有语言记录的已处理链接视为全部完成
+    pub fn pending_languages(&self, url: &str) -> Vec<String> {
+        let Some(record) = self.records.get(url).filter(|record| record.is_processed()) else {
+            return self.languages.clone();
+        };
+        match &record.languages {
+            Some(completed) => self
+                .languages
+                .iter()
+                .filter(|lang| !completed.contains(&lang.to_lowercase()))
+                .cloned()
+                .collect(),
+            None => Vec::new(),
         }
-        Ok(())
     }
 
-    pub fn mark_as_failed(&mut self, url: &str, error: &str) -> Result<()> {
-        let entry = format!("❌ {} | ERROR: {}", url, error);
-        self.append_raw_line(&entry)?;
-        Ok(())
+    // 原文或翻译设置变化、或保存的文件已被删除时需要重新翻译所有语言，否则只翻译尚未完成的语言
+    pub fn outdated_languages(&self, url: &str, content_hash: &str) -> Vec<String> {
+        let current = self.records.get(url).is_some_and(|record| {
+            record.content_hash.as_deref() == Some(content_hash)
+                && record.config_fingerprint == self.config_fingerprint
+                && record.files.iter().all(|file| file.exists())
+        });
+        if current {
+            self.pending_languages(url)
+        } else {
+            self.languages.clone()
+        }
+    }
+
+    // 开始处理一个链接，累计尝试次数并立即写入，运行中断时下次加载能看到 pending 记录；
+    // 结果由 mark_as_processed / mark_as_failed 写入。已处理的链接（补译或按 lastmod 更新）保持原状态。
+    // 尝试次数只统计尚未完成的链接，已全部完成的链接因内容更新重新处理时不累计
+    pub fn begin_attempt(&mut self, url: &str) -> Result<()> {
+        let completed = self.is_processed(url);
+        let record = self
+            .records
+            .entry(url.to_string())
+            .or_insert_with(|| LinkRecord::new(url));
+        if !record.is_processed() {
+            record.status = LinkStatus::Pending;
+        }
+        if !completed {
+            record.attempts += 1;
+        }
+        record.updated_at = Utc::now();
+        self.append_record(url)
+    }
+
+    // languages 为本次完成翻译的目标语言，与之前完成的语言合并
+    pub fn mark_as_processed(
+        &mut self,
+        url: &str,
+        saved_files: &[String],
+        languages: &[String],
+        content_hash: &str,
+    ) -> Result<()> {
+        let now = Utc::now();
+
+        // 站点地图 lastmod 模式下已处理的链接可能被重新处理，每次都记录最新的处理时间和文件
+        let record = self
+            .records
+            .entry(url.to_string())
+            .or_insert_with(|| LinkRecord::new(url));
+        record.status = LinkStatus::Processed;
+        record.files = Self::merge_saved_files(
+            &record.files,
+            saved_files.iter().map(PathBuf::from).collect(),
+        );
+        // 不翻译时记录空列表，之后开启翻译时这些链接的所有语言都待翻译
+        let completed = record.languages.get_or_insert_with(Vec::new);
+        for lang in languages.iter().map(|lang| lang.to_lowercase()) {
+            if !completed.contains(&lang) {
+                completed.push(lang);
+            }
+        }
+        record.content_hash = Some(content_hash.to_string());
+        record.config_fingerprint = self.config_fingerprint.clone();
+        record.processed_at = Some(now);
+        record.updated_at = now;
+
+        // 还有语言未完成时保留最近的错误
+        if self.pending_languages(url).is_empty()
+            && let Some(record) = self.records.get_mut(url)
+        {
+            record.last_error = None;
+        }
+        self.append_record(url)
+    }
+
+    // 只补译部分语言时，其他语言目录下之前保存的文件仍然有效
+    fn merge_saved_files(previous: &[PathBuf], files: Vec<PathBuf>) -> Vec<PathBuf> {
+        let written_dirs: HashSet<PathBuf> = files
+            .iter()
+            .filter_map(|file| file.parent().map(Path::to_path_buf))
+            .collect();
+        let kept: Vec<PathBuf> = previous
+            .iter()
+            .filter(|file| {
+                file.exists()
+                    && !files.contains(file)
+                    && file.parent().is_some_and(|dir| !written_dirs.contains(dir))
+            })
+            .cloned()
+            .collect();
+
+        let mut merged = files;
+        merged.extend(kept);
+        merged
+    }
+
+    // message 为写入记录的错误信息（可带上失败的语言等上下文），类型和是否可重试取自 error
+    pub fn mark_as_failed(&mut self, url: &str, message: &str, error: &Error) -> Result<()> {
+        let now = Utc::now();
+        let record = self
+            .records
+            .entry(url.to_string())
+            .or_insert_with(|| LinkRecord::new(url));
+        // 已保存过的链接仍算已处理，只记录错误（例如部分语言翻译失败）
+        if !record.is_processed() {
+            record.status = LinkStatus::Failed;
+        }
+        record.last_error = Some(LinkError {
+            message: message.to_string(),
+            kind: error.kind().to_string(),
+            retryable: error.is_retryable(),
+            at: now,
+        });
+        record.updated_at = now;
+        self.append_record(url)
     }
 
     pub fn get_processed_count(&self) -> usize {
-        self.processed_links.len()
+        self.records
+            .values()
+            .filter(|record| record.is_processed())
+            .count()
+    }
+
+    pub fn get_failed_count(&self) -> usize {
+        self.records
+            .values()
+            .filter(|record| record.status == LinkStatus::Failed)
+            .count()
+    }
+
+    // 记录了错误且仍未完成（未保存或还有语言没翻译）、并符合筛选条件的链接
+    pub fn failed_urls(&self, filter: &FailureFilter) -> Vec<String> {
+        let mut records: Vec<&LinkRecord> = self
+            .records
+            .values()
+            .filter(|record| {
+                !self.is_processed(&record.url) && self.url_filter.is_allowed(&record.url)
+            })
+            .filter(|record| {
+                record
+                    .last_error
+                    .as_ref()
+                    .is_some_and(|error| filter.matches(error, record.attempts))
+            })
+            .collect();
+        records.sort_by_key(|record| record.updated_at);
+        records
+            .into_iter()
+            .map(|record| record.url.clone())
+            .collect()
     }
 
     pub fn get_all_processed_links(&self) -> Vec<String> {
-        self.processed_links.iter().cloned().collect()
+        self.records
+            .values()
+            .filter(|record| record.is_processed())
+            .map(|record| record.url.clone())
+            .collect()
     }
 
-    fn load_processed_links(&mut self) -> Result<()> {
-        if !self.links_file_path.exists() {
-            self.create_links_file()?;
-            return Ok(());
+    // 每个已处理链接保存的文件（原文 / 译文 / 双语），供链接改写使用
+    pub fn saved_files(&self) -> HashMap<String, Vec<PathBuf>> {
+        self.records
+            .values()
+            .filter(|record| record.is_processed() && !record.files.is_empty())
+            .map(|record| (record.url.clone(), record.files.clone()))
+            .collect()
+    }
+
+    fn load_state(&mut self) -> Result<()> {
+        let lines = self.read_state()?;
+
+        println!(
+            "从 {} 加载了 {} 个链接记录（{} 个已处理）",
+            self.state_file_path.display(),
+            self.records.len(),
+            self.get_processed_count()
+        );
+
+        // 同一链接的旧记录超过一半时压缩文件
+        if lines > self.records.len() * 2 {
+            self.write_state()?;
         }
+        Ok(())
+    }
+
+    // 读取状态文件，返回记录行数
+    fn read_state(&mut self) -> Result<usize> {
+        let content = fs::read_to_string(&self.state_file_path)
+            .map_err(|e| Error::filesystem(&self.state_file_path, e))?;
 
-        let content = fs::read_to_string(&self.links_file_path)?;
-        
-        for line in content.lines() {
-            let line = line.trim();
-            if line.is_empty() || line.starts_with('#') {
+        let mut lines = 0;
+        for (line_number, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
                 continue;
             }
-
-            if line.starts_with("✅") {
-                if let Some(url) = self.extract_url_from_line(line) {
-                    self.processed_links.insert(url);
+            lines += 1;
+            match serde_json::from_str::<LinkRecord>(line) {
+                Ok(record) => {
+                    self.records.insert(record.url.clone(), record);
                 }
+                Err(e) => eprintln!("跳过无效的状态记录 (第 {} 行): {}", line_number + 1, e),
             }
         }
+        Ok(lines)
+    }
 
-        println!("从 {} 加载了 {} 个已处理的链接", 
-            self.links_file_path.display(), 
-            self.processed_links.len()
-        );
+    // 把旧版 _links.txt 转换为状态文件，旧文件改名为 .bak 保留
+    fn migrate_legacy_file(&mut self, legacy_path: &Path) -> Result<()> {
+        self.read_legacy_file(legacy_path)?;
 
+        self.write_state()?;
+        let backup_path = legacy_path.with_extension("txt.bak");
+        fs::rename(legacy_path, &backup_path).map_err(|e| Error::filesystem(legacy_path, e))?;
+        println!(
+            "已将 {} 中的 {} 个链接记录迁移到 {}（旧文件保存为 {}）",
+            legacy_path.display(),
+            self.records.len(),
+            self.state_file_path.display(),
+            backup_path.display()
+        );
         Ok(())
     }
 
-    fn create_links_file(&self) -> Result<()> {
-        let header = format!(
-            "# Links Processing Record\n# Generated on: {}\n# Format: ✅ URL | filename\n# ❌ URL | ERROR: error_message\n\n",
-            chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
-        );
-        
-        fs::write(&self.links_file_path, header)?;
-        println!("创建链接记录文件: {}", self.links_file_path.display());
+    fn read_legacy_file(&mut self, legacy_path: &Path) -> Result<()> {
+        let content =
+            fs::read_to_string(legacy_path).map_err(|e| Error::filesystem(legacy_path, e))?;
+
+        for line in content.lines().map(str::trim) {
+            let processed = line.starts_with("✅");
+            if !processed && !line.starts_with("❌") {
+                continue;
+            }
+            let Some(url) = Self::extract_url_from_line(line) else {
+                continue;
+            };
+            let time = Self::extract_time_from_line(line).unwrap_or_else(Utc::now);
+
+            let record = self
+                .records
+                .entry(url.clone())
+                .or_insert_with(|| LinkRecord::new(&url));
+            record.attempts += 1;
+            record.updated_at = time;
+
+            if processed {
+                let files = Self::extract_files_from_line(line);
+                if !files.is_empty() {
+                    record.files = Self::merge_saved_files(&record.files, files);
+                }
+                if let Some(languages) = Self::extract_languages_from_line(line) {
+                    let completed = record.languages.get_or_insert_with(Vec::new);
+                    for lang in languages {
+                        if !completed.contains(&lang) {
+                            completed.push(lang);
+                        }
+                    }
+                }
+                record.status = LinkStatus::Processed;
+                record.processed_at = Some(time);
+                record.last_error = None;
+            } else {
+                if !record.is_processed() {
+                    record.status = LinkStatus::Failed;
+                }
+                record.last_error = Some(Self::extract_error_from_line(line, time));
+            }
+        }
         Ok(())
     }
 
-    fn append_to_file(&self, url: &str, filename: &str) -> Result<()> {
-        let timestamp = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S");
-        let entry = format!("✅ {} | {} | {}\n", url, filename, timestamp);
-        
+    fn append_record(&self, url: &str) -> Result<()> {
+        let Some(record) = self.records.get(url) else {
+            return Ok(());
+        };
+        let line = serde_json::to_string(record).map_err(|e| self.json_error(e))?;
+
         fs::OpenOptions::new()
             .create(true)
             .append(true)
-            .open(&self.links_file_path)?
-            .write_all(entry.as_bytes())?;
-        
-        Ok(())
+            .open(&self.state_file_path)
+            .and_then(|mut file| file.write_all(format!("{}\n", line).as_bytes()))
+            .map_err(|e| Error::filesystem(&self.state_file_path, e))
     }
 
-    fn append_raw_line(&self, line: &str) -> Result<()> {
-        let timestamp = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S");
-        let entry = format!("{} | {}\n", line, timestamp);
-        
-        fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&self.links_file_path)?
-            .write_all(entry.as_bytes())?;
-        
+    fn write_state(&self) -> Result<()> {
+        let mut records: Vec<&LinkRecord> = self.records.values().collect();
+        records.sort_by_key(|record| record.updated_at);
+
+        let mut content = String::new();
+        for record in records {
+            content.push_str(&serde_json::to_string(record).map_err(|e| self.json_error(e))?);
+            content.push('\n');
+        }
+
+        // 先写临时文件再替换，避免写到一半时损坏状态文件
+        let temp_path = self.state_file_path.with_extension("jsonl.tmp");
+        fs::write(&temp_path, content).map_err(|e| Error::filesystem(&temp_path, e))?;
+        fs::rename(&temp_path, &self.state_file_path)
+            .map_err(|e| Error::filesystem(&self.state_file_path, e))?;
         Ok(())
     }
 
-    fn extract_url_from_line(&self, line: &str) -> Option<String> {
+    fn json_error(&self, e: serde_json::Error) -> Error {
+        Error::Custom(format!(
+            "Failed to serialize link record for {}: {}",
+            self.state_file_path.display(),
+            e
+        ))
+    }
+
+    fn extract_url_from_line(line: &str) -> Option<String> {
         if let Some(start) = line.find(' ') {
             let rest = &line[start + 1..];
             if let Some(end) = rest.find(" | ") {
@@ -127,35 +572,143 @@ impl LinksManager {
         None
     }
 
+    // 行格式：✅ URL [| langs: 语言] | 文件 [| 文件...] | 时间
+    fn extract_files_from_line(line: &str) -> Vec<PathBuf> {
+        let fields: Vec<&str> = line.split(" | ").skip(1).collect();
+        let files = match fields.split_last() {
+            Some((_, files)) if Self::extract_time_from_line(line).is_some() => files,
+            _ => &fields[..],
+        };
+
+        files
+            .iter()
+            .map(|file| file.trim())
+            .filter(|file| {
+                !file.is_empty() && *file != "unknown" && !file.starts_with(LANGUAGES_FIELD)
+            })
+            .map(PathBuf::from)
+            .collect()
+    }
+
+    fn extract_languages_from_line(line: &str) -> Option<Vec<String>> {
+        let field = line
+            .split(" | ")
+            .skip(1)
+            .find_map(|field| field.trim().strip_prefix(LANGUAGES_FIELD))?;
+        Some(
+            field
+                .split(',')
+                .map(|lang| lang.trim().to_lowercase())
+                .filter(|lang| !lang.is_empty())
+                .collect(),
+        )
+    }
+
+    // 行格式：❌ URL | ERROR: 错误信息 [| retryable|permanent] | 时间；没有分类的旧记录按可重试处理
+    fn extract_error_from_line(line: &str, at: DateTime<Utc>) -> LinkError {
+        let fields: Vec<&str> = line.split(" | ").skip(1).map(str::trim).collect();
+        let message = fields
+            .iter()
+            .find_map(|field| field.strip_prefix("ERROR: "))
+            .unwrap_or_default()
+            .to_string();
+        let retryable = !fields.contains(&"permanent");
+        LinkError {
+            message,
+            kind: default_error_kind(),
+            retryable,
+            at,
+        }
+    }
+
+    fn extract_time_from_line(line: &str) -> Option<DateTime<Utc>> {
+        let time = line.rsplit(" | ").next()?.trim();
+        NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M:%S")
+            .ok()
+            .map(|time| time.and_utc())
+    }
+
     fn extract_domain(url: &str) -> Result<String> {
-        let parsed_url = Url::parse(url)
-            .map_err(|e| crate::error::Error::Custom(format!("Invalid URL: {}", e)))?;
-        
-        let domain = parsed_url.host_str()
-            .ok_or_else(|| crate::error::Error::Custom("No host in URL".to_string()))?;
-        
+        let parsed_url = Url::parse(url)?;
+        let domain = parsed_url
+            .host_str()
+            .ok_or(Error::Parse(url::ParseError::EmptyHost))?;
         Ok(domain.to_string())
     }
 
     pub fn print_summary(&self) {
         println!("\n📊 处理摘要:");
-        println!("   已处理链接: {}", self.processed_links.len());
-        println!("   记录文件: {}", self.links_file_path.display());
+        println!("   已处理链接: {}", self.get_processed_count());
+        let failed = self.get_failed_count();
+        if failed > 0 {
+            println!("   失败链接: {}", failed);
+        }
+        if self.languages.len() > 1 {
+            for lang in &self.languages {
+                let completed = self
+                    .records
+                    .values()
+                    .filter(|record| {
+                        record.is_processed() && !self.pending_languages(&record.url).contains(lang)
+                    })
+                    .count();
+                println!("   已翻译为 {}: {}", lang, completed);
+            }
+        }
+        println!("   状态文件: {}", self.state_file_path.display());
+    }
+
+    pub fn should_process(&self, url: &str) -> bool {
+        !self.is_processed(url) && self.url_filter.is_allowed(url)
     }
 
     pub fn filter_unprocessed_urls(&self, urls: Vec<String>) -> Vec<String> {
         urls.into_iter()
-            .filter(|url| !self.is_processed(url))
+            .filter(|url| self.should_process(url))
+            .collect()
+    }
+
+    // 站点地图模式：未处理的链接全部保留；启用 lastmod 时，
+    // lastmod 晚于上次处理时间的已处理链接也会重新处理
+    pub fn filter_sitemap_entries(
+        &self,
+        entries: Vec<SitemapEntry>,
+        use_lastmod: bool,
+    ) -> Vec<String> {
+        entries
+            .into_iter()
+            .filter(|entry| self.url_filter.is_allowed(&entry.url))
+            .filter(|entry| {
+                if !self.is_processed(&entry.url) {
+                    return true;
+                }
+                if !use_lastmod {
+                    return false;
+                }
+                let processed_at = self
+                    .records
+                    .get(&entry.url)
+                    .and_then(|record| record.processed_at);
+                match (entry.lastmod, processed_at) {
+                    (Some(lastmod), Some(processed_at)) => lastmod > processed_at,
+                    _ => false,
+                }
+            })
+            .map(|entry| entry.url)
             .collect()
     }
 
     pub fn export_processed_links(&self, export_path: &Path) -> Result<()> {
+        let processed = self.get_all_processed_links();
         let mut export_content = String::new();
         export_content.push_str("# Processed Links Export\n");
-        export_content.push_str(&format!("# Exported on: {}\n", chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC")));
-        export_content.push_str(&format!("# Total links: {}\n\n", self.processed_links.len()));
+        export_content.push_str(&format!(
+            "# Exported on: {}\n",
+            chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
+        ));
+        export_content.push_str(&format!("# Total links: {}\n\n", processed.len()));
 
-        for url in &self.processed_links {
+        for url in &processed {
             export_content.push_str(&format!("{}\n", url));
         }
 
@@ -163,4 +716,379 @@ impl LinksManager {
         println!("导出已处理链接到: {}", export_path.display());
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SITE: &str = "https://example.com/docs/";
+
+    fn link_error(kind: &str, retryable: bool, hours_ago: i64) -> LinkError {
+        LinkError {
+            message: "failed".to_string(),
+            kind: kind.to_string(),
+            retryable,
+            at: Utc::now() - Duration::hours(hours_ago),
+        }
+    }
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+            .unwrap()
+            .and_utc()
+    }
+
+    #[test]
+    fn legacy_file_is_migrated_to_state_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let legacy = dir.path().join("example_com_links.txt");
+        fs::write(
+            &legacy,
+            "# Processed links\n\
+             ✅ https://example.com/docs/a | out/a.md | 2024-01-01 10:00:00\n\
+             ✅ https://example.com/docs/b | langs: zh, JA | out/b.md | out/zh/b.md | 2024-01-01 11:00:00\n\
+             ❌ https://example.com/docs/c | ERROR: connection reset | 2024-01-01 12:00:00\n\
+             ❌ https://example.com/docs/d | ERROR: not found | permanent | 2024-01-01 12:00:00\n\
+             ❌ https://example.com/docs/e | ERROR: timed out | retryable | 2024-01-01 12:00:00\n",
+        )
+        .unwrap();
+
+        let manager = LinksManager::new(dir.path(), SITE).unwrap();
+        assert!(!legacy.exists());
+        assert!(dir.path().join("example_com_links.txt.bak").exists());
+        assert!(manager.state_file_path().exists());
+
+        let a = manager.record("https://example.com/docs/a").unwrap();
+        assert_eq!(a.status, LinkStatus::Processed);
+        assert_eq!(a.files, vec![PathBuf::from("out/a.md")]);
+        assert_eq!(a.languages, None);
+        assert_eq!(a.processed_at, Some(utc("2024-01-01 10:00:00")));
+
+        let b = manager.record("https://example.com/docs/b").unwrap();
+        assert_eq!(
+            b.files,
+            vec![PathBuf::from("out/b.md"), PathBuf::from("out/zh/b.md")]
+        );
+        assert_eq!(b.languages, Some(vec!["zh".to_string(), "ja".to_string()]));
+
+        let c = manager.record("https://example.com/docs/c").unwrap();
+        assert_eq!(c.status, LinkStatus::Failed);
+        let error = c.last_error.as_ref().unwrap();
+        assert_eq!(error.message, "connection reset");
+        assert_eq!(error.kind, "unknown");
+        assert!(error.retryable);
+        assert_eq!(error.at, utc("2024-01-01 12:00:00"));
+
+        assert!(
+            !manager
+                .record("https://example.com/docs/d")
+                .unwrap()
+                .last_error
+                .as_ref()
+                .unwrap()
+                .retryable
+        );
+        assert!(
+            manager
+                .record("https://example.com/docs/e")
+                .unwrap()
+                .last_error
+                .as_ref()
+                .unwrap()
+                .retryable
+        );
+
+        // 再次打开时读取迁移后的状态文件
+        let reopened = LinksManager::new(dir.path(), SITE).unwrap();
+        assert_eq!(reopened.records().count(), 5);
+        assert_eq!(reopened.get_processed_count(), 2);
+        assert_eq!(reopened.get_failed_count(), 3);
+    }
+
+    #[test]
+    fn repeated_legacy_lines_accumulate_attempts() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("example_com_links.txt"),
+            "❌ https://example.com/docs/a | ERROR: timeout | 2024-01-01 10:00:00\n\
+             ✅ https://example.com/docs/a | out/a.md | 2024-01-01 11:00:00\n\
+             ✅ https://example.com/docs/b | out/b.md | 2024-01-01 10:00:00\n\
+             ❌ https://example.com/docs/b | ERROR: zh translation failed | 2024-01-01 11:00:00\n",
+        )
+        .unwrap();
+
+        let manager = LinksManager::new(dir.path(), SITE).unwrap();
+
+        let a = manager.record("https://example.com/docs/a").unwrap();
+        assert_eq!(a.attempts, 2);
+        assert_eq!(a.status, LinkStatus::Processed);
+        assert!(a.last_error.is_none());
+        assert_eq!(a.updated_at, utc("2024-01-01 11:00:00"));
+
+        // 保存过的链接之后失败仍算已处理，只保留错误
+        let b = manager.record("https://example.com/docs/b").unwrap();
+        assert_eq!(b.attempts, 2);
+        assert_eq!(b.status, LinkStatus::Processed);
+        assert_eq!(
+            b.last_error.as_ref().unwrap().message,
+            "zh translation failed"
+        );
+    }
+
+    #[test]
+    fn state_file_is_compacted_when_mostly_stale() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = dir.path().join("example_com_state.jsonl");
+        let mut record = LinkRecord::new("https://example.com/docs/a");
+        let mut lines = String::new();
+        for attempt in 1..=5 {
+            record.attempts = attempt;
+            lines.push_str(&serde_json::to_string(&record).unwrap());
+            lines.push('\n');
+        }
+        lines.push_str("not json\n");
+        fs::write(&state, lines).unwrap();
+
+        let manager = LinksManager::new(dir.path(), SITE).unwrap();
+        assert_eq!(
+            manager
+                .record("https://example.com/docs/a")
+                .unwrap()
+                .attempts,
+            5
+        );
+
+        let content = fs::read_to_string(&state).unwrap();
+        assert_eq!(content.lines().count(), 1);
+        assert!(content.contains("\"attempts\":5"));
+    }
+
+    #[test]
+    fn state_file_is_not_compacted_when_mostly_current() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = dir.path().join("example_com_state.jsonl");
+        let lines: String = ["a", "b", "a"]
+            .iter()
+            .map(|page| {
+                format!(
+                    "{}\n",
+                    serde_json::to_string(&LinkRecord::new(&format!("{}{}", SITE, page))).unwrap()
+                )
+            })
+            .collect();
+        fs::write(&state, &lines).unwrap();
+
+        LinksManager::new(dir.path(), SITE).unwrap();
+        assert_eq!(fs::read_to_string(&state).unwrap(), lines);
+    }
+
+    #[test]
+    fn failure_filter_matches_error_types() {
+        let filter = FailureFilter {
+            error_types: vec!["Timeout".to_string()],
+            ..Default::default()
+        };
+        assert!(filter.matches(&link_error("timeout", true, 1), 1));
+        assert!(!filter.matches(&link_error("http_status", true, 1), 1));
+
+        let filter = FailureFilter {
+            error_types: vec!["permanent".to_string()],
+            ..Default::default()
+        };
+        assert!(filter.matches(&link_error("rejected", false, 1), 1));
+        assert!(!filter.matches(&link_error("timeout", true, 1), 1));
+
+        let filter = FailureFilter {
+            error_types: vec!["retryable".to_string(), "rejected".to_string()],
+            ..Default::default()
+        };
+        assert!(filter.matches(&link_error("timeout", true, 1), 1));
+        assert!(filter.matches(&link_error("rejected", false, 1), 1));
+        assert!(!filter.matches(&link_error("protocol", false, 1), 1));
+    }
+
+    #[test]
+    fn failure_filter_matches_age_window() {
+        let filter = FailureFilter {
+            min_age: Some(Duration::hours(2)),
+            max_age: Some(Duration::hours(24)),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&link_error("timeout", true, 1), 1));
+        assert!(filter.matches(&link_error("timeout", true, 3), 1));
+        assert!(!filter.matches(&link_error("timeout", true, 48), 1));
+    }
+
+    #[test]
+    fn failure_filter_stops_at_max_attempts() {
+        let filter = FailureFilter {
+            max_attempts: 3,
+            ..Default::default()
+        };
+        assert!(filter.matches(&link_error("timeout", true, 1), 2));
+        assert!(!filter.matches(&link_error("timeout", true, 1), 3));
+    }
+
+    #[test]
+    fn failed_urls_include_pages_with_pending_languages() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = LinksManager::new(dir.path(), SITE)
+            .unwrap()
+            .with_languages(vec!["zh".to_string(), "ja".to_string()]);
+        let timeout = Error::Timeout {
+            service: "DeepLX".to_string(),
+        };
+        let rejected = Error::rejected("local", "no content");
+
+        // 从未保存
+        manager.begin_attempt("https://example.com/docs/a").unwrap();
+        manager
+            .mark_as_failed("https://example.com/docs/a", "timed out", &timeout)
+            .unwrap();
+
+        // 已保存，但 ja 翻译失败
+        manager.begin_attempt("https://example.com/docs/b").unwrap();
+        manager
+            .mark_as_processed(
+                "https://example.com/docs/b",
+                &[],
+                &["zh".to_string()],
+                "hash",
+            )
+            .unwrap();
+        manager
+            .mark_as_failed("https://example.com/docs/b", "ja: timed out", &timeout)
+            .unwrap();
+
+        // 失败后补全
+        manager.begin_attempt("https://example.com/docs/c").unwrap();
+        manager
+            .mark_as_failed("https://example.com/docs/c", "timed out", &timeout)
+            .unwrap();
+        manager
+            .mark_as_processed(
+                "https://example.com/docs/c",
+                &[],
+                &["zh".to_string(), "ja".to_string()],
+                "hash",
+            )
+            .unwrap();
+
+        manager.begin_attempt("https://example.com/docs/d").unwrap();
+        manager
+            .mark_as_failed("https://example.com/docs/d", "no content", &rejected)
+            .unwrap();
+
+        assert!(!manager.is_processed("https://example.com/docs/b"));
+        assert_eq!(
+            manager.pending_languages("https://example.com/docs/b"),
+            vec!["ja".to_string()]
+        );
+
+        let mut all = manager.failed_urls(&FailureFilter::default());
+        all.sort();
+        assert_eq!(
+            all,
+            vec![
+                "https://example.com/docs/a".to_string(),
+                "https://example.com/docs/b".to_string(),
+                "https://example.com/docs/d".to_string(),
+            ]
+        );
+
+        let retryable = FailureFilter {
+            error_types: vec!["retryable".to_string()],
+            ..Default::default()
+        };
+        let mut urls = manager.failed_urls(&retryable);
+        urls.sort();
+        assert_eq!(
+            urls,
+            vec![
+                "https://example.com/docs/a".to_string(),
+                "https://example.com/docs/b".to_string()
+            ]
+        );
+
+        let single_attempt = FailureFilter {
+            max_attempts: 1,
+            ..Default::default()
+        };
+        assert!(manager.failed_urls(&single_attempt).is_empty());
+    }
+
+    #[test]
+    fn attempts_only_count_until_a_page_is_complete() {
+        let dir = tempfile::tempdir().unwrap();
+        let url = "https://example.com/docs/a";
+        let timeout = Error::Timeout {
+            service: "DeepLX".to_string(),
+        };
+        let mut manager = LinksManager::new(dir.path(), SITE)
+            .unwrap()
+            .with_languages(vec!["zh".to_string(), "ja".to_string()]);
+
+        manager.begin_attempt(url).unwrap();
+        manager.mark_as_failed(url, "timed out", &timeout).unwrap();
+        manager.begin_attempt(url).unwrap();
+        manager
+            .mark_as_processed(url, &[], &["zh".to_string()], "hash")
+            .unwrap();
+        assert_eq!(manager.record(url).unwrap().attempts, 2);
+
+        // 还缺 ja，补译仍计入尝试次数
+        manager.begin_attempt(url).unwrap();
+        manager
+            .mark_as_processed(url, &[], &["ja".to_string()], "hash")
+            .unwrap();
+        assert_eq!(manager.record(url).unwrap().attempts, 3);
+
+        // 已全部完成，内容更新后重新处理不再累计
+        manager.begin_attempt(url).unwrap();
+        manager
+            .mark_as_processed(url, &[], &["zh".to_string(), "ja".to_string()], "new-hash")
+            .unwrap();
+        manager.begin_attempt(url).unwrap();
+        let record = manager.record(url).unwrap();
+        assert_eq!(record.attempts, 3);
+        assert_eq!(record.status, LinkStatus::Processed);
+    }
+
+    #[test]
+    fn pages_saved_without_translation_are_pending_once_translation_is_enabled() {
+        let dir = tempfile::tempdir().unwrap();
+        let url = "https://example.com/docs/a";
+
+        let mut manager = LinksManager::new(dir.path(), SITE).unwrap();
+        manager.begin_attempt(url).unwrap();
+        manager.mark_as_processed(url, &[], &[], "hash").unwrap();
+        assert!(manager.is_processed(url));
+
+        let manager = LinksManager::new(dir.path(), SITE)
+            .unwrap()
+            .with_languages(vec!["zh".to_string()]);
+        assert_eq!(manager.record(url).unwrap().languages, Some(Vec::new()));
+        assert_eq!(manager.pending_languages(url), vec!["zh".to_string()]);
+        assert!(manager.should_process(url));
+    }
+
+    #[test]
+    fn migrated_records_without_languages_count_as_translated() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("example_com_links.txt"),
+            "✅ https://example.com/docs/a | out/a.md | 2024-01-01 10:00:00\n",
+        )
+        .unwrap();
+
+        let manager = LinksManager::new(dir.path(), SITE)
+            .unwrap()
+            .with_languages(vec!["zh".to_string()]);
+        assert!(
+            manager
+                .pending_languages("https://example.com/docs/a")
+                .is_empty()
+        );
+    }
+}
@@ -1,52 +1,205 @@
 use markdown_downloader::{
-    cli, Crawler, MarkdownConverter, FileManager, ProgressTracker, 
-    TranslationService, FolderManager, LinksManager, Result
+    AssetDownloader, Crawler, Error, FolderManager, LinkRewriter, LinksManager, MarkdownConverter,
+    ProgressTracker, RateLimiter, Result, TranslationService, UrlFilter,
+    assets::LocalAssets,
+    cli::{self, CliCommand},
+    config::Config,
+    config_file::ConfigFile,
+    crawler::CrawledPage,
+    folder_manager::split_front_matter,
+    glossary::Glossary,
+    status::StatusReport,
+    translation_memory::TranslationMemory,
+    types::{ExistingFilePolicy, PageMetadata},
 };
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Mutex;
 
 #[tokio::main]
 async fn main() -> Result<()> {
     // 解析命令行参数
-    let config = cli::parse_args()?;
+    match cli::parse_args()? {
+        CliCommand::Run(config) => run(config).await,
+        CliCommand::ListUrls(config) => list_urls(&config).await,
+        CliCommand::TranslateDir(config) => translate_directory(config).await,
+        CliCommand::Status {
+            output_dir,
+            url,
+            json,
+        } => {
+            // 只读取状态文件
+            let links_manager = LinksManager::open_read_only(Path::new(&output_dir), &url)?;
+            let report = StatusReport::from_links(&links_manager);
+            if json {
+                println!("{}", report.to_json()?);
+            } else {
+                report.print();
+            }
+            Ok(())
+        }
+        CliCommand::ExportLinks {
+            output_dir,
+            url,
+            path,
+        } => LinksManager::open_read_only(Path::new(&output_dir), &url)?
+            .export_processed_links(Path::new(&path)),
+        CliCommand::ExportMemory { output_dir, path } => {
+            let memory = TranslationMemory::open(Path::new(&output_dir))?;
+            let count = memory.export(Path::new(&path))?;
+            println!("已导出 {} 条翻译记忆到: {}", count, path);
+            Ok(())
+        }
+        CliCommand::PruneMemory { output_dir, days } => {
+            let memory = TranslationMemory::open(Path::new(&output_dir))?;
+            let removed = memory.prune(days)?;
+            println!(
+                "已删除 {} 条超过 {} 天未使用的翻译记忆，剩余 {} 条",
+                removed,
+                days,
+                memory.len()
+            );
+            Ok(())
+        }
+        CliCommand::InitConfig { path } => {
+            ConfigFile::create_example_config(&path)?;
+            println!("已生成示例配置文件: {}", path);
+            Ok(())
+        }
+    }
+}
 
+// 只爬取并列出范围内的页面，不转换也不记录状态
+async fn list_urls(config: &Config) -> Result<()> {
+    let mut crawler = Crawler::new(config)?;
+    let mut urls: Vec<String> = if config.sitemap.enabled {
+        crawler
+            .crawl_sitemap()
+            .await?
+            .into_iter()
+            .map(|entry| entry.url)
+            .collect()
+    } else {
+        Vec::new()
+    };
+    if urls.is_empty() {
+        urls = crawler.crawl().await?;
+    }
+
+    for url in &urls {
+        println!("{}", url);
+    }
+    eprintln!("共 {} 个页面", urls.len());
+    Ok(())
+}
+
+async fn run(config: Config) -> Result<()> {
     // 初始化组件
-    let mut crawler = Crawler::new(&config)?;
-    let converter = MarkdownConverter::new(config.clone());
-    let _file_manager = FileManager::new(config.output_dir.clone())?;
-    
+    let crawler = Crawler::new(&config)?;
+    // 转换器和翻译服务共用一个令牌桶，遇到 429 时一起降速
+    let rate_limiter = RateLimiter::from_config(&config.rate_limit);
+    let converter = MarkdownConverter::new(config.clone()).with_rate_limiter(rate_limiter.clone());
+    create_output_dir(&config.output_dir)?;
+
     // 初始化翻译相关组件
-    let translation_service = if config.translation.enabled {
-        Some(TranslationService::new(config.translation.clone()))
+    let translators = Translators::new(&config, &rate_limiter)?;
+    let translation_services = translators.services.clone();
+
+    let links_manager = LinksManager::new(Path::new(&config.output_dir), &config.url)?
+        .with_url_filter(UrlFilter::new(&config.url_filter)?)
+        .with_languages(
+            translation_services
+                .iter()
+                .map(|service| service.target_lang().to_string())
+                .collect(),
+        )
+        .with_config_fingerprint(
+            config
+                .translation
+                .enabled
+                .then(|| config.translation.fingerprint()),
+        );
+
+    let mut folder_manager = FolderManager::new(
+        PathBuf::from(&config.output_dir),
+        config.translation.clone(),
+    )
+    .with_output_config(config.output.clone());
+    if config.output.rewrite_links && config.output.on_existing == ExistingFilePolicy::Version {
+        folder_manager = folder_manager
+            .with_link_rewriter(Arc::new(LinkRewriter::new(&links_manager.saved_files())));
+    }
+
+    let asset_downloader = if config.assets.enabled {
+        Some(AssetDownloader::new(&config, folder_manager.clone()))
     } else {
         None
     };
-    
-    let folder_manager = FolderManager::new(
-        std::path::PathBuf::from(&config.output_dir),
-        config.translation.clone()
-    );
-    
-    let links_manager = LinksManager::new(
-        std::path::Path::new(&config.output_dir),
-        &config.url
-    )?;
-
-    // 爬取网站
-    let all_urls = crawler.crawl().await?;
-    
-    // 过滤未处理的链接
-    let urls = links_manager.filter_unprocessed_urls(all_urls);
-    let total_pages = urls.len();
-    
-    println!("发现 {} 个新链接需要处理", total_pages);
-    
-    // 创建文件夹结构
-    if !urls.is_empty() {
-        folder_manager.create_all_folders(&urls[0])?;
-    }
+
+    // 站点地图模式优先，找不到站点地图时退回到普通爬取
+    let sitemap_entries =
+        if config.sitemap.enabled && config.retry_failed.is_none() && config.page_urls.is_none() {
+            println!("从站点地图发现页面...");
+            crawler.crawl_sitemap().await?
+        } else {
+            Vec::new()
+        };
+
+    use futures::stream::{self, BoxStream, StreamExt};
+
+    let links_manager = Arc::new(Mutex::new(links_manager));
+
+    // 页面来源：重试模式只处理记录的失败链接；指定页面时不检查是否已处理；
+    // 站点地图给出完整列表；普通爬取则边爬取边输出
+    let (page_source, known_total): (BoxStream<'static, CrawledPage>, usize) =
+        if let Some(filter) = &config.retry_failed {
+            let urls = links_manager.lock().await.failed_urls(filter);
+            println!("状态文件中有 {} 个失败链接需要重新处理", urls.len());
+            let total = urls.len();
+            (
+                stream::iter(urls.into_iter().map(CrawledPage::from_url)).boxed(),
+                total,
+            )
+        } else if let Some(urls) = &config.page_urls {
+            println!("不爬取网站，只处理指定的 {} 个页面", urls.len());
+            let total = urls.len();
+            (
+                stream::iter(urls.clone().into_iter().map(CrawledPage::from_url)).boxed(),
+                total,
+            )
+        } else if !sitemap_entries.is_empty() {
+            println!("站点地图共列出 {} 个页面", sitemap_entries.len());
+            let urls = links_manager
+                .lock()
+                .await
+                .filter_sitemap_entries(sitemap_entries, config.sitemap.use_lastmod);
+            println!("发现 {} 个新链接需要处理", urls.len());
+            let total = urls.len();
+            (
+                stream::iter(urls.into_iter().map(CrawledPage::from_url)).boxed(),
+                total,
+            )
+        } else {
+            if config.sitemap.enabled {
+                println!("未找到可用的站点地图，改为爬取网站");
+            }
+            let receiver = crawler.crawl_stream(config.batch_size * 2)?;
+            let links_manager = links_manager.clone();
+            let pages = stream::unfold(receiver, |mut receiver| async move {
+                receiver.recv().await.map(|page| (page, receiver))
+            })
+            // 过滤已处理的链接
+            .filter(move |page| {
+                let links_manager = links_manager.clone();
+                let url = page.url.clone();
+                async move { links_manager.lock().await.should_process(&url) }
+            });
+            (pages.boxed(), 0)
+        };
 
     // 初始化进度追踪器
     let progress = ProgressTracker::new(
-        total_pages,
+        known_total,
         config.batch_size,
         config.wait_time,
         config.request_delay,
@@ -54,112 +207,486 @@ async fn main() -> Result<()> {
 
     progress.log_start(&config.url);
 
-    // 并发处理URL
-    use futures::stream::{self, StreamExt};
-    use std::sync::Arc;
-    use tokio::sync::Mutex;
-    
-    let links_manager = Arc::new(Mutex::new(links_manager));
     let progress = Arc::new(Mutex::new(progress));
-    
+
     // 限制并发数量，基于429错误经验进一步保守设置
     let concurrency = std::cmp::min(config.batch_size, 3);
-    
+
+    let pipeline = PagePipeline {
+        converter,
+        translation_services,
+        folder_manager: folder_manager.clone(),
+        asset_downloader,
+        links_manager: links_manager.clone(),
+        progress: progress.clone(),
+        request_delay: config.request_delay,
+    };
+
     // 分批处理以避免API压力
     let mut processed = 0;
-    for batch in urls.chunks(config.batch_size) {
+    // 因可重试错误失败的页面，全部批次处理完后重新处理
+    let mut retry_queue: Vec<CrawledPage> = Vec::new();
+    let mut batches = page_source.chunks(config.batch_size);
+    while let Some(batch) = batches.next().await {
         if processed > 0 {
             println!("等待 {} 秒后继续处理下一批...", config.wait_time);
             tokio::time::sleep(tokio::time::Duration::from_secs(config.wait_time)).await;
+        } else {
+            // 创建文件夹结构
+            folder_manager.create_all_folders(&batch[0].url)?;
+        }
+
+        if known_total == 0 {
+            progress.lock().await.add_discovered(batch.len());
         }
-        
-        stream::iter(batch.iter().enumerate())
-            .map(|(batch_index, url)| {
+
+        let failed: Vec<Option<CrawledPage>> = stream::iter(batch.iter().enumerate())
+            .map(|(batch_index, page)| {
+                let pipeline = &pipeline;
                 let index = processed + batch_index;
-                let converter = converter.clone();
-                let translation_service = translation_service.clone();
-                let folder_manager = folder_manager.clone();
-                let links_manager = links_manager.clone();
-                let progress = progress.clone();
-                let config = config.clone();
-                let url = url.clone();
-                
                 async move {
-                    {
-                        let progress_guard = progress.lock().await;
-                        progress_guard.log_processing(index, &url);
-                    }
-                    
-                    // 转换为 Markdown
-                    let markdown_result = converter.convert_url_to_markdown(&url).await;
-                    
-                    match markdown_result {
-                        Ok(markdown_content) => {
-                            let mut translated_content = None;
-                            
-                            // 如果启用翻译，进行翻译
-                            if let Some(translator) = &translation_service {
-                                match translator.translate(&markdown_content).await {
-                                    Ok(translated) => {
-                                        translated_content = Some(translated);
-                                        println!("翻译完成: {}", url);
-                                    }
-                                    Err(e) => {
-                                        eprintln!("翻译失败: {} - {}", url, e);
-                                        let mut links_guard = links_manager.lock().await;
-                                        let _ = links_guard.mark_as_failed(&url, &format!("Translation failed: {}", e));
-                                        let mut progress_guard = progress.lock().await;
-                                        progress_guard.log_error(&e.to_string());
-                                        return;
-                                    }
-                                }
-                            }
-
-                            // 保存文件
-                            match folder_manager.save_content(&url, &markdown_content, translated_content.as_deref()) {
-                                Ok(saved_files) => {
-                                    let filename = saved_files.first().unwrap_or(&"unknown".to_string()).clone();
-                                    let mut links_guard = links_manager.lock().await;
-                                    let _ = links_guard.mark_as_processed(&url, &filename);
-                                    let mut progress_guard = progress.lock().await;
-                                    progress_guard.log_success(&format!("Saved {} files", saved_files.len()));
-                                }
-                                Err(e) => {
-                                    let mut links_guard = links_manager.lock().await;
-                                    let _ = links_guard.mark_as_failed(&url, &e.to_string());
-                                    let mut progress_guard = progress.lock().await;
-                                    progress_guard.log_error(&e.to_string());
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            let mut links_guard = links_manager.lock().await;
-                            let _ = links_guard.mark_as_failed(&url, &e.to_string());
-                            let mut progress_guard = progress.lock().await;
-                            progress_guard.log_error(&e.to_string());
-                        }
-                    }
-                    
-                    // 添加请求间延迟
-                    tokio::time::sleep(tokio::time::Duration::from_millis(config.request_delay)).await;
+                    pipeline
+                        .progress
+                        .lock()
+                        .await
+                        .log_processing(index, &page.url);
+                    pipeline.process(page).await.then(|| page.clone())
                 }
             })
             .buffer_unordered(concurrency)
-            .collect::<Vec<_>>()
+            .collect()
             .await;
-            
+        retry_queue.extend(failed.into_iter().flatten());
+
         processed += batch.len();
     }
 
+    if processed == 0 {
+        println!("没有新链接需要处理");
+    }
+
+    // 运行结束前重新处理因限流、超时等可重试错误失败的页面
+    for round in 1..=config.retry.page_retries {
+        if retry_queue.is_empty() {
+            break;
+        }
+        println!(
+            "第 {} 轮重试: {} 个页面处理失败，等待 {} 秒后重新处理...",
+            round,
+            retry_queue.len(),
+            config.wait_time
+        );
+        tokio::time::sleep(tokio::time::Duration::from_secs(config.wait_time)).await;
+
+        let count = retry_queue.len();
+        let failed: Vec<Option<CrawledPage>> = stream::iter(retry_queue.iter().enumerate())
+            .map(|(index, page)| {
+                let pipeline = &pipeline;
+                async move {
+                    pipeline
+                        .progress
+                        .lock()
+                        .await
+                        .log_retrying(index, count, &page.url);
+                    let failed = pipeline.process(page).await;
+                    if !failed {
+                        pipeline.progress.lock().await.log_recovered();
+                    }
+                    failed.then(|| page.clone())
+                }
+            })
+            .buffer_unordered(concurrency)
+            .collect()
+            .await;
+        retry_queue = failed.into_iter().flatten().collect();
+    }
+    if !retry_queue.is_empty() {
+        println!(
+            "{} 个页面重试后仍然失败，已记录到状态文件",
+            retry_queue.len()
+        );
+    }
+
+    // 把指向已下载页面的链接改写为本地相对路径
+    if config.output.rewrite_links {
+        let links_guard = links_manager.lock().await;
+        LinkRewriter::new(&links_guard.saved_files()).rewrite_all();
+    }
+
     {
         let progress_guard = progress.lock().await;
         progress_guard.log_completion();
     }
-    
+
     {
         let links_guard = links_manager.lock().await;
         links_guard.print_summary();
     }
-    
+
+    translators.finish(&config.output_dir);
+
     Ok(())
 }
+
+// 翻译本地目录中的 Markdown 文件，译文和双语对照按原目录结构保存；
+// 原文和翻译设置都没有变化的文件跳过
+async fn translate_directory(config: Config) -> Result<()> {
+    let source_dir = config
+        .source_dir
+        .clone()
+        .ok_or_else(|| Error::Custom("Source directory is required".to_string()))?;
+    let source_dir = source_dir
+        .canonicalize()
+        .map_err(|e| Error::filesystem(&source_dir, e))?;
+    let name = source_dir
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "local".to_string());
+    let output_dir = PathBuf::from(&config.output_dir);
+    create_output_dir(&config.output_dir)?;
+
+    let rate_limiter = RateLimiter::from_config(&config.rate_limit);
+    let translators = Translators::new(&config, &rate_limiter)?;
+    let folder_manager = FolderManager::new(output_dir.clone(), config.translation.clone())
+        .with_output_config(config.output.clone());
+    let mut links_manager = LinksManager::for_directory(&output_dir, &name)?
+        .with_languages(
+            translators
+                .services
+                .iter()
+                .map(|service| service.target_lang().to_string())
+                .collect(),
+        )
+        .with_config_fingerprint(Some(config.translation.fingerprint()));
+
+    // 输出目录在源目录内时不翻译之前生成的文件
+    let excluded = output_dir.canonicalize().ok();
+    let mut files = Vec::new();
+    collect_markdown_files(&source_dir, excluded.as_deref(), &mut files)?;
+    files.sort();
+    println!(
+        "在 {} 中找到 {} 个 Markdown 文件",
+        source_dir.display(),
+        files.len()
+    );
+
+    let mut progress = ProgressTracker::new(
+        files.len(),
+        config.batch_size,
+        config.wait_time,
+        config.request_delay,
+    );
+    let mut skipped = 0;
+    for (index, path) in files.iter().enumerate() {
+        let relative_path = path.strip_prefix(&source_dir).unwrap_or(path);
+        let key = relative_path.to_string_lossy().replace('\\', "/");
+        let original = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => {
+                let e = Error::filesystem(path, e);
+                let _ = links_manager.mark_as_failed(&key, &e.to_string(), &e);
+                progress.log_error(&e.to_string());
+                continue;
+            }
+        };
+
+        let content_hash = PageMetadata::new(&key, &original).content_hash;
+        let languages = links_manager.outdated_languages(&key, &content_hash);
+        if languages.is_empty() {
+            skipped += 1;
+            continue;
+        }
+
+        progress.log_processing(index, &key);
+        let _ = links_manager.begin_attempt(&key);
+        // 源文件自带的 front matter 不翻译，保存时原样保留
+        let body = split_front_matter(&original).1;
+        let pending_translators: Vec<&TranslationService> = translators
+            .services
+            .iter()
+            .filter(|translator| {
+                languages
+                    .iter()
+                    .any(|lang| lang == translator.target_lang())
+            })
+            .collect();
+        let results = futures::future::join_all(
+            pending_translators
+                .iter()
+                .map(|translator| translator.translate_page(&key, body)),
+        )
+        .await;
+        // 发出过翻译请求就等待，无论之后保存成功与否
+        tokio::time::sleep(tokio::time::Duration::from_millis(config.request_delay)).await;
+
+        let mut translations = Vec::new();
+        for (translator, result) in pending_translators.iter().zip(results) {
+            let lang = translator.target_lang();
+            match result {
+                Ok(translated) => translations.push((lang.to_string(), translated)),
+                Err(e) => {
+                    eprintln!("翻译失败 ({}): {} - {}", lang, key, e);
+                    let message = format!("Translation ({}) failed: {}", lang, e);
+                    let _ = links_manager.mark_as_failed(&key, &message, &e);
+                    progress.log_error(&e.to_string());
+                }
+            }
+        }
+        if translations.is_empty() {
+            continue;
+        }
+
+        match folder_manager.save_local_translations(&name, relative_path, &original, &translations)
+        {
+            Ok(saved_files) => {
+                let completed: Vec<String> =
+                    translations.iter().map(|(lang, _)| lang.clone()).collect();
+                let _ =
+                    links_manager.mark_as_processed(&key, &saved_files, &completed, &content_hash);
+                progress.log_success(&format!("Saved {} files", saved_files.len()));
+            }
+            Err(e) => {
+                let _ = links_manager.mark_as_failed(&key, &e.to_string(), &e);
+                progress.log_error(&e.to_string());
+            }
+        }
+    }
+
+    if skipped > 0 {
+        println!("\n{} 个文件的译文已是最新，已跳过", skipped);
+    }
+    progress.log_completion();
+    links_manager.print_summary();
+    translators.finish(&config.output_dir);
+    Ok(())
+}
+
+// 状态文件、翻译记忆等都写在输出目录下
+fn create_output_dir(output_dir: &str) -> Result<()> {
+    std::fs::create_dir_all(output_dir).map_err(|e| Error::filesystem(Path::new(output_dir), e))
+}
+
+// 递归查找 .md 文件，跳过隐藏目录（如 .git）和 excluded 目录
+fn collect_markdown_files(
+    dir: &Path,
+    excluded: Option<&Path>,
+    files: &mut Vec<PathBuf>,
+) -> Result<()> {
+    for entry in std::fs::read_dir(dir).map_err(|e| Error::filesystem(dir, e))? {
+        let path = entry?.path();
+        if path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+        {
+            continue;
+        }
+        if path.is_dir() {
+            if excluded != Some(path.as_path()) {
+                collect_markdown_files(&path, excluded, files)?;
+            }
+        } else if path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("md"))
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+// 每个目标语言一个翻译服务，共用限流器、翻译记忆和术语表
+struct Translators {
+    services: Vec<TranslationService>,
+    memory: Option<Arc<TranslationMemory>>,
+    glossary: Option<Arc<Glossary>>,
+}
+
+impl Translators {
+    fn new(config: &Config, rate_limiter: &RateLimiter) -> Result<Self> {
+        if !config.translation.enabled {
+            return Ok(Self {
+                services: Vec::new(),
+                memory: None,
+                glossary: None,
+            });
+        }
+
+        let memory = if config.translation.memory {
+            Some(Arc::new(TranslationMemory::open(Path::new(
+                &config.output_dir,
+            ))?))
+        } else {
+            None
+        };
+        let glossary = match &config.translation.glossary {
+            Some(path) => Some(Arc::new(Glossary::load(Path::new(path))?)),
+            None => None,
+        };
+
+        let mut service = TranslationService::new(config.translation.clone())
+            .with_rate_limiter(rate_limiter.clone())
+            .with_retry_config(config.retry.translate.clone());
+        if let Some(memory) = &memory {
+            service = service.with_memory(memory.clone());
+        }
+        if let Some(glossary) = &glossary {
+            service = service.with_glossary(glossary.clone());
+        }
+        let services = config
+            .translation
+            .target_langs
+            .iter()
+            .map(|lang| service.for_language(lang))
+            .collect();
+
+        Ok(Self {
+            services,
+            memory,
+            glossary,
+        })
+    }
+
+    // 运行结束时保存翻译记忆并写入术语报告
+    fn finish(&self, output_dir: &str) {
+        if let Some(memory) = &self.memory {
+            if let Err(e) = memory.save() {
+                eprintln!("保存翻译记忆失败: {}", e);
+            }
+            println!(
+                "   翻译记忆: {} 条 ({})",
+                memory.len(),
+                memory.path().display()
+            );
+        }
+
+        if let Some(glossary) = &self.glossary {
+            let report_path = Path::new(output_dir).join("glossary_report.md");
+            match glossary.write_report(&report_path) {
+                Ok(0) => println!("   术语表: 所有术语均按术语表翻译"),
+                Ok(count) => println!(
+                    "   术语表: {} 处术语未按术语表翻译，详见 {}",
+                    count,
+                    report_path.display()
+                ),
+                Err(e) => eprintln!("写入术语报告失败: {}", e),
+            }
+        }
+    }
+}
+
+// 单个页面的转换、翻译和保存流程，并发任务共用同一个实例
+struct PagePipeline {
+    converter: MarkdownConverter,
+    translation_services: Vec<TranslationService>,
+    folder_manager: FolderManager,
+    asset_downloader: Option<AssetDownloader>,
+    links_manager: Arc<Mutex<LinksManager>>,
+    progress: Arc<Mutex<ProgressTracker>>,
+    request_delay: u64,
+}
+
+impl PagePipeline {
+    // 返回 true 表示页面（或其中某个语言）因可重试的错误失败，需要重新处理
+    async fn process(&self, page: &CrawledPage) -> bool {
+        let url = &page.url;
+        let mut needs_retry = false;
+        let _ = self.links_manager.lock().await.begin_attempt(url);
+
+        // 转换为 Markdown
+        match self.converter.convert_page(url, page.html.as_deref()).await {
+            Ok(markdown_content) => {
+                // 只翻译尚未完成的目标语言；全部完成的链接（站点地图 lastmod 更新）重新翻译所有语言
+                let pending = self.links_manager.lock().await.pending_languages(url);
+                let translators: Vec<&TranslationService> = self
+                    .translation_services
+                    .iter()
+                    .filter(|translator| {
+                        pending.is_empty()
+                            || pending.iter().any(|lang| lang == translator.target_lang())
+                    })
+                    .collect();
+
+                let results = futures::future::join_all(
+                    translators
+                        .iter()
+                        .map(|translator| translator.translate_page(url, &markdown_content)),
+                )
+                .await;
+
+                let mut translations = Vec::new();
+                for (translator, result) in translators.iter().zip(results) {
+                    let lang = translator.target_lang();
+                    match result {
+                        Ok(translated) => {
+                            println!("翻译完成 ({}): {}", lang, url);
+                            translations.push((lang.to_string(), translated));
+                        }
+                        Err(e) => {
+                            eprintln!("翻译失败 ({}): {} - {}", lang, url, e);
+                            needs_retry |= e.is_retryable();
+                            let message = format!("Translation ({}) failed: {}", lang, e);
+                            self.record_failure(url, &message, &e).await;
+                        }
+                    }
+                }
+
+                // 所有语言都失败时不保存，下次运行重新处理
+                if translators.is_empty() || !translations.is_empty() {
+                    // 下载页面引用的图片等资源
+                    let assets = match &self.asset_downloader {
+                        Some(downloader) => {
+                            downloader.download_assets(url, &markdown_content).await
+                        }
+                        None => LocalAssets::default(),
+                    };
+
+                    // 保存文件
+                    match self.folder_manager.save_content_with_assets(
+                        url,
+                        &markdown_content,
+                        &translations,
+                        &assets,
+                    ) {
+                        Ok(saved_files) => {
+                            let completed: Vec<String> =
+                                translations.iter().map(|(lang, _)| lang.clone()).collect();
+                            let content_hash =
+                                PageMetadata::new(url, &markdown_content).content_hash;
+                            let _ = self.links_manager.lock().await.mark_as_processed(
+                                url,
+                                &saved_files,
+                                &completed,
+                                &content_hash,
+                            );
+                            self.progress
+                                .lock()
+                                .await
+                                .log_success(&format!("Saved {} files", saved_files.len()));
+                        }
+                        Err(e) => {
+                            needs_retry |= e.is_retryable();
+                            self.record_failure(url, &e.to_string(), &e).await;
+                        }
+                    }
+                }
+            }
+            Err(e) => {
+                needs_retry = e.is_retryable();
+                self.record_failure(url, &e.to_string(), &e).await;
+            }
+        }
+
+        // 添加请求间延迟
+        tokio::time::sleep(tokio::time::Duration::from_millis(self.request_delay)).await;
+        needs_retry
+    }
+
+    async fn record_failure(&self, url: &str, message: &str, error: &Error) {
+        let _ = self
+            .links_manager
+            .lock()
+            .await
+            .mark_as_failed(url, message, error);
+        self.progress.lock().await.log_error(&error.to_string());
+    }
+}
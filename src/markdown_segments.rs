@@ -0,0 +1,404 @@
+use pulldown_cmark::{Event, LinkType, Options, Parser, Tag};
+use regex::Regex;
+use std::ops::Range;
+use std::sync::LazyLock;
+
+// 译文中的占位符，容忍翻译服务在括号内插入空格
+static PLACEHOLDER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"⟦\s*(\d+)\s*⟧").expect("valid regex"));
+
+// 一段可翻译的行内内容（段落、标题、表格单元格、紧凑列表项的文本）。
+// 行内代码、链接地址、HTML、图片等受保护内容被替换为 ⟦n⟧ 占位符
+#[derive(Debug, Clone)]
+pub struct Segment {
+    range: Range<usize>,
+    text: String,
+    protected: Vec<String>,
+}
+
+impl Segment {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    // 还原占位符；占位符缺失或重复时返回 None，由调用方保留原文
+    pub fn restore(&self, translated: &str) -> Option<String> {
+        // 原文片段不含换行，译文中的换行会破坏标题、表格等结构
+        let translated = translated.split_whitespace().collect::<Vec<_>>().join(" ");
+
+        let mut counts = vec![0usize; self.protected.len()];
+        for caps in PLACEHOLDER.captures_iter(&translated) {
+            let index: usize = caps[1].parse().ok()?;
+            *counts.get_mut(index)? += 1;
+        }
+        if counts.iter().any(|count| *count != 1) {
+            return None;
+        }
+
+        let restored = PLACEHOLDER.replace_all(&translated, |caps: &regex::Captures| {
+            let index: usize = caps[1].parse().unwrap_or_default();
+            self.protected[index].clone()
+        });
+        Some(restored.into_owned())
+    }
+
+    fn has_translatable_text(&self) -> bool {
+        PLACEHOLDER
+            .replace_all(&self.text, "")
+            .chars()
+            .any(|c| c.is_alphabetic())
+    }
+}
+
+// 按 Markdown 语法树切分的文档：只有可读文本会被翻译，其余源码原样保留，
+// 重建后的文档结构与原文一致
+pub struct MarkdownSegments<'a> {
+    source: &'a str,
+    segments: Vec<Segment>,
+}
+
+impl<'a> MarkdownSegments<'a> {
+    pub fn parse(source: &'a str) -> Self {
+        let options = Options::ENABLE_TABLES
+            | Options::ENABLE_STRIKETHROUGH
+            | Options::ENABLE_TASKLISTS
+            | Options::ENABLE_FOOTNOTES
+            | Options::ENABLE_YAML_STYLE_METADATA_BLOCKS;
+
+        let mut segments = Vec::new();
+        let mut builder = SegmentBuilder::default();
+        // 代码块、元数据块内的文本，以及图片、自动链接等整体受保护元素内的文本都不翻译
+        let mut verbatim_depth = 0usize;
+        let mut atomic_depth = 0usize;
+
+        for (event, range) in Parser::new_ext(source, options).into_offset_iter() {
+            match event {
+                Event::Start(Tag::CodeBlock(_) | Tag::MetadataBlock(_) | Tag::HtmlBlock) => {
+                    builder.flush(source, &mut segments);
+                    verbatim_depth += 1;
+                }
+                Event::End(
+                    pulldown_cmark::TagEnd::CodeBlock
+                    | pulldown_cmark::TagEnd::MetadataBlock(_)
+                    | pulldown_cmark::TagEnd::HtmlBlock,
+                ) => {
+                    verbatim_depth = verbatim_depth.saturating_sub(1);
+                }
+                _ if verbatim_depth > 0 => {}
+
+                Event::Start(tag) if Self::is_atomic(&tag) => {
+                    if atomic_depth == 0 {
+                        builder.touch(&range);
+                    }
+                    atomic_depth += 1;
+                }
+                Event::End(pulldown_cmark::TagEnd::Image | pulldown_cmark::TagEnd::Link)
+                    if atomic_depth > 0 =>
+                {
+                    atomic_depth -= 1;
+                    builder.touch(&range);
+                }
+                _ if atomic_depth > 0 => {}
+
+                Event::Text(_) => builder.push_text(&range),
+                Event::SoftBreak => builder.push_soft_break(&range),
+                Event::Start(
+                    Tag::Emphasis
+                    | Tag::Strong
+                    | Tag::Strikethrough
+                    | Tag::Superscript
+                    | Tag::Subscript
+                    | Tag::Link { .. },
+                ) => builder.touch(&range),
+                Event::End(
+                    pulldown_cmark::TagEnd::Emphasis
+                    | pulldown_cmark::TagEnd::Strong
+                    | pulldown_cmark::TagEnd::Strikethrough
+                    | pulldown_cmark::TagEnd::Superscript
+                    | pulldown_cmark::TagEnd::Subscript
+                    | pulldown_cmark::TagEnd::Link,
+                ) => builder.touch(&range),
+                Event::Code(_)
+                | Event::InlineHtml(_)
+                | Event::InlineMath(_)
+                | Event::DisplayMath(_)
+                | Event::FootnoteReference(_)
+                | Event::HardBreak
+                | Event::TaskListMarker(_) => builder.touch(&range),
+                // 其余都是块级事件，结束当前的行内片段
+                _ => builder.flush(source, &mut segments),
+            }
+        }
+        builder.flush(source, &mut segments);
+
+        Self {
+            source,
+            segments: segments
+                .into_iter()
+                .filter(|s| s.has_translatable_text())
+                .collect(),
+        }
+    }
+
+    // 图片和无法安全翻译文字的链接（自动链接、以文字作为引用标签的链接）整体保护
+    fn is_atomic(tag: &Tag) -> bool {
+        match tag {
+            Tag::Image { .. } => true,
+            Tag::Link { link_type, .. } => matches!(
+                link_type,
+                LinkType::Autolink
+                    | LinkType::Email
+                    | LinkType::Collapsed
+                    | LinkType::CollapsedUnknown
+                    | LinkType::Shortcut
+                    | LinkType::ShortcutUnknown
+                    | LinkType::WikiLink { .. }
+            ),
+            _ => false,
+        }
+    }
+
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    // translations 与 segments 一一对应；None 或占位符不匹配的片段保留原文
+    pub fn rebuild(&self, translations: &[Option<String>]) -> String {
+        let mut output = String::with_capacity(self.source.len());
+        let mut cursor = 0;
+
+        for (segment, translation) in self.segments.iter().zip(translations) {
+            let Some(translation) = translation else {
+                continue;
+            };
+            let Some(restored) = segment.restore(translation) else {
+                eprintln!("译文占位符不完整，保留原文: {}", segment.text);
+                continue;
+            };
+
+            output.push_str(&self.source[cursor..segment.range.start]);
+            output.push_str(&restored);
+            cursor = segment.range.end;
+        }
+
+        output.push_str(&self.source[cursor..]);
+        output
+    }
+}
+
+// 收集一段连续的行内事件：Text 为可翻译内容，其间未被 Text 覆盖的源码
+// （强调符号、链接地址、行内代码等）作为受保护内容
+#[derive(Default)]
+struct SegmentBuilder {
+    start: Option<usize>,
+    end: usize,
+    pieces: Vec<Piece>,
+}
+
+enum Piece {
+    Text(Range<usize>),
+    SoftBreak(Range<usize>),
+    Other(Range<usize>),
+}
+
+impl SegmentBuilder {
+    fn push(&mut self, piece: Piece, range: &Range<usize>) {
+        if self.start.is_none() {
+            self.start = Some(range.start);
+            self.end = range.start;
+        }
+        self.end = self.end.max(range.end);
+        self.pieces.push(piece);
+    }
+
+    fn touch(&mut self, range: &Range<usize>) {
+        self.push(Piece::Other(range.clone()), range);
+    }
+
+    fn push_text(&mut self, range: &Range<usize>) {
+        self.push(Piece::Text(range.clone()), range);
+    }
+
+    fn push_soft_break(&mut self, range: &Range<usize>) {
+        self.push(Piece::SoftBreak(range.clone()), range);
+    }
+
+    fn flush(&mut self, source: &str, segments: &mut Vec<Segment>) {
+        let Some(start) = self.start.take() else {
+            return;
+        };
+        let end = self.end;
+
+        let mut text = String::new();
+        let mut protected = Vec::new();
+        let mut cursor = start;
+        // 软换行后下一行的缩进或引用前缀不需要保留
+        let mut skip_gap = false;
+
+        for piece in std::mem::take(&mut self.pieces) {
+            match piece {
+                Piece::Other(range) => {
+                    if skip_gap && range.start >= cursor {
+                        cursor = range.start;
+                        skip_gap = false;
+                    }
+                }
+                Piece::Text(range) | Piece::SoftBreak(range) if range.start < cursor => {}
+                Piece::Text(range) => {
+                    if !skip_gap {
+                        Self::protect(&source[cursor..range.start], &mut text, &mut protected);
+                    }
+                    text.push_str(&source[range.clone()]);
+                    cursor = range.end;
+                    skip_gap = false;
+                }
+                Piece::SoftBreak(range) => {
+                    if !skip_gap {
+                        Self::protect(&source[cursor..range.start], &mut text, &mut protected);
+                    }
+                    text.push(' ');
+                    cursor = range.end;
+                    skip_gap = true;
+                }
+            }
+        }
+        if cursor < end && !skip_gap {
+            Self::protect(&source[cursor..end], &mut text, &mut protected);
+        }
+
+        segments.push(Segment {
+            range: start..end,
+            text,
+            protected,
+        });
+    }
+
+    fn protect(span: &str, text: &mut String, protected: &mut Vec<String>) {
+        if span.is_empty() {
+            return;
+        }
+        // 单行的纯空白直接保留，不占用占位符
+        if span.chars().all(|c| c == ' ' || c == '\t') {
+            text.push_str(span);
+            return;
+        }
+        text.push_str(&format!("⟦{}⟧", protected.len()));
+        protected.push(span.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(document: &MarkdownSegments) -> Vec<String> {
+        document
+            .segments()
+            .iter()
+            .map(|segment| segment.text().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn parse_extracts_headings_and_paragraphs() {
+        let document =
+            MarkdownSegments::parse("# Title\n\nFirst paragraph.\n\nSecond paragraph.\n");
+        assert_eq!(
+            texts(&document),
+            ["Title", "First paragraph.", "Second paragraph."]
+        );
+    }
+
+    #[test]
+    fn parse_skips_code_fences() {
+        let source =
+            "Intro text.\n\n```rust\nlet greeting = \"hello world\";\n```\n\nOutro text.\n";
+        let document = MarkdownSegments::parse(source);
+        assert_eq!(texts(&document), ["Intro text.", "Outro text."]);
+    }
+
+    #[test]
+    fn parse_protects_inline_code_and_link_targets() {
+        let document = MarkdownSegments::parse(
+            "Run `cargo build` and read [the guide](https://example.com/guide).\n",
+        );
+        let segment = &document.segments()[0];
+        assert_eq!(segment.text(), "Run ⟦0⟧ and read ⟦1⟧the guide⟦2⟧.");
+        assert_eq!(
+            segment.protected,
+            ["`cargo build`", "[", "](https://example.com/guide)"]
+        );
+    }
+
+    #[test]
+    fn parse_joins_soft_breaks_into_one_line() {
+        let document = MarkdownSegments::parse("First line\nsecond line\n");
+        assert_eq!(texts(&document), ["First line second line"]);
+    }
+
+    #[test]
+    fn parse_splits_blank_line_separated_list_paragraphs() {
+        let document = MarkdownSegments::parse("- First part.\n\n  Second part.\n");
+        assert_eq!(texts(&document), ["First part.", "Second part."]);
+        assert!(
+            document
+                .segments()
+                .iter()
+                .all(|segment| !segment.text().contains('\n'))
+        );
+    }
+
+    #[test]
+    fn restore_replaces_placeholders_in_any_order() {
+        let document = MarkdownSegments::parse(
+            "Run `cargo build` and read [the guide](https://example.com/guide).\n",
+        );
+        let restored = document.segments()[0]
+            .restore("阅读 ⟦1⟧指南⟦2⟧ 并运行 ⟦ 0 ⟧。")
+            .unwrap();
+        assert_eq!(
+            restored,
+            "阅读 [指南](https://example.com/guide) 并运行 `cargo build`。"
+        );
+    }
+
+    #[test]
+    fn restore_rejects_missing_or_duplicated_placeholders() {
+        let document = MarkdownSegments::parse("Run `cargo build` now.\n");
+        let segment = &document.segments()[0];
+        assert_eq!(segment.restore("现在运行。"), None);
+        assert_eq!(segment.restore("运行 ⟦0⟧ ⟦0⟧。"), None);
+        assert_eq!(segment.restore("运行 ⟦0⟧ ⟦1⟧。"), None);
+    }
+
+    #[test]
+    fn restore_collapses_blank_lines_in_translation() {
+        let document = MarkdownSegments::parse("# Getting started\n");
+        let restored = document.segments()[0].restore("开始\n\n使用").unwrap();
+        assert_eq!(restored, "开始 使用");
+    }
+
+    #[test]
+    fn rebuild_keeps_structure_and_untranslated_segments() {
+        let source = "# Title\n\nRun `make` first.\n\n```sh\nmake install\n```\n\nLast words.\n";
+        let document = MarkdownSegments::parse(source);
+        assert_eq!(texts(&document), ["Title", "Run ⟦0⟧ first.", "Last words."]);
+
+        let rebuilt = document.rebuild(&[
+            Some("标题".to_string()),
+            Some("先运行 ⟦0⟧。".to_string()),
+            None,
+        ]);
+        assert_eq!(
+            rebuilt,
+            "# 标题\n\n先运行 `make`。\n\n```sh\nmake install\n```\n\nLast words.\n"
+        );
+    }
+
+    #[test]
+    fn rebuild_keeps_original_when_placeholders_are_lost() {
+        let source = "Run `make` first.\n";
+        let document = MarkdownSegments::parse(source);
+        assert_eq!(document.rebuild(&[Some("先运行。".to_string())]), source);
+    }
+}
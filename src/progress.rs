@@ -5,6 +5,8 @@ pub struct ProgressTracker {
     total_pages: usize,
     success_count: usize,
     error_count: usize,
+    // 运行结束前重新处理后成功的页面数
+    recovered_count: usize,
     batch_size: usize,
     wait_time: u64,
     request_delay: u64,
@@ -16,6 +18,7 @@ impl ProgressTracker {
             total_pages,
             success_count: 0,
             error_count: 0,
+            recovered_count: 0,
             batch_size,
             wait_time,
             request_delay,
@@ -24,14 +27,34 @@ impl ProgressTracker {
 
     pub fn log_start(&self, url: &str) {
         println!("开始爬取网站: {}", url);
-        println!("发现 {} 个页面，开始下载", self.total_pages);
-        println!("批处理设置: 每 {} 个页面等待 {} 秒", self.batch_size, self.wait_time);
+        if self.total_pages > 0 {
+            println!("发现 {} 个页面，开始下载", self.total_pages);
+        } else {
+            println!("边爬取边下载，发现的页面会分批处理");
+        }
+        println!(
+            "批处理设置: 每 {} 个页面等待 {} 秒",
+            self.batch_size, self.wait_time
+        );
+    }
+
+    // 流式处理时页面总数未知，随着新批次到达逐步累加
+    pub fn add_discovered(&mut self, count: usize) {
+        self.total_pages += count;
     }
 
     pub fn log_processing(&self, index: usize, url: &str) {
         println!("[{}/{}] 处理: {}", index + 1, self.total_pages, url);
     }
 
+    pub fn log_retrying(&self, index: usize, count: usize, url: &str) {
+        println!("[重试 {}/{}] 处理: {}", index + 1, count, url);
+    }
+
+    pub fn log_recovered(&mut self) {
+        self.recovered_count += 1;
+    }
+
     pub fn log_success(&mut self, filename: &str) {
         self.success_count += 1;
         println!("✓ 保存为: {}", filename);
@@ -47,7 +70,7 @@ impl ProgressTracker {
         sleep(Duration::from_millis(self.request_delay)).await;
 
         // 批处理限制：每处理 batch_size 个页面后等待
-        if (index + 1) % self.batch_size == 0 && index + 1 < self.total_pages {
+        if (index + 1).is_multiple_of(self.batch_size) && index + 1 < self.total_pages {
             println!(
                 "⏳ 已处理 {} 个页面，等待 {} 秒后继续...",
                 index + 1,
@@ -62,9 +85,12 @@ impl ProgressTracker {
         println!("\n下载完成!");
         println!("成功: {} 个文件", self.success_count);
         println!("失败: {} 个文件", self.error_count);
+        if self.recovered_count > 0 {
+            println!("重试后成功: {} 个页面", self.recovered_count);
+        }
     }
 
     pub fn get_stats(&self) -> (usize, usize) {
         (self.success_count, self.error_count)
     }
-}
\ No newline at end of file
+}
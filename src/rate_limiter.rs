@@ -0,0 +1,405 @@
+use crate::error::{Error, Result};
+use crate::types::RetryConfig;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+
+// 连续成功多少次后提高一档请求速率
+const RAMP_UP_AFTER_SUCCESSES: u32 = 10;
+// 限流后最多降到基准速率的几分之一
+const MIN_RATE_DIVISOR: f64 = 16.0;
+
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+    pub requests_per_second: f64,
+    // 空闲后允许连续发出的请求数
+    pub burst: u32,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_second: 2.0,
+            burst: 2,
+        }
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    // 当前速率；收到 429 后降低，持续成功后逐步恢复到基准速率
+    rate: f64,
+    last_refill: Instant,
+    // Retry-After 要求暂停到的时间
+    blocked_until: Option<Instant>,
+    successes: u32,
+}
+
+impl Bucket {
+    fn refill(&mut self, now: Instant, capacity: f64) {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(capacity);
+        self.last_refill = self.last_refill.max(now);
+    }
+
+    // 取得一个令牌；没有令牌时返回需要等待的时间
+    fn take(&mut self, now: Instant, capacity: f64) -> std::result::Result<(), Duration> {
+        match self.blocked_until {
+            Some(until) if until > now => Err(until - now),
+            _ => {
+                self.blocked_until = None;
+                self.refill(now, capacity);
+                if self.tokens >= 1.0 {
+                    self.tokens -= 1.0;
+                    return Ok(());
+                }
+                Err(Duration::from_secs_f64((1.0 - self.tokens) / self.rate))
+            }
+        }
+    }
+}
+
+// 令牌桶限流器，转换器和翻译服务共用同一个实例（克隆后共享状态）
+#[derive(Clone)]
+pub struct RateLimiter {
+    bucket: Arc<Mutex<Bucket>>,
+    base_rate: f64,
+    capacity: f64,
+}
+
+impl RateLimiter {
+    pub fn new(requests_per_second: f64) -> Self {
+        Self::from_config(&RateLimitConfig {
+            requests_per_second,
+            burst: 1,
+        })
+    }
+
+    pub fn from_config(config: &RateLimitConfig) -> Self {
+        let base_rate = if config.requests_per_second > 0.0 {
+            config.requests_per_second
+        } else {
+            1.0
+        };
+        let capacity = config.burst.max(1) as f64;
+
+        Self {
+            bucket: Arc::new(Mutex::new(Bucket {
+                tokens: capacity,
+                rate: base_rate,
+                last_refill: Instant::now(),
+                blocked_until: None,
+                successes: 0,
+            })),
+            base_rate,
+            capacity,
+        }
+    }
+
+    // 等待直到取得一个令牌
+    pub async fn acquire(&self) -> Result<()> {
+        loop {
+            let wait = match self.lock().take(Instant::now(), self.capacity) {
+                Ok(()) => return Ok(()),
+                Err(wait) => wait,
+            };
+            sleep(wait).await;
+        }
+    }
+
+    pub fn current_rate(&self) -> f64 {
+        self.lock().rate
+    }
+
+    // 收到 429：速率减半，清空令牌，并在 Retry-After 指定的时间内暂停所有请求
+    pub fn on_rate_limited(&self, retry_after: Option<Duration>) {
+        let mut bucket = self.lock();
+        let now = Instant::now();
+
+        bucket.rate = (bucket.rate / 2.0).max(self.base_rate / MIN_RATE_DIVISOR);
+        bucket.tokens = 0.0;
+        bucket.successes = 0;
+
+        let pause = retry_after.unwrap_or_else(|| Duration::from_secs_f64(1.0 / bucket.rate));
+        let until = now.checked_add(pause).unwrap_or(now);
+        let until = bucket
+            .blocked_until
+            .map_or(until, |current| current.max(until));
+        bucket.blocked_until = Some(until);
+        bucket.last_refill = until;
+
+        println!(
+            "触发限流 (429)，请求速率降为 {:.2} 次/秒，{:.1} 秒后继续",
+            bucket.rate,
+            (until - now).as_secs_f64()
+        );
+    }
+
+    // 持续成功后逐步恢复速率
+    pub fn on_success(&self) {
+        let mut bucket = self.lock();
+        if bucket.rate >= self.base_rate {
+            return;
+        }
+
+        bucket.successes += 1;
+        if bucket.successes >= RAMP_UP_AFTER_SUCCESSES {
+            bucket.rate = (bucket.rate * 1.5).min(self.base_rate);
+            bucket.successes = 0;
+            println!("请求速率恢复到 {:.2} 次/秒", bucket.rate);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Bucket> {
+        self.bucket
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+// 解析 Retry-After：秒数或 HTTP 日期
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?;
+    parse_retry_after(value, chrono::Utc::now())
+}
+
+fn parse_retry_after(value: &str, now: chrono::DateTime<chrono::Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let seconds = (date.with_timezone(&chrono::Utc) - now).num_milliseconds();
+    Some(Duration::from_millis(seconds.max(0) as u64))
+}
+
+// HTTP 429 时返回 RateLimited 错误，其余状态交给调用方处理
+pub fn check_rate_limited(response: &reqwest::Response, service: &str) -> Result<()> {
+    if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return Err(Error::RateLimited {
+            service: service.to_string(),
+            retry_after: retry_after(response.headers()),
+        });
+    }
+    Ok(())
+}
+
+// rate_limiter 为 None 时不限流（资源下载、站点地图等不经过共享限流器的请求）
+pub async fn retry_with_backoff<F, Fut, T>(
+    mut operation: F,
+    config: &RetryConfig,
+    rate_limiter: Option<&RateLimiter>,
+) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T>>,
+{
+    for attempt in 0..=config.max_retries {
+        if let Some(rate_limiter) = rate_limiter {
+            rate_limiter.acquire().await?;
+        }
+
+        match operation().await {
+            Ok(result) => {
+                if let Some(rate_limiter) = rate_limiter {
+                    rate_limiter.on_success();
+                }
+                return Ok(result);
+            }
+            Err(e) => {
+                if let Error::RateLimited {
+                    retry_after: Some(retry_after),
+                    ..
+                } = &e
+                    && *retry_after > config.max_retry_after()
+                {
+                    // 不缩短服务器要求的等待时间，超过上限时直接放弃这个请求
+                    eprintln!(
+                        "Retry-After of {}s exceeds max_retry_after_secs ({}s), giving up",
+                        retry_after.as_secs(),
+                        config.max_retry_after_secs
+                    );
+                    return Err(e);
+                }
+                if let (Some(rate_limiter), Error::RateLimited { retry_after, .. }) =
+                    (rate_limiter, &e)
+                {
+                    rate_limiter.on_rate_limited(*retry_after);
+                }
+                if attempt == config.max_retries || !e.is_retryable() {
+                    return Err(e);
+                }
+
+                let delay = match (&e, rate_limiter) {
+                    // 429 的等待由限流器统一处理，所有请求一起暂停
+                    (Error::RateLimited { .. }, Some(_)) => Duration::ZERO,
+                    (
+                        Error::RateLimited {
+                            retry_after: Some(retry_after),
+                            ..
+                        },
+                        None,
+                    ) => *retry_after,
+                    _ => config.delay(attempt),
+                };
+                eprintln!(
+                    "Attempt {} failed: {}. Retrying in {}ms...",
+                    attempt + 1,
+                    e,
+                    delay.as_millis()
+                );
+                sleep(delay).await;
+            }
+        }
+    }
+
+    unreachable!()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn limiter(requests_per_second: f64, burst: u32) -> RateLimiter {
+        RateLimiter::from_config(&RateLimitConfig {
+            requests_per_second,
+            burst,
+        })
+    }
+
+    #[test]
+    fn burst_tokens_are_consumed_then_refilled_at_the_rate() {
+        let limiter = limiter(2.0, 3);
+        let now = Instant::now();
+        let mut bucket = limiter.lock();
+
+        for _ in 0..3 {
+            assert_eq!(bucket.take(now, limiter.capacity), Ok(()));
+        }
+        let wait = bucket.take(now, limiter.capacity).unwrap_err();
+        assert!((wait.as_secs_f64() - 0.5).abs() < 0.01, "{:?}", wait);
+
+        assert!(
+            bucket
+                .take(now + Duration::from_millis(250), limiter.capacity)
+                .is_err()
+        );
+        assert_eq!(
+            bucket.take(now + Duration::from_millis(500), limiter.capacity),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn idle_time_never_accumulates_more_than_the_burst() {
+        let limiter = limiter(1.0, 2);
+        let later = Instant::now() + Duration::from_secs(60);
+        let mut bucket = limiter.lock();
+
+        assert_eq!(bucket.take(later, limiter.capacity), Ok(()));
+        assert_eq!(bucket.take(later, limiter.capacity), Ok(()));
+        assert!(bucket.take(later, limiter.capacity).is_err());
+    }
+
+    #[test]
+    fn rate_limited_halves_the_rate_down_to_the_floor() {
+        let limiter = limiter(4.0, 1);
+
+        limiter.on_rate_limited(Some(Duration::ZERO));
+        assert_eq!(limiter.current_rate(), 2.0);
+        limiter.on_rate_limited(Some(Duration::ZERO));
+        assert_eq!(limiter.current_rate(), 1.0);
+
+        for _ in 0..10 {
+            limiter.on_rate_limited(Some(Duration::ZERO));
+        }
+        assert_eq!(limiter.current_rate(), 4.0 / MIN_RATE_DIVISOR);
+    }
+
+    #[test]
+    fn successes_ramp_the_rate_up_by_half_until_the_base_rate() {
+        let limiter = limiter(4.0, 1);
+        limiter.on_rate_limited(Some(Duration::ZERO));
+        limiter.on_rate_limited(Some(Duration::ZERO));
+        assert_eq!(limiter.current_rate(), 1.0);
+
+        for _ in 0..RAMP_UP_AFTER_SUCCESSES - 1 {
+            limiter.on_success();
+        }
+        assert_eq!(limiter.current_rate(), 1.0);
+        limiter.on_success();
+        assert_eq!(limiter.current_rate(), 1.5);
+
+        for _ in 0..RAMP_UP_AFTER_SUCCESSES * 5 {
+            limiter.on_success();
+        }
+        assert_eq!(limiter.current_rate(), 4.0);
+    }
+
+    #[test]
+    fn rate_limited_pauses_for_the_full_retry_after() {
+        let limiter = limiter(2.0, 1);
+        limiter.on_rate_limited(Some(Duration::from_secs(60)));
+
+        let now = Instant::now();
+        let wait = limiter.lock().take(now, limiter.capacity).unwrap_err();
+        assert!(wait > Duration::from_secs(59), "{:?}", wait);
+    }
+
+    #[test]
+    fn retry_after_parses_seconds() {
+        let now = chrono::Utc::now();
+        assert_eq!(
+            parse_retry_after("120", now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(parse_retry_after(" 0 ", now), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn retry_after_parses_http_dates() {
+        let now = chrono::DateTime::parse_from_rfc3339("2015-10-21T07:27:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT", now),
+            Some(Duration::from_secs(60))
+        );
+        // 已经过去的时间不需要等待
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:00:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[tokio::test]
+    async fn retry_after_above_the_cap_fails_without_waiting() {
+        let config = RetryConfig {
+            max_retry_after_secs: 60,
+            ..RetryConfig::default()
+        };
+        let attempts = AtomicUsize::new(0);
+
+        let result: Result<()> = retry_with_backoff(
+            || async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err(Error::RateLimited {
+                    service: "test".to_string(),
+                    retry_after: Some(Duration::from_secs(3600)),
+                })
+            },
+            &config,
+            None,
+        )
+        .await;
+
+        assert!(matches!(result, Err(Error::RateLimited { .. })));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+}
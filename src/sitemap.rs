@@ -0,0 +1,428 @@
+use crate::config::Config;
+use crate::error::{Error, Result};
+use crate::rate_limiter::{check_rate_limited, retry_with_backoff};
+use crate::types::RetryConfig;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use flate2::read::GzDecoder;
+use quick_xml::Reader;
+use quick_xml::events::Event;
+use reqwest::Client;
+use std::collections::{HashSet, VecDeque};
+use std::io::Read;
+use std::time::Duration;
+use url::Url;
+
+// 单次运行最多读取的站点地图数量，防止过深或过大的站点地图索引
+const MAX_SITEMAP_FETCHES: usize = 200;
+
+#[derive(Debug, Clone, Default)]
+pub struct SitemapConfig {
+    pub enabled: bool,
+    pub sitemap_url: Option<String>,
+    pub use_lastmod: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct SitemapEntry {
+    pub url: String,
+    pub lastmod: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default)]
+struct ParsedSitemap {
+    urls: Vec<SitemapEntry>,
+    sitemaps: Vec<String>,
+}
+
+pub struct SitemapDiscovery {
+    client: Client,
+    user_agent: String,
+    timeout: u64,
+    retry_config: RetryConfig,
+}
+
+impl SitemapDiscovery {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            client: Client::new(),
+            user_agent: config.user_agent.clone(),
+            timeout: config.timeout,
+            retry_config: config.retry.crawl.clone(),
+        }
+    }
+
+    pub async fn discover(
+        &self,
+        start_url: &str,
+        sitemap_url: Option<&str>,
+    ) -> Result<Vec<SitemapEntry>> {
+        let base = Url::parse(start_url)?;
+
+        let mut queue: VecDeque<String> = match sitemap_url {
+            Some(url) => VecDeque::from([url.to_string()]),
+            None => self.sitemap_locations(&base).await.into(),
+        };
+
+        let mut visited = HashSet::new();
+        let mut seen_urls = HashSet::new();
+        let mut entries = Vec::new();
+
+        while let Some(sitemap) = queue.pop_front() {
+            if !visited.insert(sitemap.clone()) || visited.len() > MAX_SITEMAP_FETCHES {
+                continue;
+            }
+
+            let xml = match self.fetch_sitemap(&sitemap).await {
+                Ok(xml) => xml,
+                Err(e) => {
+                    eprintln!("读取站点地图失败: {} - {}", sitemap, e);
+                    continue;
+                }
+            };
+
+            let parsed = match Self::parse_sitemap(&xml) {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    eprintln!("解析站点地图失败: {} - {}", sitemap, e);
+                    continue;
+                }
+            };
+
+            println!(
+                "站点地图 {}: {} 个页面, {} 个子站点地图",
+                sitemap,
+                parsed.urls.len(),
+                parsed.sitemaps.len()
+            );
+
+            queue.extend(parsed.sitemaps);
+            for entry in parsed.urls {
+                if seen_urls.insert(entry.url.clone()) {
+                    entries.push(entry);
+                }
+            }
+        }
+
+        Ok(entries)
+    }
+
+    // robots.txt 中声明的站点地图优先，其次是常见位置
+    async fn sitemap_locations(&self, base: &Url) -> Vec<String> {
+        let mut locations = Vec::new();
+
+        if let Ok(robots_url) = base.join("/robots.txt") {
+            match self.fetch_text(robots_url.as_str()).await {
+                Ok(robots) => locations = Self::robots_sitemaps(&robots),
+                Err(e) => eprintln!("读取 robots.txt 失败: {}", e),
+            }
+        }
+
+        if locations.is_empty() {
+            for path in ["/sitemap.xml", "/sitemap_index.xml", "/sitemap.xml.gz"] {
+                if let Ok(url) = base.join(path) {
+                    locations.push(url.to_string());
+                }
+            }
+        }
+
+        locations
+    }
+
+    // robots.txt 中 Sitemap: 行给出的地址（指令名不区分大小写）
+    fn robots_sitemaps(robots: &str) -> Vec<String> {
+        robots
+            .lines()
+            .filter_map(|line| line.trim().split_once(':'))
+            .filter(|(directive, _)| directive.trim().eq_ignore_ascii_case("sitemap"))
+            .map(|(_, location)| location.trim().to_string())
+            .filter(|location| !location.is_empty())
+            .collect()
+    }
+
+    async fn fetch_text(&self, url: &str) -> Result<String> {
+        let bytes = self.fetch_bytes(url).await?;
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    async fn fetch_bytes(&self, url: &str) -> Result<Vec<u8>> {
+        retry_with_backoff(|| self.fetch_bytes_once(url), &self.retry_config, None).await
+    }
+
+    async fn fetch_bytes_once(&self, url: &str) -> Result<Vec<u8>> {
+        let response = self
+            .client
+            .get(url)
+            .header("User-Agent", &self.user_agent)
+            .timeout(Duration::from_secs(self.timeout))
+            .send()
+            .await
+            .map_err(|e| Error::network(url, e))?;
+
+        check_rate_limited(&response, url)?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(Error::http_status(url, status, ""));
+        }
+
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    async fn fetch_sitemap(&self, url: &str) -> Result<String> {
+        let bytes = self.fetch_bytes(url).await?;
+        Self::decode_body(&bytes)
+    }
+
+    fn decode_body(bytes: &[u8]) -> Result<String> {
+        // 根据 gzip 魔数判断，服务器不一定会设置正确的 Content-Encoding
+        if bytes.starts_with(&[0x1f, 0x8b]) {
+            let mut decoder = GzDecoder::new(bytes);
+            let mut xml = String::new();
+            decoder.read_to_string(&mut xml)?;
+            Ok(xml)
+        } else {
+            Ok(String::from_utf8_lossy(bytes).into_owned())
+        }
+    }
+
+    fn parse_sitemap(xml: &str) -> Result<ParsedSitemap> {
+        let mut reader = Reader::from_str(xml);
+        let mut parsed = ParsedSitemap::default();
+
+        // 当前所在的父元素（url 或 sitemap）和正在读取的子元素
+        let mut parent: Option<Vec<u8>> = None;
+        let mut field: Option<Vec<u8>> = None;
+        let mut loc = String::new();
+        let mut lastmod = String::new();
+
+        loop {
+            let event = reader
+                .read_event()
+                .map_err(|e| crate::error::Error::Custom(format!("Invalid sitemap XML: {}", e)))?;
+
+            match event {
+                Event::Start(e) => {
+                    let name = e.local_name().as_ref().to_vec();
+                    match name.as_slice() {
+                        b"url" | b"sitemap" => {
+                            parent = Some(name);
+                            loc.clear();
+                            lastmod.clear();
+                        }
+                        b"loc" | b"lastmod" if parent.is_some() => field = Some(name),
+                        _ => {}
+                    }
+                }
+                Event::Text(e) => {
+                    let text = e.decode().map_err(|e| {
+                        crate::error::Error::Custom(format!("Invalid sitemap text: {}", e))
+                    })?;
+                    Self::push_field(&field, &text, &mut loc, &mut lastmod);
+                }
+                Event::CData(e) => {
+                    let text = String::from_utf8_lossy(&e).into_owned();
+                    Self::push_field(&field, &text, &mut loc, &mut lastmod);
+                }
+                Event::GeneralRef(e) => {
+                    let resolved = match e.resolve_char_ref() {
+                        Ok(Some(ch)) => ch.to_string(),
+                        _ => match &*e {
+                            b"amp" => "&".to_string(),
+                            b"lt" => "<".to_string(),
+                            b"gt" => ">".to_string(),
+                            b"quot" => "\"".to_string(),
+                            b"apos" => "'".to_string(),
+                            _ => String::new(),
+                        },
+                    };
+                    Self::push_field(&field, &resolved, &mut loc, &mut lastmod);
+                }
+                Event::End(e) => {
+                    let name = e.local_name();
+                    match name.as_ref() {
+                        b"loc" | b"lastmod" => field = None,
+                        b"url" | b"sitemap" => {
+                            let location = loc.trim().to_string();
+                            if !location.is_empty() {
+                                if name.as_ref() == b"url" {
+                                    parsed.urls.push(SitemapEntry {
+                                        url: location,
+                                        lastmod: Self::parse_lastmod(lastmod.trim()),
+                                    });
+                                } else {
+                                    parsed.sitemaps.push(location);
+                                }
+                            }
+                            parent = None;
+                        }
+                        _ => {}
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    fn push_field(field: &Option<Vec<u8>>, text: &str, loc: &mut String, lastmod: &mut String) {
+        match field.as_deref() {
+            Some(b"loc") => loc.push_str(text),
+            Some(b"lastmod") => lastmod.push_str(text),
+            _ => {}
+        }
+    }
+
+    // W3C Datetime：支持完整时间戳和仅日期两种写法，没有时区的时间戳按 UTC 处理
+    fn parse_lastmod(value: &str) -> Option<DateTime<Utc>> {
+        if value.is_empty() {
+            return None;
+        }
+
+        if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+            return Some(datetime.with_timezone(&Utc));
+        }
+
+        if let Ok(datetime) = DateTime::parse_from_str(value, "%Y-%m-%dT%H:%M%:z") {
+            return Some(datetime.with_timezone(&Utc));
+        }
+
+        if let Ok(datetime) = NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f") {
+            return Some(datetime.and_utc());
+        }
+
+        NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .ok()
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .map(|datetime| datetime.and_utc())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn robots_sitemap_lines_are_found_among_non_ascii_lines() {
+        let robots = "# 站点地图和爬虫规则\nUser-agent: *\nDisallow: /私有/\nSitemap: https://example.com/sitemap.xml\nsitemap:https://example.com/news.xml\nSitemap:\n";
+        assert_eq!(
+            SitemapDiscovery::robots_sitemaps(robots),
+            vec![
+                "https://example.com/sitemap.xml",
+                "https://example.com/news.xml"
+            ]
+        );
+    }
+
+    #[test]
+    fn urlset_entries_are_pages() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+              <url><loc>https://example.com/a</loc><lastmod>2024-03-01</lastmod></url>
+              <url><loc> https://example.com/b </loc></url>
+              <url><lastmod>2024-03-01</lastmod></url>
+            </urlset>"#;
+
+        let parsed = SitemapDiscovery::parse_sitemap(xml).unwrap();
+        assert!(parsed.sitemaps.is_empty());
+        assert_eq!(parsed.urls.len(), 2);
+        assert_eq!(parsed.urls[0].url, "https://example.com/a");
+        assert_eq!(parsed.urls[0].lastmod, Some(utc("2024-03-01T00:00:00Z")));
+        assert_eq!(parsed.urls[1].url, "https://example.com/b");
+        assert_eq!(parsed.urls[1].lastmod, None);
+    }
+
+    #[test]
+    fn sitemapindex_entries_are_child_sitemaps() {
+        let xml = r#"<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+              <sitemap><loc>https://example.com/sitemap-1.xml</loc><lastmod>2024-03-01</lastmod></sitemap>
+              <sitemap><loc>https://example.com/sitemap-2.xml.gz</loc></sitemap>
+            </sitemapindex>"#;
+
+        let parsed = SitemapDiscovery::parse_sitemap(xml).unwrap();
+        assert!(parsed.urls.is_empty());
+        assert_eq!(
+            parsed.sitemaps,
+            vec![
+                "https://example.com/sitemap-1.xml",
+                "https://example.com/sitemap-2.xml.gz"
+            ]
+        );
+    }
+
+    #[test]
+    fn cdata_and_entities_in_loc_are_decoded() {
+        let xml = r#"<urlset>
+              <url><loc><![CDATA[https://example.com/search?q=a&page=2]]></loc></url>
+              <url><loc>https://example.com/list?a=1&amp;b=2&#38;c=3</loc></url>
+            </urlset>"#;
+
+        let parsed = SitemapDiscovery::parse_sitemap(xml).unwrap();
+        let urls: Vec<&str> = parsed.urls.iter().map(|entry| entry.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            vec![
+                "https://example.com/search?q=a&page=2",
+                "https://example.com/list?a=1&b=2&c=3"
+            ]
+        );
+    }
+
+    #[test]
+    fn gzip_bodies_are_decompressed() {
+        let xml = "<urlset><url><loc>https://example.com/gz</loc></url></urlset>";
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(xml.as_bytes()).unwrap();
+        let gzipped = encoder.finish().unwrap();
+
+        assert_eq!(SitemapDiscovery::decode_body(&gzipped).unwrap(), xml);
+        assert_eq!(SitemapDiscovery::decode_body(xml.as_bytes()).unwrap(), xml);
+    }
+
+    #[test]
+    fn lastmod_formats() {
+        assert_eq!(
+            SitemapDiscovery::parse_lastmod("2024-03-01"),
+            Some(utc("2024-03-01T00:00:00Z"))
+        );
+        assert_eq!(
+            SitemapDiscovery::parse_lastmod("2024-03-01T10:20:30+08:00"),
+            Some(utc("2024-03-01T02:20:30Z"))
+        );
+        assert_eq!(
+            SitemapDiscovery::parse_lastmod("2024-03-01T10:20:30.5Z"),
+            Some(utc("2024-03-01T10:20:30.5Z"))
+        );
+        assert_eq!(
+            SitemapDiscovery::parse_lastmod("2024-03-01T10:20+02:00"),
+            Some(utc("2024-03-01T08:20:00Z"))
+        );
+        assert_eq!(
+            SitemapDiscovery::parse_lastmod("2024-03-01T10:20:30"),
+            Some(utc("2024-03-01T10:20:30Z"))
+        );
+    }
+
+    #[test]
+    fn unparsable_lastmod_is_ignored() {
+        assert_eq!(SitemapDiscovery::parse_lastmod(""), None);
+        assert_eq!(SitemapDiscovery::parse_lastmod("yesterday"), None);
+        assert_eq!(SitemapDiscovery::parse_lastmod("2024-13-40"), None);
+
+        // 页面仍然保留，只是没有 lastmod，按未知修改时间处理
+        let xml =
+            "<urlset><url><loc>https://example.com/a</loc><lastmod>soon</lastmod></url></urlset>";
+        let parsed = SitemapDiscovery::parse_sitemap(xml).unwrap();
+        assert_eq!(parsed.urls.len(), 1);
+        assert_eq!(parsed.urls[0].lastmod, None);
+    }
+}
@@ -0,0 +1,389 @@
+use crate::error::{Error, Result};
+use crate::links_manager::{LinkStatus, LinksManager};
+use crate::types::FileType;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+// 同一类型错误的汇总
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FailureGroup {
+    pub count: usize,
+    // 其中可重试的链接数
+    pub retryable: usize,
+    // 最近一次出现的错误信息
+    pub example: String,
+    #[serde(skip)]
+    latest: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DiskUsage {
+    pub files: usize,
+    pub bytes: u64,
+}
+
+// status 命令的输出，根据状态文件统计一个站点的下载情况
+#[derive(Debug, Clone, Serialize)]
+pub struct StatusReport {
+    pub state_file: PathBuf,
+    pub total: usize,
+    pub processed: usize,
+    pub failed: usize,
+    // 已开始处理但没有结果（例如运行中断）
+    pub pending: usize,
+    // 已保存但仍有错误（例如部分语言翻译失败）
+    pub partial: usize,
+    // 按错误类型（Error::kind）分组，包括 partial 的链接
+    pub failures_by_kind: BTreeMap<String, FailureGroup>,
+    // 每个目标语言已完成翻译的链接数
+    pub languages: BTreeMap<String, usize>,
+    pub last_run: Option<DateTime<Utc>>,
+    // 按文件类型（original / translated / bilingual）统计已保存的文件
+    pub disk_usage: BTreeMap<String, DiskUsage>,
+}
+
+impl StatusReport {
+    pub fn from_links(links_manager: &LinksManager) -> Self {
+        let mut report = Self {
+            state_file: links_manager.state_file_path().to_path_buf(),
+            total: 0,
+            processed: 0,
+            failed: 0,
+            pending: 0,
+            partial: 0,
+            failures_by_kind: BTreeMap::new(),
+            languages: BTreeMap::new(),
+            last_run: None,
+            disk_usage: [
+                FileType::Original,
+                FileType::Translated,
+                FileType::Bilingual,
+            ]
+            .iter()
+            .map(|file_type| (file_type.folder_name().to_string(), DiskUsage::default()))
+            .collect(),
+        };
+
+        let mut seen_files = HashSet::new();
+        for record in links_manager.records() {
+            report.total += 1;
+            match record.status {
+                LinkStatus::Processed => report.processed += 1,
+                LinkStatus::Failed => report.failed += 1,
+                LinkStatus::Pending => report.pending += 1,
+            }
+            report.last_run = report.last_run.max(Some(record.updated_at));
+
+            if record.is_processed() {
+                for lang in record.languages.iter().flatten() {
+                    *report.languages.entry(lang.clone()).or_default() += 1;
+                }
+            }
+
+            if let Some(error) = &record.last_error {
+                if record.is_processed() {
+                    report.partial += 1;
+                }
+                let group = report
+                    .failures_by_kind
+                    .entry(error.kind.clone())
+                    .or_default();
+                group.count += 1;
+                if error.retryable {
+                    group.retryable += 1;
+                }
+                if group.latest.is_none_or(|latest| error.at > latest) {
+                    group.latest = Some(error.at);
+                    group.example = error.message.clone();
+                }
+            }
+
+            for file in &record.files {
+                if !seen_files.insert(file.clone()) {
+                    continue;
+                }
+                // 已被删除或移动的文件不计入
+                let Ok(metadata) = fs::metadata(file) else {
+                    continue;
+                };
+                let usage = report
+                    .disk_usage
+                    .entry(Self::file_type_of(file).to_string())
+                    .or_default();
+                usage.files += 1;
+                usage.bytes += metadata.len();
+            }
+        }
+        report
+    }
+
+    // 根据路径中的类型目录判断文件类型；单语言且不翻译时文件直接保存在站点目录下，视为原文
+    fn file_type_of(path: &Path) -> &'static str {
+        path.components()
+            .rev()
+            .filter_map(|component| match component {
+                Component::Normal(name) => name.to_str(),
+                _ => None,
+            })
+            .find_map(|name| {
+                [
+                    FileType::Original,
+                    FileType::Translated,
+                    FileType::Bilingual,
+                ]
+                .iter()
+                .map(FileType::folder_name)
+                .find(|folder| *folder == name)
+            })
+            .unwrap_or(FileType::Original.folder_name())
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| Error::Custom(format!("Status report serialization failed: {}", e)))
+    }
+
+    pub fn print(&self) {
+        println!("状态文件: {}", self.state_file.display());
+        if self.total == 0 {
+            println!("没有任何链接记录");
+            return;
+        }
+
+        println!("链接总数: {}", self.total);
+        println!("  成功: {}", self.processed);
+        if self.partial > 0 {
+            println!("    其中有错误（如部分语言翻译失败）: {}", self.partial);
+        }
+        println!("  失败: {}", self.failed);
+        if self.pending > 0 {
+            println!("  未完成: {}", self.pending);
+        }
+        if let Some(last_run) = self.last_run {
+            println!(
+                "最近运行: {}",
+                last_run
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+            );
+        }
+
+        if !self.languages.is_empty() {
+            println!("\n已完成翻译:");
+            for (lang, count) in &self.languages {
+                println!("  {}: {} / {}", lang, count, self.processed);
+            }
+        }
+
+        if !self.failures_by_kind.is_empty() {
+            println!("\n失败原因:");
+            for (kind, group) in &self.failures_by_kind {
+                println!(
+                    "  {}: {} 个（可重试 {} 个）",
+                    kind, group.count, group.retryable
+                );
+                println!("    最近: {}", group.example);
+            }
+        }
+
+        println!("\n磁盘占用:");
+        for (folder, usage) in &self.disk_usage {
+            println!(
+                "  {}: {} 个文件，{}",
+                folder,
+                usage.files,
+                Self::format_size(usage.bytes)
+            );
+        }
+    }
+
+    fn format_size(bytes: u64) -> String {
+        const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+        let mut size = bytes as f64;
+        let mut unit = 0;
+        while size >= 1024.0 && unit < UNITS.len() - 1 {
+            size /= 1024.0;
+            unit += 1;
+        }
+        if unit == 0 {
+            format!("{} {}", bytes, UNITS[0])
+        } else {
+            format!("{:.1} {}", size, UNITS[unit])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SITE: &str = "https://example.com/docs/";
+
+    fn save(dir: &Path, file: &str, content: &str) -> String {
+        let path = dir.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content).unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    // a：zh 和 ja 都已完成；b：只完成 zh，ja 超时；c：失败；d：开始处理后中断
+    fn report(dir: &Path) -> StatusReport {
+        let mut manager = LinksManager::new(dir, SITE)
+            .unwrap()
+            .with_languages(vec!["zh".to_string(), "ja".to_string()]);
+        let timeout = Error::Timeout {
+            service: "DeepLX".to_string(),
+        };
+        let not_found = Error::HttpStatus {
+            service: "r.jina.ai".to_string(),
+            status: 404,
+            body: String::new(),
+        };
+        let (a, b, c, d) = (
+            "https://example.com/docs/a",
+            "https://example.com/docs/b",
+            "https://example.com/docs/c",
+            "https://example.com/docs/d",
+        );
+
+        let files_a = vec![
+            save(dir, "site/original/a.md", "12345"),
+            save(dir, "site/translated/zh/a.md", "123"),
+            save(dir, "site/bilingual/zh/a.md", "12345678"),
+            save(dir, "site/translated/ja/a.md", "12"),
+        ];
+        manager.begin_attempt(a).unwrap();
+        manager
+            .mark_as_processed(a, &files_a, &["zh".to_string(), "ja".to_string()], "hash")
+            .unwrap();
+
+        // 与 a 重复的文件只统计一次，已删除的文件不计入
+        let files_b = vec![
+            save(dir, "site/original/b.md", "1"),
+            files_a[0].clone(),
+            dir.join("gone.md").to_string_lossy().to_string(),
+        ];
+        manager.begin_attempt(b).unwrap();
+        manager
+            .mark_as_processed(b, &files_b, &["zh".to_string()], "hash")
+            .unwrap();
+        manager
+            .mark_as_failed(b, "ja: timed out", &timeout)
+            .unwrap();
+
+        manager.begin_attempt(c).unwrap();
+        manager.mark_as_failed(c, "timed out", &timeout).unwrap();
+        manager.begin_attempt(c).unwrap();
+        manager.mark_as_failed(c, "not found", &not_found).unwrap();
+
+        manager.begin_attempt(d).unwrap();
+
+        StatusReport::from_links(&LinksManager::new(dir, SITE).unwrap())
+    }
+
+    #[test]
+    fn links_are_counted_by_status() {
+        let dir = tempfile::tempdir().unwrap();
+        let report = report(dir.path());
+
+        assert_eq!(
+            (
+                report.total,
+                report.processed,
+                report.failed,
+                report.pending,
+                report.partial
+            ),
+            (4, 2, 1, 1, 1)
+        );
+        assert!(report.last_run.is_some());
+
+        let timeout = &report.failures_by_kind["timeout"];
+        assert_eq!(
+            (timeout.count, timeout.retryable, timeout.example.as_str()),
+            (1, 1, "ja: timed out")
+        );
+        let http_status = &report.failures_by_kind["http_status"];
+        assert_eq!(
+            (
+                http_status.count,
+                http_status.retryable,
+                http_status.example.as_str()
+            ),
+            (1, 0, "not found")
+        );
+    }
+
+    #[test]
+    fn completed_languages_are_counted_per_language() {
+        let dir = tempfile::tempdir().unwrap();
+        let report = report(dir.path());
+
+        assert_eq!(
+            report.languages,
+            BTreeMap::from([("ja".to_string(), 1), ("zh".to_string(), 2)])
+        );
+    }
+
+    #[test]
+    fn disk_usage_is_grouped_by_file_type() {
+        let dir = tempfile::tempdir().unwrap();
+        let usage = report(dir.path()).disk_usage;
+
+        assert_eq!((usage["original"].files, usage["original"].bytes), (2, 6));
+        assert_eq!(
+            (usage["translated"].files, usage["translated"].bytes),
+            (2, 5)
+        );
+        assert_eq!((usage["bilingual"].files, usage["bilingual"].bytes), (1, 8));
+        assert_eq!(
+            StatusReport::file_type_of(Path::new("out/example.com/a.md")),
+            "original"
+        );
+    }
+
+    #[test]
+    fn json_report_shape() {
+        let dir = tempfile::tempdir().unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&report(dir.path()).to_json().unwrap()).unwrap();
+
+        for key in [
+            "state_file",
+            "total",
+            "processed",
+            "failed",
+            "pending",
+            "partial",
+            "failures_by_kind",
+            "languages",
+            "last_run",
+            "disk_usage",
+        ] {
+            assert!(json.get(key).is_some(), "missing {}", key);
+        }
+        assert_eq!(json["total"], 4);
+        assert_eq!(
+            json["failures_by_kind"]["timeout"],
+            serde_json::json!({ "count": 1, "retryable": 1, "example": "ja: timed out" })
+        );
+        assert_eq!(json["languages"]["zh"], 2);
+        assert_eq!(
+            json["disk_usage"]["bilingual"],
+            serde_json::json!({ "files": 1, "bytes": 8 })
+        );
+    }
+
+    #[test]
+    fn sizes_are_human_readable() {
+        assert_eq!(StatusReport::format_size(512), "512 B");
+        assert_eq!(StatusReport::format_size(1536), "1.5 KB");
+        assert_eq!(
+            StatusReport::format_size(5 * 1024 * 1024 * 1024 * 1024),
+            "5120.0 GB"
+        );
+    }
+}
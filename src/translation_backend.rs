@@ -0,0 +1,555 @@
+use crate::error::{Error, Result};
+use crate::rate_limiter::check_rate_limited;
+use crate::types::{
+    ChatCompletionRequest, ChatCompletionResponse, ChatMessage, DeepLRequest, DeepLResponse,
+    DeepLXRequest, DeepLXResponse, DpTransResponse, LibreTranslateRequest, LibreTranslateResponse,
+    TranslationBackendKind, TranslationConfig,
+};
+use futures::future::BoxFuture;
+use reqwest::{Client, Response};
+use serde::de::DeserializeOwned;
+use std::sync::Arc;
+
+// 翻译服务提供方。限流和重试由 TranslationService 统一处理，后端只负责单次请求
+pub trait TranslationBackend: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn translate<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<String>>;
+}
+
+pub fn create_backend(client: Client, config: &TranslationConfig) -> Arc<dyn TranslationBackend> {
+    let endpoint = Endpoint::new(client, config);
+    match config.backend {
+        TranslationBackendKind::DeepLX => Arc::new(DeepLXBackend { endpoint }),
+        TranslationBackendKind::DpTrans => Arc::new(DpTransBackend { endpoint }),
+        TranslationBackendKind::LibreTranslate => Arc::new(LibreTranslateBackend { endpoint }),
+        TranslationBackendKind::DeepL => Arc::new(DeepLBackend { endpoint }),
+        TranslationBackendKind::OpenAI => Arc::new(OpenAIBackend {
+            endpoint,
+            model: config.model.clone(),
+        }),
+    }
+}
+
+// 各后端共用的接口地址、密钥和语言设置
+struct Endpoint {
+    client: Client,
+    api_url: String,
+    api_key: Option<String>,
+    source_lang: String,
+    target_lang: String,
+}
+
+impl Endpoint {
+    fn new(client: Client, config: &TranslationConfig) -> Self {
+        Self {
+            client,
+            api_url: config.deeplx_api_url.clone(),
+            api_key: config.resolved_api_key(),
+            source_lang: config.source_lang.clone(),
+            target_lang: config.target_lang().to_string(),
+        }
+    }
+
+    fn is_auto_source(&self) -> bool {
+        self.source_lang.eq_ignore_ascii_case("auto")
+    }
+
+    fn require_api_key(&self, backend: &str) -> Result<&str> {
+        self.api_key
+            .as_deref()
+            .ok_or_else(|| Error::rejected(backend, "an api_key is required"))
+    }
+
+    async fn read_json<T: DeserializeOwned>(response: Response, backend: &str) -> Result<T> {
+        let body = Self::read_body(response, backend).await?;
+        serde_json::from_str(&body).map_err(|e| Error::invalid_response(backend, e, &body))
+    }
+
+    async fn read_body(response: Response, backend: &str) -> Result<String> {
+        let status = response.status();
+        println!("{}响应状态: {}", backend, status);
+        check_rate_limited(&response, backend)?;
+
+        let body = response
+            .text()
+            .await
+            .map_err(|e| Error::network(backend, e))?;
+
+        if !status.is_success() {
+            return Err(Error::http_status(backend, status, &body));
+        }
+        Ok(body)
+    }
+}
+
+fn non_empty(text: String, backend: &str) -> Result<String> {
+    if text.trim().is_empty() {
+        Err(Error::empty_result(backend))
+    } else {
+        Ok(text)
+    }
+}
+
+pub struct DeepLXBackend {
+    endpoint: Endpoint,
+}
+
+impl DeepLXBackend {
+    fn request(&self, text: &str) -> DeepLXRequest {
+        DeepLXRequest {
+            text: text.to_string(),
+            source_lang: self.endpoint.source_lang.clone(),
+            target_lang: self.endpoint.target_lang.clone(),
+        }
+    }
+
+    // DeepLX 把上游的 HTTP 状态放在 code 字段中
+    fn parse(result: DeepLXResponse) -> Result<String> {
+        if result.code != 200 {
+            return Err(match u16::try_from(result.code) {
+                Ok(429) => Error::RateLimited {
+                    service: "DeepLX".to_string(),
+                    retry_after: None,
+                },
+                Ok(status) => Error::HttpStatus {
+                    service: "DeepLX".to_string(),
+                    status,
+                    body: String::new(),
+                },
+                Err(_) => Error::protocol("DeepLX", format!("返回代码: {}", result.code)),
+            });
+        }
+        non_empty(result.data, "DeepLX")
+    }
+}
+
+impl TranslationBackend for DeepLXBackend {
+    fn name(&self) -> &'static str {
+        "deeplx"
+    }
+
+    fn translate<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<String>> {
+        Box::pin(async move {
+            let request = self.request(text);
+
+            let mut builder = self
+                .endpoint
+                .client
+                .post(&self.endpoint.api_url)
+                .header("Content-Type", "application/json")
+                .header("Accept", "application/json")
+                .json(&request);
+            if let Some(key) = &self.endpoint.api_key {
+                builder = builder.bearer_auth(key);
+            }
+
+            let response = builder
+                .send()
+                .await
+                .map_err(|e| Error::network("DeepLX", e))?;
+            let result: DeepLXResponse = Endpoint::read_json(response, "DeepLX").await?;
+            Self::parse(result)
+        })
+    }
+}
+
+pub struct DpTransBackend {
+    endpoint: Endpoint,
+}
+
+// dptrans 的请求格式与 DeepLX 相同
+impl DpTransBackend {
+    fn request(&self, text: &str) -> DeepLXRequest {
+        DeepLXRequest {
+            text: text.to_string(),
+            source_lang: if self.endpoint.is_auto_source() {
+                "auto".to_string()
+            } else {
+                self.endpoint.source_lang.clone()
+            },
+            target_lang: self.endpoint.target_lang.clone(),
+        }
+    }
+
+    // dptrans 可能直接返回纯文本译文
+    fn parse(body: String) -> Result<String> {
+        if !body.trim_start().starts_with('{') {
+            return non_empty(body, "dptrans");
+        }
+        let result: DpTransResponse = serde_json::from_str(&body)
+            .map_err(|e| Error::invalid_response("dptrans", e, &body))?;
+        non_empty(result.data, "dptrans")
+    }
+}
+
+impl TranslationBackend for DpTransBackend {
+    fn name(&self) -> &'static str {
+        "dptrans"
+    }
+
+    fn translate<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<String>> {
+        Box::pin(async move {
+            let request = self.request(text);
+
+            let response = self
+                .endpoint
+                .client
+                .post(&self.endpoint.api_url)
+                .header("Content-Type", "application/json")
+                .header("Accept", "application/json, text/plain, */*")
+                .header(
+                    "User-Agent",
+                    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36",
+                )
+                .json(&request)
+                .send()
+                .await
+                .map_err(|e| Error::network("dptrans", e))?;
+
+            let body = Endpoint::read_body(response, "dptrans").await?;
+            Self::parse(body)
+        })
+    }
+}
+
+pub struct LibreTranslateBackend {
+    endpoint: Endpoint,
+}
+
+impl LibreTranslateBackend {
+    fn request(&self, text: &str) -> LibreTranslateRequest {
+        LibreTranslateRequest {
+            q: text.to_string(),
+            source: self.endpoint.source_lang.to_lowercase(),
+            target: self.endpoint.target_lang.to_lowercase(),
+            format: "text".to_string(),
+            api_key: self.endpoint.api_key.clone(),
+        }
+    }
+}
+
+impl TranslationBackend for LibreTranslateBackend {
+    fn name(&self) -> &'static str {
+        "libretranslate"
+    }
+
+    fn translate<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<String>> {
+        Box::pin(async move {
+            let request = self.request(text);
+
+            let response = self
+                .endpoint
+                .client
+                .post(&self.endpoint.api_url)
+                .json(&request)
+                .send()
+                .await
+                .map_err(|e| Error::network("LibreTranslate", e))?;
+
+            let result: LibreTranslateResponse =
+                Endpoint::read_json(response, "LibreTranslate").await?;
+            non_empty(result.translated_text, "LibreTranslate")
+        })
+    }
+}
+
+pub struct DeepLBackend {
+    endpoint: Endpoint,
+}
+
+impl DeepLBackend {
+    fn request(&self, text: &str) -> DeepLRequest {
+        DeepLRequest {
+            text: vec![text.to_string()],
+            source_lang: (!self.endpoint.is_auto_source())
+                .then(|| self.endpoint.source_lang.to_uppercase()),
+            target_lang: self.endpoint.target_lang.to_uppercase(),
+            preserve_formatting: true,
+        }
+    }
+
+    fn parse(result: DeepLResponse) -> Result<String> {
+        let translated = result
+            .translations
+            .into_iter()
+            .next()
+            .map(|translation| translation.text)
+            .unwrap_or_default();
+        non_empty(translated, "DeepL")
+    }
+}
+
+impl TranslationBackend for DeepLBackend {
+    fn name(&self) -> &'static str {
+        "deepl"
+    }
+
+    fn translate<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<String>> {
+        Box::pin(async move {
+            let api_key = self.endpoint.require_api_key("DeepL")?;
+            let request = self.request(text);
+
+            let response = self
+                .endpoint
+                .client
+                .post(&self.endpoint.api_url)
+                .header("Authorization", format!("DeepL-Auth-Key {}", api_key))
+                .json(&request)
+                .send()
+                .await
+                .map_err(|e| Error::network("DeepL", e))?;
+
+            let result: DeepLResponse = Endpoint::read_json(response, "DeepL").await?;
+            Self::parse(result)
+        })
+    }
+}
+
+pub struct OpenAIBackend {
+    endpoint: Endpoint,
+    model: String,
+}
+
+impl OpenAIBackend {
+    fn system_prompt(&self) -> String {
+        let source = if self.endpoint.is_auto_source() {
+            "the source language".to_string()
+        } else {
+            self.endpoint.source_lang.clone()
+        };
+
+        format!(
+            "You are a translation engine. Translate the user's Markdown text from {} to {}. \
+             Output only the translation. Keep placeholders such as ⟦0⟧ or ⟦T0⟧ exactly as they are, \
+             keep Markdown syntax unchanged and keep blank lines between paragraphs.",
+            source, self.endpoint.target_lang
+        )
+    }
+
+    fn request(&self, text: &str) -> ChatCompletionRequest {
+        ChatCompletionRequest {
+            model: self.model.clone(),
+            messages: vec![
+                ChatMessage {
+                    role: "system".to_string(),
+                    content: self.system_prompt(),
+                },
+                ChatMessage {
+                    role: "user".to_string(),
+                    content: text.to_string(),
+                },
+            ],
+            temperature: 0.0,
+        }
+    }
+
+    fn parse(result: ChatCompletionResponse) -> Result<String> {
+        let translated = result
+            .choices
+            .into_iter()
+            .next()
+            .map(|choice| choice.message.content)
+            .unwrap_or_default();
+        non_empty(translated, "OpenAI")
+    }
+}
+
+impl TranslationBackend for OpenAIBackend {
+    fn name(&self) -> &'static str {
+        "openai"
+    }
+
+    fn translate<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<String>> {
+        Box::pin(async move {
+            let request = self.request(text);
+
+            // 本地部署的兼容接口可以不需要密钥
+            let mut builder = self
+                .endpoint
+                .client
+                .post(&self.endpoint.api_url)
+                .json(&request);
+            if let Some(key) = &self.endpoint.api_key {
+                builder = builder.bearer_auth(key);
+            }
+
+            let response = builder
+                .send()
+                .await
+                .map_err(|e| Error::network("OpenAI", e))?;
+            let result: ChatCompletionResponse = Endpoint::read_json(response, "OpenAI").await?;
+            Self::parse(result)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn endpoint(source_lang: &str, api_key: Option<&str>) -> Endpoint {
+        let config = TranslationConfig {
+            source_lang: source_lang.to_string(),
+            target_langs: vec!["zh-Hans".to_string()],
+            api_key: api_key.map(str::to_string),
+            ..TranslationConfig::default()
+        };
+        Endpoint::new(Client::new(), &config)
+    }
+
+    #[test]
+    fn deeplx_request_and_response() {
+        let backend = DeepLXBackend {
+            endpoint: endpoint("en", None),
+        };
+        assert_eq!(
+            serde_json::to_value(backend.request("Hello")).unwrap(),
+            json!({ "text": "Hello", "source_lang": "en", "target_lang": "zh-Hans" })
+        );
+
+        let response = |body| serde_json::from_value::<DeepLXResponse>(body).unwrap();
+        assert_eq!(
+            DeepLXBackend::parse(response(json!({ "code": 200, "data": "你好" }))).unwrap(),
+            "你好"
+        );
+        assert!(matches!(
+            DeepLXBackend::parse(response(json!({ "code": 429, "data": "" }))),
+            Err(Error::RateLimited { .. })
+        ));
+        assert!(matches!(
+            DeepLXBackend::parse(response(json!({ "code": 503, "data": "" }))),
+            Err(Error::HttpStatus { status: 503, .. })
+        ));
+    }
+
+    #[test]
+    fn dptrans_accepts_plain_text_and_json() {
+        let backend = DpTransBackend {
+            endpoint: endpoint("AUTO", None),
+        };
+        assert_eq!(
+            serde_json::to_value(backend.request("Hello")).unwrap(),
+            json!({ "text": "Hello", "source_lang": "auto", "target_lang": "zh-Hans" })
+        );
+
+        assert_eq!(DpTransBackend::parse("你好".to_string()).unwrap(), "你好");
+        assert_eq!(
+            DpTransBackend::parse(r#"{"data": "你好"}"#.to_string()).unwrap(),
+            "你好"
+        );
+        assert_eq!(
+            DpTransBackend::parse(r#"{"translated_text": "你好"}"#.to_string()).unwrap(),
+            "你好"
+        );
+        assert!(matches!(
+            DpTransBackend::parse("  ".to_string()),
+            Err(Error::EmptyResult { .. })
+        ));
+    }
+
+    #[test]
+    fn invalid_responses_keep_only_the_start_of_the_body() {
+        let body = format!("{{\"unexpected\": \"{}\"}}", "x".repeat(1000));
+        match DpTransBackend::parse(body) {
+            Err(Error::Protocol { service, message }) => {
+                assert_eq!(service, "dptrans");
+                assert!(message.chars().count() < 300, "{}", message);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn libretranslate_request_and_response() {
+        let backend = LibreTranslateBackend {
+            endpoint: endpoint("EN", None),
+        };
+        assert_eq!(
+            serde_json::to_value(backend.request("Hello")).unwrap(),
+            json!({ "q": "Hello", "source": "en", "target": "zh-hans", "format": "text" })
+        );
+
+        let backend = LibreTranslateBackend {
+            endpoint: endpoint("en", Some("secret")),
+        };
+        assert_eq!(
+            serde_json::to_value(backend.request("Hello")).unwrap()["api_key"],
+            "secret"
+        );
+
+        let response: LibreTranslateResponse =
+            serde_json::from_value(json!({ "translatedText": "你好" })).unwrap();
+        assert_eq!(response.translated_text, "你好");
+    }
+
+    #[test]
+    fn deepl_request_and_response() {
+        let backend = DeepLBackend {
+            endpoint: endpoint("auto", Some("secret")),
+        };
+        assert_eq!(
+            serde_json::to_value(backend.request("Hello")).unwrap(),
+            json!({ "text": ["Hello"], "target_lang": "ZH-HANS", "preserve_formatting": true })
+        );
+
+        let backend = DeepLBackend {
+            endpoint: endpoint("en", Some("secret")),
+        };
+        assert_eq!(
+            serde_json::to_value(backend.request("Hello")).unwrap()["source_lang"],
+            "EN"
+        );
+
+        let response = serde_json::from_value(json!({
+            "translations": [{ "detected_source_language": "EN", "text": "你好" }]
+        }))
+        .unwrap();
+        assert_eq!(DeepLBackend::parse(response).unwrap(), "你好");
+
+        let empty = serde_json::from_value(json!({ "translations": [] })).unwrap();
+        assert!(matches!(
+            DeepLBackend::parse(empty),
+            Err(Error::EmptyResult { .. })
+        ));
+    }
+
+    #[test]
+    fn deepl_requires_an_api_key() {
+        let endpoint = endpoint("en", None);
+        assert!(matches!(
+            endpoint.require_api_key("DeepL"),
+            Err(Error::Rejected { .. })
+        ));
+    }
+
+    #[test]
+    fn openai_request_and_response() {
+        let backend = OpenAIBackend {
+            endpoint: endpoint("en", None),
+            model: "gpt-4o-mini".to_string(),
+        };
+        let request = serde_json::to_value(backend.request("Hello")).unwrap();
+
+        assert_eq!(request["model"], "gpt-4o-mini");
+        assert_eq!(request["temperature"], 0.0);
+        assert_eq!(request["messages"][0]["role"], "system");
+        assert!(
+            request["messages"][0]["content"]
+                .as_str()
+                .unwrap()
+                .contains("from en to zh-Hans")
+        );
+        assert_eq!(
+            request["messages"][1],
+            json!({ "role": "user", "content": "Hello" })
+        );
+
+        let response = serde_json::from_value(json!({
+            "choices": [{ "message": { "role": "assistant", "content": "你好" } }]
+        }))
+        .unwrap();
+        assert_eq!(OpenAIBackend::parse(response).unwrap(), "你好");
+    }
+}
@@ -0,0 +1,434 @@
+use crate::error::{Error, Result};
+use crate::types::TranslationConfig;
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const MEMORY_FILE_NAME: &str = "translation_memory.jsonl";
+
+// last_used 的精度：命中时距上次记录超过这个时间才需要写回，避免每次运行都写文件
+const LAST_USED_RESOLUTION: TimeDelta = TimeDelta::days(1);
+
+// 翻译记忆中的一条记录；source 为带占位符的原文片段
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryEntry {
+    pub hash: String,
+    pub source_lang: String,
+    pub target_lang: String,
+    pub backend: String,
+    // TranslationConfig::fingerprint()，接口地址、模型或术语表变化后不再复用旧译文；旧记录没有此字段
+    #[serde(default)]
+    pub fingerprint: String,
+    pub source: String,
+    pub translation: String,
+    pub created_at: DateTime<Utc>,
+    pub last_used: DateTime<Utc>,
+}
+
+impl MemoryEntry {
+    fn key(&self) -> String {
+        Self::make_key(
+            &self.hash,
+            &self.source_lang,
+            &self.target_lang,
+            &self.backend,
+            &self.fingerprint,
+        )
+    }
+
+    fn make_key(
+        hash: &str,
+        source_lang: &str,
+        target_lang: &str,
+        backend: &str,
+        fingerprint: &str,
+    ) -> String {
+        format!(
+            "{}|{}|{}|{}|{}",
+            hash, source_lang, target_lang, backend, fingerprint
+        )
+    }
+}
+
+struct MemoryState {
+    entries: HashMap<String, MemoryEntry>,
+    // 命中时更新了 last_used 的记录，结束时追加到文件
+    touched: HashSet<String>,
+    // 文件中的记录行数，包括被后面的记录覆盖的旧行
+    lines: usize,
+}
+
+// 保存在输出目录下的翻译记忆（JSONL，每行一条记录，后出现的记录覆盖前面的）。
+// 以 (原文片段哈希, 源语言, 目标语言, 翻译后端, 翻译设置指纹) 为键，重复运行时未变化的片段不再请求翻译接口
+pub struct TranslationMemory {
+    path: PathBuf,
+    state: Mutex<MemoryState>,
+}
+
+impl TranslationMemory {
+    pub fn open(output_dir: &Path) -> Result<Self> {
+        let path = output_dir.join(MEMORY_FILE_NAME);
+        let mut entries = HashMap::new();
+        let mut lines = 0;
+
+        if path.exists() {
+            let content = fs::read_to_string(&path).map_err(|e| Error::filesystem(&path, e))?;
+            for (line_number, line) in content.lines().enumerate() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                lines += 1;
+                match serde_json::from_str::<MemoryEntry>(line) {
+                    Ok(entry) => {
+                        entries.insert(entry.key(), entry);
+                    }
+                    Err(e) => {
+                        eprintln!("跳过无效的翻译记忆记录 (第 {} 行): {}", line_number + 1, e)
+                    }
+                }
+            }
+            println!("从 {} 加载了 {} 条翻译记忆", path.display(), entries.len());
+        }
+
+        Ok(Self {
+            path,
+            state: Mutex::new(MemoryState {
+                entries,
+                touched: HashSet::new(),
+                lines,
+            }),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn lookup(&self, source: &str, config: &TranslationConfig) -> Option<String> {
+        let key = Self::key_for(source, config);
+        let mut state = self.lock();
+        let entry = state.entries.get_mut(&key)?;
+        let translation = entry.translation.clone();
+
+        let now = Utc::now();
+        if now - entry.last_used >= LAST_USED_RESOLUTION {
+            entry.last_used = now;
+            state.touched.insert(key);
+        }
+        Some(translation)
+    }
+
+    pub fn store(&self, source: &str, translation: &str, config: &TranslationConfig) -> Result<()> {
+        let now = Utc::now();
+        let entry = MemoryEntry {
+            hash: Self::hash(source),
+            source_lang: config.source_lang.to_lowercase(),
+            target_lang: config.target_lang().to_lowercase(),
+            backend: config.backend_name().to_string(),
+            fingerprint: config.fingerprint(),
+            source: source.to_string(),
+            translation: translation.to_string(),
+            created_at: now,
+            last_used: now,
+        };
+
+        // 立即追加到文件，运行中断也不会丢失已完成的翻译；持有锁以免与 save 的重写交错
+        let line = serde_json::to_string(&entry).map_err(Self::json_error)?;
+        let mut state = self.lock();
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| Error::filesystem(parent, e))?;
+        }
+        fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(format!("{}\n", line).as_bytes()))
+            .map_err(|e| Error::filesystem(&self.path, e))?;
+
+        state.lines += 1;
+        state.touched.remove(&entry.key());
+        state.entries.insert(entry.key(), entry);
+        Ok(())
+    }
+
+    // 把更新了 last_used 的记录追加到文件；被覆盖的旧行超过一半时整体重写以压缩文件
+    pub fn save(&self) -> Result<()> {
+        let mut state = self.lock();
+        if state.lines + state.touched.len() > state.entries.len() * 2 {
+            Self::write_entries(&self.path, state.entries.values())?;
+            state.lines = state.entries.len();
+        } else if !state.touched.is_empty() {
+            let mut content = String::new();
+            for entry in state
+                .touched
+                .iter()
+                .filter_map(|key| state.entries.get(key))
+            {
+                content.push_str(&serde_json::to_string(entry).map_err(Self::json_error)?);
+                content.push('\n');
+            }
+            fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)
+                .and_then(|mut file| file.write_all(content.as_bytes()))
+                .map_err(|e| Error::filesystem(&self.path, e))?;
+            state.lines += state.touched.len();
+        }
+        state.touched.clear();
+        Ok(())
+    }
+
+    // 删除超过 older_than_days 天未使用的记录并压缩文件，返回删除的记录数
+    pub fn prune(&self, older_than_days: u64) -> Result<usize> {
+        let cutoff = Self::cutoff(older_than_days)?;
+        let mut state = self.lock();
+
+        let before = state.entries.len();
+        state.entries.retain(|_, entry| entry.last_used >= cutoff);
+        let removed = before - state.entries.len();
+
+        // 还没有记忆文件（输出目录可能也不存在）时不需要重写
+        if removed == 0 && !self.path.exists() {
+            return Ok(0);
+        }
+
+        Self::write_entries(&self.path, state.entries.values())?;
+        state.lines = state.entries.len();
+        state.touched.clear();
+        Ok(removed)
+    }
+
+    // 导出为 JSON 数组，便于人工校对或导入其他工具
+    pub fn export(&self, export_path: &Path) -> Result<usize> {
+        let state = self.lock();
+        let mut entries: Vec<&MemoryEntry> = state.entries.values().collect();
+        entries.sort_by_key(|entry| entry.created_at);
+
+        let content = serde_json::to_string_pretty(&entries).map_err(Self::json_error)?;
+        fs::write(export_path, content).map_err(|e| Error::filesystem(export_path, e))?;
+        Ok(entries.len())
+    }
+
+    fn write_entries<'a>(
+        path: &Path,
+        entries: impl Iterator<Item = &'a MemoryEntry>,
+    ) -> Result<()> {
+        let mut entries: Vec<&MemoryEntry> = entries.collect();
+        entries.sort_by_key(|entry| entry.created_at);
+
+        let mut content = String::new();
+        for entry in entries {
+            content.push_str(&serde_json::to_string(entry).map_err(Self::json_error)?);
+            content.push('\n');
+        }
+
+        // 先写临时文件再替换，避免写到一半时损坏记忆文件
+        let temp_path = path.with_extension("jsonl.tmp");
+        fs::write(&temp_path, content).map_err(|e| Error::filesystem(&temp_path, e))?;
+        fs::rename(&temp_path, path).map_err(|e| Error::filesystem(path, e))?;
+        Ok(())
+    }
+
+    // older_than_days 天前的时间点；超出时间范围时返回错误
+    pub fn cutoff(older_than_days: u64) -> Result<DateTime<Utc>> {
+        i64::try_from(older_than_days)
+            .ok()
+            .and_then(TimeDelta::try_days)
+            .and_then(|age| Utc::now().checked_sub_signed(age))
+            .ok_or_else(|| {
+                Error::Custom(format!("Number of days out of range: {}", older_than_days))
+            })
+    }
+
+    fn key_for(source: &str, config: &TranslationConfig) -> String {
+        MemoryEntry::make_key(
+            &Self::hash(source),
+            &config.source_lang.to_lowercase(),
+            &config.target_lang().to_lowercase(),
+            config.backend_name(),
+            &config.fingerprint(),
+        )
+    }
+
+    fn json_error(e: serde_json::Error) -> crate::error::Error {
+        crate::error::Error::Custom(format!("Translation memory serialization failed: {}", e))
+    }
+
+    fn hash(source: &str) -> String {
+        format!("{:x}", Sha256::digest(source.as_bytes()))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, MemoryState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::TranslationBackendKind;
+
+    fn entry(source: &str, config: &TranslationConfig, days_ago: i64) -> MemoryEntry {
+        let at = Utc::now() - TimeDelta::days(days_ago);
+        MemoryEntry {
+            hash: TranslationMemory::hash(source),
+            source_lang: config.source_lang.clone(),
+            target_lang: config.target_lang().to_string(),
+            backend: config.backend_name().to_string(),
+            fingerprint: config.fingerprint(),
+            source: source.to_string(),
+            translation: format!("{} (translated)", source),
+            created_at: at,
+            last_used: at,
+        }
+    }
+
+    fn write_entries(dir: &Path, entries: &[MemoryEntry]) {
+        let content: String = entries
+            .iter()
+            .map(|entry| format!("{}\n", serde_json::to_string(entry).unwrap()))
+            .collect();
+        fs::write(dir.join(MEMORY_FILE_NAME), content).unwrap();
+    }
+
+    #[test]
+    fn lookup_is_keyed_by_language_backend_and_fingerprint() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = TranslationConfig::default();
+        let memory = TranslationMemory::open(dir.path()).unwrap();
+        memory.store("Hello", "你好", &config).unwrap();
+
+        assert_eq!(memory.lookup("Hello", &config).as_deref(), Some("你好"));
+        assert_eq!(memory.lookup("Hello!", &config), None);
+        assert_eq!(memory.lookup("Hello", &config.for_language("ja")), None);
+
+        let other_backend = TranslationConfig {
+            backend: TranslationBackendKind::DpTrans,
+            ..config.clone()
+        };
+        assert_eq!(memory.lookup("Hello", &other_backend), None);
+
+        let other_model = TranslationConfig {
+            model: "gpt-4o".to_string(),
+            ..config.clone()
+        };
+        assert_eq!(memory.lookup("Hello", &other_model), None);
+
+        // 记录立即写入文件，重新打开后仍然命中
+        let reopened = TranslationMemory::open(dir.path()).unwrap();
+        assert_eq!(reopened.lookup("Hello", &config).as_deref(), Some("你好"));
+    }
+
+    #[test]
+    fn recent_hits_do_not_rewrite_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = TranslationConfig::default();
+        write_entries(dir.path(), &[entry("Hello", &config, 0)]);
+        let before = fs::read_to_string(dir.path().join(MEMORY_FILE_NAME)).unwrap();
+
+        let memory = TranslationMemory::open(dir.path()).unwrap();
+        assert!(memory.lookup("Hello", &config).is_some());
+        memory.save().unwrap();
+
+        assert_eq!(
+            fs::read_to_string(dir.path().join(MEMORY_FILE_NAME)).unwrap(),
+            before
+        );
+    }
+
+    #[test]
+    fn stale_last_used_is_appended_on_save() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = TranslationConfig::default();
+        write_entries(
+            dir.path(),
+            &[entry("Hello", &config, 3), entry("World", &config, 3)],
+        );
+
+        let memory = TranslationMemory::open(dir.path()).unwrap();
+        assert!(memory.lookup("Hello", &config).is_some());
+        memory.save().unwrap();
+
+        let content = fs::read_to_string(dir.path().join(MEMORY_FILE_NAME)).unwrap();
+        assert_eq!(content.lines().count(), 3);
+
+        // 更新后的 last_used 让这条记录不会被清理
+        let reopened = TranslationMemory::open(dir.path()).unwrap();
+        assert_eq!(reopened.prune(2).unwrap(), 1);
+        assert!(reopened.lookup("Hello", &config).is_some());
+        assert!(reopened.lookup("World", &config).is_none());
+    }
+
+    #[test]
+    fn prune_removes_entries_unused_since_the_cutoff() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = TranslationConfig::default();
+        write_entries(
+            dir.path(),
+            &[
+                entry("Old", &config, 40),
+                entry("Recent", &config, 1),
+                entry("Recent", &config, 1),
+            ],
+        );
+
+        let memory = TranslationMemory::open(dir.path()).unwrap();
+        assert_eq!(memory.prune(30).unwrap(), 1);
+        assert_eq!(memory.len(), 1);
+
+        let content = fs::read_to_string(dir.path().join(MEMORY_FILE_NAME)).unwrap();
+        assert_eq!(content.lines().count(), 1);
+        assert!(content.contains("\"source\":\"Recent\""));
+    }
+
+    #[test]
+    fn prune_without_a_memory_file_does_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let output_dir = dir.path().join("not-created-yet");
+
+        let memory = TranslationMemory::open(&output_dir).unwrap();
+        assert_eq!(memory.prune(30).unwrap(), 0);
+        assert!(!output_dir.exists());
+    }
+
+    #[test]
+    fn prune_rejects_out_of_range_ages() {
+        assert!(TranslationMemory::cutoff(u64::MAX).is_err());
+        assert!(TranslationMemory::cutoff(0).is_ok());
+    }
+
+    #[test]
+    fn export_writes_entries_oldest_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = TranslationConfig::default();
+        write_entries(
+            dir.path(),
+            &[entry("Newer", &config, 1), entry("Older", &config, 5)],
+        );
+
+        let memory = TranslationMemory::open(dir.path()).unwrap();
+        let export_path = dir.path().join("export.json");
+        assert_eq!(memory.export(&export_path).unwrap(), 2);
+
+        let exported: Vec<MemoryEntry> =
+            serde_json::from_str(&fs::read_to_string(&export_path).unwrap()).unwrap();
+        let sources: Vec<&str> = exported.iter().map(|entry| entry.source.as_str()).collect();
+        assert_eq!(sources, vec!["Older", "Newer"]);
+    }
+}
@@ -1,71 +1,24 @@
-use crate::types::{TranslationConfig, DeepLXRequest, DeepLXResponse, DpTransRequest, RetryConfig, TextSegment};
 use crate::error::Result;
+use crate::glossary::{Glossary, MaskedText};
+use crate::markdown_segments::MarkdownSegments;
+use crate::translation_backend::{TranslationBackend, create_backend};
+use crate::translation_memory::TranslationMemory;
+use crate::types::{RetryConfig, TranslationConfig};
+use futures::stream::{self, StreamExt};
 use reqwest::Client;
+use std::ops::Range;
 use std::sync::Arc;
-use std::time::Duration;
-use tokio::sync::Semaphore;
-use tokio::time::sleep;
 
-#[derive(Clone)]
-pub struct RateLimiter {
-    semaphore: Arc<Semaphore>,
-    delay: Duration,
-}
-
-impl RateLimiter {
-    pub fn new(requests_per_second: f64) -> Self {
-        let permits = requests_per_second.ceil() as usize;
-        let delay = Duration::from_millis((1000.0 / requests_per_second) as u64);
-
-        Self {
-            semaphore: Arc::new(Semaphore::new(permits)),
-            delay,
-        }
-    }
-
-    pub async fn acquire(&self) -> Result<()> {
-        let _permit = self.semaphore.acquire().await
-            .map_err(|e| crate::error::Error::Custom(format!("Rate limiter error: {}", e)))?;
-        sleep(self.delay).await;
-        Ok(())
-    }
-}
-
-pub async fn retry_with_backoff<F, Fut, T>(
-    mut operation: F,
-    config: &RetryConfig,
-    rate_limiter: &RateLimiter,
-) -> Result<T>
-where
-    F: FnMut() -> Fut,
-    Fut: std::future::Future<Output = Result<T>>,
-{
-    let mut delay = config.initial_delay_ms;
-
-    for attempt in 0..=config.max_retries {
-        rate_limiter.acquire().await?;
-
-        match operation().await {
-            Ok(result) => return Ok(result),
-            Err(e) if attempt == config.max_retries => return Err(e),
-            Err(e) => {
-                eprintln!("Attempt {} failed: {}. Retrying in {}ms...", attempt + 1, e, delay);
-                sleep(Duration::from_millis(delay)).await;
-                delay = std::cmp::min(
-                    (delay as f64 * config.backoff_multiplier) as u64,
-                    config.max_delay_ms,
-                );
-            }
-        }
-    }
-
-    unreachable!()
-}
+pub use crate::rate_limiter::{RateLimiter, retry_with_backoff};
 
 #[derive(Clone)]
 pub struct TranslationService {
     client: Client,
+    backend: Arc<dyn TranslationBackend>,
+    memory: Option<Arc<TranslationMemory>>,
+    glossary: Option<Arc<Glossary>>,
     rate_limiter: RateLimiter,
+    retry_config: RetryConfig,
     config: TranslationConfig,
 }
 
@@ -73,449 +26,352 @@ impl TranslationService {
     pub fn new(config: TranslationConfig) -> Self {
         // 创建兼容性更好的 HTTP 客户端
         let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(30))  // 增加超时时间
+            .timeout(std::time::Duration::from_secs(30)) // 增加超时时间
             .pool_idle_timeout(std::time::Duration::from_secs(30))
-            .pool_max_idle_per_host(5)  // 减少连接池大小
+            .pool_max_idle_per_host(5) // 减少连接池大小
             .tcp_keepalive(std::time::Duration::from_secs(60))
-            .http1_title_case_headers()  // 强制使用 HTTP/1.1
-            .http2_keep_alive_interval(None)  // 禁用 HTTP/2 keep-alive
+            .http1_title_case_headers() // 强制使用 HTTP/1.1
+            .http2_keep_alive_interval(None) // 禁用 HTTP/2 keep-alive
             .user_agent("Mozilla/5.0 (compatible; MarkdownDownloader/1.0)")
             .build()
             .unwrap_or_else(|e| {
                 eprintln!("Failed to create optimized client: {}, using default", e);
                 Client::new()
             });
-            
+
         Self {
+            backend: create_backend(client.clone(), &config),
             client,
+            memory: None,
+            glossary: None,
             rate_limiter: RateLimiter::new(config.max_requests_per_second),
+            retry_config: RetryConfig::default(),
             config,
         }
     }
 
+    pub fn with_memory(mut self, memory: Arc<TranslationMemory>) -> Self {
+        self.memory = Some(memory);
+        self
+    }
+
+    // 与转换器共用的限流器，两者的请求合计受同一速率限制
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
+    pub fn with_glossary(mut self, glossary: Arc<Glossary>) -> Self {
+        self.glossary = Some(glossary);
+        self
+    }
+
+    // 单个翻译请求的重试策略，对应 [retry.translate]
+    pub fn with_retry_config(mut self, retry_config: RetryConfig) -> Self {
+        self.retry_config = retry_config;
+        self
+    }
+
+    // 翻译为另一种目标语言的服务，与当前服务共用限流器、翻译记忆和术语表
+    pub fn for_language(&self, lang: &str) -> Self {
+        let config = self.config.for_language(lang);
+        Self {
+            backend: create_backend(self.client.clone(), &config),
+            config,
+            ..self.clone()
+        }
+    }
+
+    pub fn target_lang(&self) -> &str {
+        self.config.target_lang()
+    }
+
     pub async fn translate(&self, text: &str) -> Result<String> {
+        self.translate_page("", text).await
+    }
+
+    // page 用于术语不一致报告中标明出处（URL 或文件路径）
+    pub async fn translate_page(&self, page: &str, text: &str) -> Result<String> {
         if !self.config.enabled {
             return Ok(text.to_string());
         }
 
         println!("文本总长度: {} 字符", text.len());
 
-        if text.len() <= self.config.max_text_length {
-            println!("文本较短，直接翻译");
-            return self.translate_chunk(text).await;
+        // 按 Markdown 语法树提取可翻译的文本，代码、链接地址、HTML 等用占位符保护
+        let document = MarkdownSegments::parse(text);
+        // 术语表中的术语也替换为占位符，翻译记忆按替换后的文本查找
+        let masked: Vec<MaskedText> = document
+            .segments()
+            .iter()
+            .map(|segment| match &self.glossary {
+                Some(glossary) => glossary.mask(segment.text(), self.config.target_lang()),
+                None => MaskedText::unmasked(segment.text()),
+            })
+            .collect();
+        let texts: Vec<&str> = masked.iter().map(|m| m.text()).collect();
+        if texts.is_empty() {
+            println!("没有需要翻译的文本");
+            return Ok(text.to_string());
         }
 
-        let chunks = self.split_text_into_chunks(text);
-        println!("文本较长，分为 {} 块进行翻译", chunks.len());
+        let mut translations: Vec<Option<String>> = vec![None; texts.len()];
+
+        // 先查翻译记忆，只有未命中的片段才请求翻译接口
+        if let Some(memory) = &self.memory {
+            for (index, text) in texts.iter().enumerate() {
+                translations[index] = memory
+                    .lookup(text, &self.config)
+                    .map(|cached| self.restore_terms(page, &masked[index], &cached).0);
+            }
+        }
+        let pending: Vec<usize> = (0..texts.len())
+            .filter(|&index| translations[index].is_none())
+            .collect();
+        let cached = texts.len() - pending.len();
+        if cached > 0 {
+            println!("翻译记忆命中 {} / {} 个片段", cached, texts.len());
+        }
 
-        let mut translated_chunks = Vec::new();
+        let pending_texts: Vec<&str> = pending.iter().map(|&index| texts[index]).collect();
+        let batches = if pending_texts.is_empty() {
+            Vec::new()
+        } else {
+            self.group_segments(&pending_texts)
+        };
+        println!(
+            "共 {} 个文本片段，分为 {} 块进行翻译",
+            pending_texts.len(),
+            batches.len()
+        );
+
+        // 各块并发翻译，请求速率由 RateLimiter 控制；buffered 按块的顺序返回结果。
+        // 每块单独重试。启用翻译记忆时，某块最终失败后其余块的译文已写入记忆，重新翻译页面时不会重复请求；
+        // 未启用时整页重试会重新翻译所有块
+        let mut results = stream::iter(batches.iter().enumerate())
+            .map(|(i, batch)| {
+                let batch_texts = &pending_texts[batch.clone()];
+                async move {
+                    println!("翻译第 {} 块，{} 个片段", i + 1, batch_texts.len());
+                    (i, batch.clone(), self.translate_segments(batch_texts).await)
+                }
+            })
+            .buffered(self.config.max_concurrent_requests.max(1));
+
+        let mut first_error = None;
+        while let Some((i, batch, result)) = results.next().await {
+            let translated = match result {
+                Ok(translated) => translated,
+                Err(e) => {
+                    eprintln!("第 {} 块翻译失败: {}", i + 1, e);
+                    first_error.get_or_insert(e);
+                    continue;
+                }
+            };
 
-        for (i, chunk) in chunks.iter().enumerate() {
-            println!("翻译第 {} 块，长度: {} 字符", i + 1, chunk.len());
+            for (&index, translated) in pending[batch].iter().zip(translated) {
+                let (restored, terms_complete) =
+                    self.restore_terms(page, &masked[index], &translated);
+                // 只记住占位符完整的译文，否则下次仍会重新翻译
+                if let Some(memory) = &self.memory
+                    && terms_complete
+                    && document.segments()[index].restore(&restored).is_some()
+                    && let Err(e) = memory.store(texts[index], &translated, &self.config)
+                {
+                    eprintln!("写入翻译记忆失败: {}", e);
+                }
+                translations[index] = Some(restored);
+            }
+        }
+        drop(results);
 
-            let translated_chunk = self.translate_chunk(chunk).await?;
-            translated_chunks.push(translated_chunk);
+        if let Some(e) = first_error {
+            return Err(e);
         }
 
-        Ok(translated_chunks.join("\n\n"))
+        Ok(document.rebuild(&translations))
     }
 
-    fn split_text_into_chunks(&self, text: &str) -> Vec<String> {
-        let mut chunks = Vec::new();
-        let max_length = self.config.max_text_length;
-
-        if text.len() <= max_length {
-            chunks.push(text.to_string());
-            return chunks;
+    // 把术语占位符换回固定译法；返回还原后的文本以及所有术语是否都保留了下来
+    fn restore_terms(&self, page: &str, masked: &MaskedText, translated: &str) -> (String, bool) {
+        let (restored, missing) = masked.restore(translated);
+        let complete = missing.is_empty();
+        if let Some(glossary) = &self.glossary {
+            glossary.record_issues(page, self.config.target_lang(), masked.original(), missing);
         }
+        (restored, complete)
+    }
 
-        // 识别和保护代码块
-        let protected_sections = self.identify_code_blocks(text);
-        let segments = self.split_by_code_blocks(text, &protected_sections);
+    // 把相邻片段合并为一次请求，受最大长度和最大段落数限制
+    fn group_segments(&self, texts: &[&str]) -> Vec<Range<usize>> {
+        let max_length = self.config.max_text_length;
+        let max_segments = self.config.max_paragraphs_per_request.max(1);
 
-        let mut current_chunk = String::new();
+        let mut batches = Vec::new();
+        let mut start = 0;
+        let mut length = 0;
 
-        for segment in segments {
-            if segment.is_code_block {
-                // 代码块作为完整单元处理
-                if !current_chunk.is_empty() && current_chunk.len() + segment.content.len() + 2 > max_length {
-                    // 当前块加上代码块会超长，先保存当前块
-                    chunks.push(current_chunk.clone());
-                    current_chunk.clear();
-                }
-                
-                if segment.content.len() > max_length {
-                    // 如果代码块本身超长，但仍作为整体翻译
-                    if !current_chunk.is_empty() {
-                        chunks.push(current_chunk.clone());
-                        current_chunk.clear();
-                    }
-                    chunks.push(segment.content);
-                } else {
-                    if !current_chunk.is_empty() {
-                        current_chunk.push_str("\n\n");
-                    }
-                    current_chunk.push_str(&segment.content);
-                }
+        for (index, text) in texts.iter().enumerate() {
+            let added = if index == start {
+                text.len()
             } else {
-                // 普通文本按段落分割
-                let paragraphs = self.split_text_by_empty_lines(&segment.content);
-                
-                for paragraph in paragraphs {
-                    if paragraph.trim().is_empty() {
-                        continue;
-                    }
-
-                    let potential_length = if current_chunk.is_empty() {
-                        paragraph.len()
-                    } else {
-                        current_chunk.len() + 2 + paragraph.len()
-                    };
-
-                    if potential_length <= max_length {
-                        if !current_chunk.is_empty() {
-                            current_chunk.push_str("\n\n");
-                        }
-                        current_chunk.push_str(&paragraph);
-                    } else {
-                        if !current_chunk.is_empty() {
-                            chunks.push(current_chunk.clone());
-                            current_chunk.clear();
-                        }
-
-                        if paragraph.len() > max_length {
-                            let sub_chunks = self.split_long_paragraph(&paragraph, max_length);
-                            chunks.extend(sub_chunks);
-                        } else {
-                            current_chunk = paragraph;
-                        }
-                    }
-                }
+                length + 2 + text.len()
+            };
+            if index > start && (added > max_length || index - start >= max_segments) {
+                batches.push(start..index);
+                start = index;
+                length = text.len();
+            } else {
+                length = added;
             }
         }
+        batches.push(start..texts.len());
 
-        if !current_chunk.is_empty() {
-            chunks.push(current_chunk);
-        }
+        batches
+    }
 
-        if chunks.is_empty() {
-            chunks.push(text.to_string());
+    // 多个片段以空行连接后一起翻译；译文段数对不上时逐个片段重新翻译
+    async fn translate_segments(&self, texts: &[&str]) -> Result<Vec<String>> {
+        if texts.len() == 1 {
+            return Ok(vec![self.translate_chunk(texts[0]).await?]);
         }
 
-        chunks
-    }
+        let translated = self.translate_chunk(&texts.join("\n\n")).await?;
+        let parts: Vec<String> = translated
+            .split("\n\n")
+            .map(|part| part.trim())
+            .filter(|part| !part.is_empty())
+            .map(|part| part.to_string())
+            .collect();
 
-    // 识别代码块的位置
-    fn identify_code_blocks(&self, text: &str) -> Vec<(usize, usize)> {
-        let mut code_blocks = Vec::new();
-        let mut in_code_block = false;
-        let mut current_start = 0;
-        
-        let lines: Vec<&str> = text.lines().collect();
-        let mut char_pos = 0;
-        
-        for (i, line) in lines.iter().enumerate() {
-            if line.starts_with("```") {
-                if in_code_block {
-                    // 代码块结束
-                    let end_pos = char_pos + line.len();
-                    code_blocks.push((current_start, end_pos));
-                    in_code_block = false;
-                } else {
-                    // 代码块开始
-                    current_start = char_pos;
-                    in_code_block = true;
-                }
-            }
-            char_pos += line.len() + 1; // +1 for newline
+        if parts.len() == texts.len() {
+            return Ok(parts);
         }
-        
-        // 如果有未闭合的代码块，将其延伸到文本结尾
-        if in_code_block {
-            code_blocks.push((current_start, text.len()));
+
+        eprintln!(
+            "译文段落数不匹配 ({} / {})，改为逐段翻译",
+            parts.len(),
+            texts.len()
+        );
+        let mut results = Vec::with_capacity(texts.len());
+        for text in texts {
+            results.push(self.translate_chunk(text).await?);
         }
-        
-        code_blocks
+        Ok(results)
     }
 
-    // 根据代码块分割文本
-    fn split_by_code_blocks(&self, text: &str, code_blocks: &[(usize, usize)]) -> Vec<TextSegment> {
-        let mut segments = Vec::new();
-        let mut last_end = 0;
-        
-        for &(start, end) in code_blocks {
-            // 添加代码块前的普通文本
-            if start > last_end {
-                let content = text[last_end..start].to_string();
-                if !content.trim().is_empty() {
-                    segments.push(TextSegment {
-                        content,
-                        is_code_block: false,
-                    });
-                }
-            }
-            
-            // 添加代码块
-            let content = text[start..end].to_string();
-            segments.push(TextSegment {
-                content,
-                is_code_block: true,
-            });
-            
-            last_end = end;
-        }
-        
-        // 添加最后剩余的普通文本
-        if last_end < text.len() {
-            let content = text[last_end..].to_string();
-            if !content.trim().is_empty() {
-                segments.push(TextSegment {
-                    content,
-                    is_code_block: false,
-                });
-            }
-        }
-        
-        // 如果没有代码块，整个文本作为普通文本
-        if segments.is_empty() {
-            segments.push(TextSegment {
-                content: text.to_string(),
-                is_code_block: false,
-            });
-        }
-        
-        segments
+    async fn translate_chunk(&self, text: &str) -> Result<String> {
+        println!(
+            "发送翻译请求到: {} ({})",
+            self.config.deeplx_api_url,
+            self.backend.name()
+        );
+        println!("翻译文本长度: {} 字符", text.len());
+
+        let backend = &self.backend;
+
+        retry_with_backoff(
+            || backend.translate(text),
+            &self.retry_config,
+            Some(&self.rate_limiter),
+        )
+        .await
     }
+}
 
-    // 根据空行分割文本，但更智能地选择分割点
-    fn split_text_by_empty_lines(&self, text: &str) -> Vec<String> {
-        let max_length = self.config.max_text_length;
-        
-        // 如果文本不长，直接返回
-        if text.len() <= max_length {
-            return vec![text.to_string()];
-        }
-        
-        // 按双换行符分割得到段落
-        let paragraphs: Vec<&str> = text.split("\n\n").collect();
-        let mut result = Vec::new();
-        let mut current_group = Vec::new();
-        let mut current_length = 0;
-        
-        for paragraph in paragraphs {
-            let paragraph = paragraph.trim();
-            if paragraph.is_empty() {
-                continue;
-            }
-            
-            let para_len = paragraph.len();
-            
-            // 检查加入当前段落后是否超长
-            let potential_length = if current_group.is_empty() {
-                para_len
-            } else {
-                current_length + 2 + para_len // +2 for "\n\n"
-            };
-            
-            if potential_length <= max_length {
-                // 可以加入当前组
-                current_group.push(paragraph);
-                current_length = potential_length;
-            } else {
-                // 超长了，先保存当前组
-                if !current_group.is_empty() {
-                    result.push(current_group.join("\n\n"));
-                    current_group.clear();
-                }
-                
-                // 检查单个段落是否超长
-                if para_len > max_length {
-                    // 需要进一步分割
-                    let sub_parts = self.split_long_paragraph(paragraph, max_length);
-                    result.extend(sub_parts);
-                    current_length = 0;
-                } else {
-                    // 作为新组的开始
-                    current_group.push(paragraph);
-                    current_length = para_len;
-                }
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::future::BoxFuture;
+    use std::sync::Mutex;
+
+    // 记录收到的请求，按给定函数返回译文
+    struct MockBackend {
+        requests: Mutex<Vec<String>>,
+        respond: fn(&str) -> String,
+    }
+
+    impl TranslationBackend for MockBackend {
+        fn name(&self) -> &'static str {
+            "mock"
         }
-        
-        // 处理最后一组
-        if !current_group.is_empty() {
-            result.push(current_group.join("\n\n"));
+
+        fn translate<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<String>> {
+            self.requests.lock().unwrap().push(text.to_string());
+            let translated = (self.respond)(text);
+            Box::pin(async move { Ok(translated) })
         }
-        
-        result
     }
 
-    fn split_long_paragraph(&self, paragraph: &str, max_length: usize) -> Vec<String> {
-        let mut chunks = Vec::new();
-        let mut start = 0;
+    fn service(respond: fn(&str) -> String) -> (TranslationService, Arc<MockBackend>) {
+        let backend = Arc::new(MockBackend {
+            requests: Mutex::new(Vec::new()),
+            respond,
+        });
+        let config = TranslationConfig {
+            enabled: true,
+            max_requests_per_second: 1000.0,
+            ..Default::default()
+        };
+        let mut service = TranslationService::new(config);
+        service.backend = backend.clone();
+        (service, backend)
+    }
 
-        while start < paragraph.len() {
-            let end = std::cmp::min(start + max_length, paragraph.len());
-            let mut actual_end = end;
-
-            if end < paragraph.len() {
-                for i in (start..end).rev() {
-                    let ch = paragraph.chars().nth(i).unwrap_or(' ');
-                    if ch == '.' || ch == '!' || ch == '?' || ch == '。' || ch == '！' || ch == '？' {
-                        actual_end = i + 1;
-                        break;
-                    }
-                }
+    fn requests(backend: &MockBackend) -> Vec<String> {
+        backend.requests.lock().unwrap().clone()
+    }
 
-                if actual_end == end {
-                    for i in (start..end).rev() {
-                        let ch = paragraph.chars().nth(i).unwrap_or(' ');
-                        if ch == ' ' || ch == '\n' || ch == '\t' {
-                            actual_end = i + 1;
-                            break;
-                        }
-                    }
-                }
+    #[tokio::test]
+    async fn translate_round_trip_keeps_code_and_links() {
+        let (service, backend) = service(|text| text.to_uppercase());
+        let source = "# Intro\n\nRun `cargo build` and read [the guide](https://example.com/guide).\n\n```sh\necho hello\n```\n";
+
+        let translated = service.translate(source).await.unwrap();
+
+        assert_eq!(
+            translated,
+            "# INTRO\n\nRUN `cargo build` AND READ [THE GUIDE](https://example.com/guide).\n\n```sh\necho hello\n```\n"
+        );
+        assert_eq!(
+            requests(&backend),
+            ["Intro\n\nRun ⟦0⟧ and read ⟦1⟧the guide⟦2⟧."]
+        );
+    }
 
-                if actual_end == end && end - start < max_length / 2 {
-                    actual_end = end;
-                }
-            }
+    #[tokio::test]
+    async fn batched_translation_is_split_on_blank_lines() {
+        let (service, backend) = service(|text| text.replace("paragraph", "段落"));
 
-            let chunk = paragraph[start..actual_end].trim().to_string();
-            if !chunk.is_empty() {
-                chunks.push(chunk);
-            }
+        let translated = service
+            .translate("First paragraph.\n\nSecond paragraph.\n\nThird paragraph.\n")
+            .await
+            .unwrap();
 
-            start = actual_end;
-        }
+        assert_eq!(translated, "First 段落.\n\nSecond 段落.\n\nThird 段落.\n");
+        assert_eq!(requests(&backend).len(), 1);
+    }
 
-        chunks
+    #[tokio::test]
+    async fn mismatched_batch_falls_back_to_single_segments() {
+        // 合并请求时把空行吞掉，译文段数对不上
+        let (service, backend) = service(|text| text.replace("\n\n", " ").to_uppercase());
+
+        let translated = service.translate("One.\n\nTwo.\n\nThree.\n").await.unwrap();
+
+        assert_eq!(translated, "ONE.\n\nTWO.\n\nTHREE.\n");
+        assert_eq!(
+            requests(&backend),
+            ["One.\n\nTwo.\n\nThree.", "One.", "Two.", "Three."]
+        );
     }
 
-    async fn translate_chunk(&self, text: &str) -> Result<String> {
-        println!("发送翻译请求到: {}", self.config.deeplx_api_url);
-        println!("翻译文本长度: {} 字符", text.len());
+    #[tokio::test]
+    async fn blank_line_inside_a_single_translation_is_collapsed() {
+        let (service, _backend) = service(|text| text.to_uppercase().replace(' ', "\n\n"));
 
-        let retry_config = RetryConfig::default();
-        let client = &self.client;
-        let config = &self.config;
-        let text_clone = text.to_string();
-
-        let result = retry_with_backoff(
-            || {
-                let client = client.clone();
-                let config = config.clone();
-                let text = text_clone.clone();
-
-                Box::pin(async move {
-                    let response = if config.deeplx_api_url.contains("dptrans") {
-                        println!("使用dptrans API格式请求");
-
-                        let request = DpTransRequest {
-                            text: text.clone(),
-                            source_lang: if config.source_lang == "auto" { "auto".to_string() } else { config.source_lang.clone() },
-                            target_lang: config.target_lang.clone(),
-                        };
-
-                        client
-                            .post(&config.deeplx_api_url)
-                            .header("Content-Type", "application/json")
-                            .header("Accept", "application/json, text/plain, */*")
-                            .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36")
-                            .json(&request)
-                            .send()
-                            .await
-                            .map_err(|e| {
-                                crate::error::Error::Custom(format!("DeepLX网络请求失败: {}", e))
-                            })?
-                    } else {
-                        println!("使用标准DeepLX API格式请求");
-
-                        let request = DeepLXRequest {
-                            text: text.clone(),
-                            source_lang: config.source_lang.clone(),
-                            target_lang: config.target_lang.clone(),
-                        };
-
-                        client
-                            .post(&config.deeplx_api_url)
-                            .header("Content-Type", "application/json")
-                            .header("Accept", "application/json")
-                            .json(&request)
-                            .send()
-                            .await
-                            .map_err(|e| {
-                                crate::error::Error::Custom(format!("DeepLX网络请求失败: {}", e))
-                            })?
-                    };
-
-                    let status = response.status();
-                    println!("DeepLX响应状态: {}", status);
-
-                    if response.status().is_success() {
-                        let response_text = response
-                            .text()
-                            .await
-                            .map_err(|e| crate::error::Error::Custom(format!("读取响应文本失败: {}", e)))?;
-
-                        if let Ok(result) = serde_json::from_str::<DeepLXResponse>(&response_text) {
-                            if result.code == 200 {
-                                if result.data.is_empty() {
-                                    Err(crate::error::Error::Custom("DeepLX返回了空的翻译结果".to_string()))
-                                } else {
-                                    Ok(result.data)
-                                }
-                            } else {
-                                Err(crate::error::Error::Custom(format!(
-                                    "DeepLX翻译失败，返回代码: {}",
-                                    result.code
-                                )))
-                            }
-                        } else {
-                            if response_text.trim().is_empty() {
-                                Err(crate::error::Error::Custom("API返回了空的翻译结果".to_string()))
-                            } else if response_text.starts_with("{") {
-                                if let Ok(json_value) = serde_json::from_str::<serde_json::Value>(&response_text) {
-                                    if let Some(translated) = json_value
-                                        .get("translated_text")
-                                        .or_else(|| json_value.get("result"))
-                                        .or_else(|| json_value.get("translation"))
-                                        .or_else(|| json_value.get("data"))
-                                        .and_then(|v| v.as_str())
-                                    {
-                                        Ok(translated.to_string())
-                                    } else {
-                                        Err(crate::error::Error::Custom(format!(
-                                            "无法从JSON响应中提取翻译结果: {}",
-                                            response_text
-                                        )))
-                                    }
-                                } else {
-                                    Err(crate::error::Error::Custom(format!("无法解析JSON响应: {}", response_text)))
-                                }
-                            } else {
-                                println!("假设响应是纯文本翻译结果");
-                                Ok(response_text)
-                            }
-                        }
-                    } else {
-                        let error_text = response
-                            .text()
-                            .await
-                            .unwrap_or_else(|_| "无法读取错误信息".to_string());
-                        Err(crate::error::Error::Custom(format!(
-                            "DeepLX API请求失败: {} - {}",
-                            status, error_text
-                        )))
-                    }
-                })
-            },
-            &retry_config,
-            &self.rate_limiter,
-        )
-        .await?;
+        let translated = service.translate("# Getting started\n").await.unwrap();
 
-        Ok(result)
+        assert_eq!(translated, "# GETTING STARTED\n");
     }
-}
\ No newline at end of file
+}
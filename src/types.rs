@@ -1,26 +1,251 @@
+use crate::error::Result;
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+// 翻译服务提供方，对应 [translation] 中的 backend 键
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TranslationBackendKind {
+    #[default]
+    DeepLX,
+    DpTrans,
+    LibreTranslate,
+    DeepL,
+    OpenAI,
+}
+
+impl TranslationBackendKind {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "deeplx" => Ok(TranslationBackendKind::DeepLX),
+            "dptrans" => Ok(TranslationBackendKind::DpTrans),
+            "libretranslate" => Ok(TranslationBackendKind::LibreTranslate),
+            "deepl" => Ok(TranslationBackendKind::DeepL),
+            "openai" => Ok(TranslationBackendKind::OpenAI),
+            other => Err(format!(
+                "Unknown translation backend: {} (expected one of: deeplx, dptrans, libretranslate, deepl, openai)",
+                other
+            ).into()),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TranslationBackendKind::DeepLX => "deeplx",
+            TranslationBackendKind::DpTrans => "dptrans",
+            TranslationBackendKind::LibreTranslate => "libretranslate",
+            TranslationBackendKind::DeepL => "deepl",
+            TranslationBackendKind::OpenAI => "openai",
+        }
+    }
+
+    // 未显式配置接口地址时使用的默认地址
+    pub fn default_api_url(&self) -> &'static str {
+        match self {
+            TranslationBackendKind::DeepLX | TranslationBackendKind::DpTrans => {
+                "http://localhost:1188/translate"
+            }
+            TranslationBackendKind::LibreTranslate => "https://libretranslate.com/translate",
+            TranslationBackendKind::DeepL => "https://api-free.deepl.com/v2/translate",
+            TranslationBackendKind::OpenAI => "https://api.openai.com/v1/chat/completions",
+        }
+    }
+
+    // 未配置 api_key 时读取的环境变量
+    pub fn api_key_env(&self) -> Option<&'static str> {
+        match self {
+            TranslationBackendKind::DeepLX | TranslationBackendKind::DpTrans => None,
+            TranslationBackendKind::LibreTranslate => Some("LIBRETRANSLATE_API_KEY"),
+            TranslationBackendKind::DeepL => Some("DEEPL_API_KEY"),
+            TranslationBackendKind::OpenAI => Some("OPENAI_API_KEY"),
+        }
+    }
+
+    // 没有 api_key 就无法调用的后端
+    pub fn requires_api_key(&self) -> bool {
+        matches!(self, TranslationBackendKind::DeepL)
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct TranslationConfig {
     pub enabled: bool,
+    pub backend: TranslationBackendKind,
     pub source_lang: String,
-    pub target_lang: String,
+    // 目标语言列表：页面只抓取和转换一次，再分别翻译为每种语言
+    pub target_langs: Vec<String>,
+    // 翻译接口地址；字段名沿用 DeepLX，所有后端共用
     pub deeplx_api_url: String,
+    pub api_key: Option<String>,
+    // OpenAI 兼容接口使用的模型
+    pub model: String,
+    // 是否使用输出目录下的翻译记忆
+    pub memory: bool,
+    // 术语表文件路径
+    pub glossary: Option<String>,
     pub max_requests_per_second: f64,
     pub max_text_length: usize,
     pub max_paragraphs_per_request: usize,
+    // 同一页面同时进行中的翻译请求数，请求速率仍由 max_requests_per_second 限制
+    pub max_concurrent_requests: usize,
 }
 
 impl Default for TranslationConfig {
     fn default() -> Self {
         Self {
             enabled: false,
+            backend: TranslationBackendKind::default(),
             source_lang: "auto".to_string(),
-            target_lang: "zh".to_string(),
+            target_langs: vec!["zh".to_string()],
             deeplx_api_url: "http://localhost:1188/translate".to_string(),
-            max_requests_per_second: 0.5,  // 大幅降低请求频率
-            max_text_length: 3000,  // 保持合理长度
-            max_paragraphs_per_request: 10,  // 减少段落数
+            api_key: None,
+            model: "gpt-4o-mini".to_string(),
+            memory: true,
+            glossary: None,
+            max_requests_per_second: 0.5,   // 大幅降低请求频率
+            max_text_length: 3000,          // 保持合理长度
+            max_paragraphs_per_request: 10, // 减少段落数
+            max_concurrent_requests: 4,
+        }
+    }
+}
+
+impl TranslationConfig {
+    pub fn backend_name(&self) -> &'static str {
+        self.backend.name()
+    }
+
+    // 第一个目标语言；单个翻译服务只使用这个语言
+    pub fn target_lang(&self) -> &str {
+        self.target_langs
+            .first()
+            .map(String::as_str)
+            .unwrap_or("zh")
+    }
+
+    pub fn is_multilingual(&self) -> bool {
+        self.target_langs.len() > 1
+    }
+
+    // 配置中的 api_key，未配置时读取后端对应的环境变量
+    pub fn resolved_api_key(&self) -> Option<String> {
+        self.api_key.clone().or_else(|| {
+            self.backend
+                .api_key_env()
+                .and_then(|name| std::env::var(name).ok())
+                .filter(|key| !key.is_empty())
+        })
+    }
+
+    // 影响译文的翻译设置（不含目标语言）的指纹，记录在链接状态中
+    pub fn fingerprint(&self) -> String {
+        use sha2::{Digest, Sha256};
+
+        let settings = format!(
+            "{}|{}|{}|{}|{}",
+            self.backend_name(),
+            self.source_lang.to_lowercase(),
+            self.deeplx_api_url,
+            self.model,
+            self.glossary.as_deref().unwrap_or_default()
+        );
+        format!("{:x}", Sha256::digest(settings.as_bytes()))[..16].to_string()
+    }
+
+    // 只翻译为 lang 的配置
+    pub fn for_language(&self, lang: &str) -> Self {
+        Self {
+            target_langs: vec![lang.to_string()],
+            ..self.clone()
+        }
+    }
+}
+
+pub const FRONT_MATTER_FIELDS: &[&str] = &[
+    "source_url",
+    "title",
+    "fetched_at",
+    "content_hash",
+    "file_type",
+    "source_lang",
+    "target_lang",
+    "translation_backend",
+];
+
+// 输出文件的组织方式：flat 为扁平的带时间戳文件名，mirror 按 URL 路径建立目录
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputLayout {
+    #[default]
+    Flat,
+    Mirror,
+}
+
+impl OutputLayout {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "flat" => Ok(OutputLayout::Flat),
+            "mirror" => Ok(OutputLayout::Mirror),
+            other => Err(format!(
+                "Unknown output layout: {} (expected \"flat\" or \"mirror\")",
+                other
+            )
+            .into()),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            OutputLayout::Flat => "flat",
+            OutputLayout::Mirror => "mirror",
+        }
+    }
+}
+
+// 目标文件已存在时的处理方式：直接覆盖，或先把旧文件另存为带时间戳的版本
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExistingFilePolicy {
+    #[default]
+    Overwrite,
+    Version,
+}
+
+impl ExistingFilePolicy {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "overwrite" => Ok(ExistingFilePolicy::Overwrite),
+            "version" => Ok(ExistingFilePolicy::Version),
+            other => Err(format!(
+                "Unknown on_existing policy: {} (expected \"overwrite\" or \"version\")",
+                other
+            )
+            .into()),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExistingFilePolicy::Overwrite => "overwrite",
+            ExistingFilePolicy::Version => "version",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OutputConfig {
+    pub front_matter: bool,
+    pub front_matter_fields: Vec<String>,
+    pub layout: OutputLayout,
+    pub on_existing: ExistingFilePolicy,
+    pub rewrite_links: bool,
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        Self {
+            front_matter: false,
+            front_matter_fields: FRONT_MATTER_FIELDS.iter().map(|f| f.to_string()).collect(),
+            layout: OutputLayout::default(),
+            on_existing: ExistingFilePolicy::default(),
+            rewrite_links: true,
         }
     }
 }
@@ -31,28 +256,70 @@ pub struct RetryConfig {
     pub initial_delay_ms: u64,
     pub max_delay_ms: u64,
     pub backoff_multiplier: f64,
+    // 等待时间随机浮动的比例，0.2 表示 ±20%，避免并发请求同时重试
+    pub jitter: f64,
+    // 愿意遵守的最长 Retry-After（秒），超过时请求直接失败而不是缩短等待
+    pub max_retry_after_secs: u64,
 }
 
 impl Default for RetryConfig {
     fn default() -> Self {
         Self {
-            max_retries: 2,  // 减少重试次数
-            initial_delay_ms: 300,  // 减少初始延迟
-            max_delay_ms: 3000,  // 减少最大延迟
-            backoff_multiplier: 1.5,  // 减少退避倍数
+            max_retries: 2,          // 减少重试次数
+            initial_delay_ms: 300,   // 减少初始延迟
+            max_delay_ms: 3000,      // 减少最大延迟
+            backoff_multiplier: 1.5, // 减少退避倍数
+            jitter: 0.2,
+            max_retry_after_secs: 600,
         }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct DeepLXRequest {
-    pub text: String,
-    pub source_lang: String,
-    pub target_lang: String,
+impl RetryConfig {
+    // 服务器 Retry-After 要求的等待超过这个时间时直接放弃该请求
+    pub fn max_retry_after(&self) -> Duration {
+        Duration::from_secs(self.max_retry_after_secs)
+    }
+
+    // 第 attempt 次重试前的等待时间（从 0 开始）
+    pub fn delay(&self, attempt: usize) -> Duration {
+        let base = self.initial_delay_ms as f64 * self.backoff_multiplier.powi(attempt as i32);
+        let base = base.min(self.max_delay_ms as f64);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        let factor = if jitter > 0.0 {
+            rand::random_range(1.0 - jitter..=1.0 + jitter)
+        } else {
+            1.0
+        };
+        Duration::from_millis((base * factor) as u64)
+    }
+}
+
+// 各阶段的重试策略，对应配置文件的 [retry] 段
+#[derive(Debug, Clone)]
+pub struct RetryPolicies {
+    pub crawl: RetryConfig,
+    pub convert: RetryConfig,
+    pub translate: RetryConfig,
+    pub assets: RetryConfig,
+    // 运行结束前重新处理失败页面的轮数，0 表示不重新处理
+    pub page_retries: usize,
+}
+
+impl Default for RetryPolicies {
+    fn default() -> Self {
+        Self {
+            crawl: RetryConfig::default(),
+            convert: RetryConfig::default(),
+            translate: RetryConfig::default(),
+            assets: RetryConfig::default(),
+            page_retries: 1,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct DpTransRequest {
+pub struct DeepLXRequest {
     pub text: String,
     pub source_lang: String,
     pub target_lang: String,
@@ -64,7 +331,73 @@ pub struct DeepLXResponse {
     pub data: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Deserialize)]
+pub struct DpTransResponse {
+    #[serde(alias = "translated_text", alias = "result", alias = "translation")]
+    pub data: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LibreTranslateRequest {
+    pub q: String,
+    pub source: String,
+    pub target: String,
+    pub format: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LibreTranslateResponse {
+    #[serde(rename = "translatedText")]
+    pub translated_text: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeepLRequest {
+    pub text: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_lang: Option<String>,
+    pub target_lang: String,
+    pub preserve_formatting: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeepLResponse {
+    pub translations: Vec<DeepLTranslation>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeepLTranslation {
+    #[serde(default)]
+    pub detected_source_language: Option<String>,
+    pub text: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub role: String,
+    pub content: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChatCompletionRequest {
+    pub model: String,
+    pub messages: Vec<ChatMessage>,
+    pub temperature: f32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChatCompletionResponse {
+    pub choices: Vec<ChatChoice>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChatChoice {
+    pub message: ChatMessage,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FileType {
     Original,
     Translated,
@@ -75,12 +408,50 @@ impl FileType {
     pub fn folder_name(&self) -> &'static str {
         match self {
             FileType::Original => "original",
-            FileType::Translated => "translated", 
+            FileType::Translated => "translated",
             FileType::Bilingual => "bilingual",
         }
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct PageMetadata {
+    pub source_url: String,
+    pub title: Option<String>,
+    pub fetched_at: chrono::DateTime<chrono::Utc>,
+    pub content_hash: String,
+}
+
+impl PageMetadata {
+    pub fn new(source_url: &str, markdown: &str) -> Self {
+        use sha2::{Digest, Sha256};
+
+        Self {
+            source_url: source_url.to_string(),
+            title: Self::extract_title(markdown),
+            fetched_at: chrono::Utc::now(),
+            content_hash: format!("{:x}", Sha256::digest(markdown.as_bytes())),
+        }
+    }
+
+    // r.jina.ai 的输出以 "Title: ..." 开头；本地转换以一级标题开头
+    fn extract_title(markdown: &str) -> Option<String> {
+        for line in markdown.lines().take(50) {
+            let line = line.trim();
+            let title = line
+                .strip_prefix("Title:")
+                .or_else(|| line.strip_prefix("# "))
+                .map(|title| title.trim());
+            if let Some(title) = title
+                && !title.is_empty()
+            {
+                return Some(title.to_string());
+            }
+        }
+        None
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ProcessedLink {
     pub url: String,
@@ -99,9 +470,3 @@ impl ProcessedLink {
         }
     }
 }
-
-#[derive(Debug, Clone)]
-pub struct TextSegment {
-    pub content: String,
-    pub is_code_block: bool,
-}
\ No newline at end of file
@@ -0,0 +1,187 @@
+use crate::error::Result;
+use regex::Regex;
+
+// URL 范围规则：
+// - glob 模式匹配 URL 路径，例如 "/guide/**"、"/blog/*"
+// - 正则模式匹配完整 URL，例如 "^https://docs\.example\.com/(login|signup)"
+// include 为空表示不限制；exclude 优先于 include
+#[derive(Debug, Clone, Default)]
+pub struct UrlFilterConfig {
+    pub include_patterns: Vec<String>,
+    pub exclude_patterns: Vec<String>,
+    pub include_regex: Vec<String>,
+    pub exclude_regex: Vec<String>,
+}
+
+impl UrlFilterConfig {
+    pub fn is_empty(&self) -> bool {
+        self.include_patterns.is_empty()
+            && self.exclude_patterns.is_empty()
+            && self.include_regex.is_empty()
+            && self.exclude_regex.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct UrlFilter {
+    include: Vec<Regex>,
+    exclude: Vec<Regex>,
+}
+
+impl UrlFilter {
+    pub fn new(config: &UrlFilterConfig) -> Result<Self> {
+        let include = Self::compile(&config.include_patterns, &config.include_regex)?;
+        let exclude = Self::compile(&config.exclude_patterns, &config.exclude_regex)?;
+        Ok(Self { include, exclude })
+    }
+
+    pub fn is_allowed(&self, url: &str) -> bool {
+        if self.exclude.iter().any(|re| re.is_match(url)) {
+            return false;
+        }
+        self.include.is_empty() || self.include.iter().any(|re| re.is_match(url))
+    }
+
+    pub fn filter_urls(&self, urls: Vec<String>) -> Vec<String> {
+        urls.into_iter()
+            .filter(|url| self.is_allowed(url))
+            .collect()
+    }
+
+    // 排除规则的正则源码，交给 spider 的黑名单在爬取时剪枝
+    pub fn exclude_sources(&self) -> Vec<String> {
+        self.exclude
+            .iter()
+            .map(|re| re.as_str().to_string())
+            .collect()
+    }
+
+    fn compile(globs: &[String], regexes: &[String]) -> Result<Vec<Regex>> {
+        let mut compiled = Vec::new();
+
+        for glob in globs {
+            let source = Self::glob_to_regex(glob);
+            let re = Regex::new(&source).map_err(|e| {
+                crate::error::Error::Custom(format!("Invalid glob pattern '{}': {}", glob, e))
+            })?;
+            compiled.push(re);
+        }
+
+        for pattern in regexes {
+            let re = Regex::new(pattern).map_err(|e| {
+                crate::error::Error::Custom(format!("Invalid regex pattern '{}': {}", pattern, e))
+            })?;
+            compiled.push(re);
+        }
+
+        Ok(compiled)
+    }
+
+    // 将路径 glob 转换为匹配完整 URL 的正则
+    fn glob_to_regex(glob: &str) -> String {
+        let mut source = String::from("^[a-zA-Z][a-zA-Z0-9+.-]*://[^/]+");
+        if !glob.starts_with('/') {
+            source.push('/');
+        }
+
+        // 结尾的 "/**" 同时匹配目录本身，例如 "/guide/**" 也匹配 "/guide"
+        let (glob, any_suffix) = match glob.strip_suffix("/**") {
+            Some(prefix) => (prefix, true),
+            None => (glob, false),
+        };
+
+        let mut chars = glob.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '*' => {
+                    if chars.peek() == Some(&'*') {
+                        chars.next();
+                        source.push_str(".*");
+                    } else {
+                        source.push_str("[^/]*");
+                    }
+                }
+                '?' => source.push_str("[^/]"),
+                _ => source.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+
+        if any_suffix {
+            source.push_str("(?:/.*)?");
+        }
+
+        // 忽略查询字符串和锚点
+        source.push_str("(?:[?#].*)?$");
+        source
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(include: &[&str], exclude: &[&str]) -> UrlFilter {
+        let config = UrlFilterConfig {
+            include_patterns: include.iter().map(|p| p.to_string()).collect(),
+            exclude_patterns: exclude.iter().map(|p| p.to_string()).collect(),
+            ..Default::default()
+        };
+        UrlFilter::new(&config).unwrap()
+    }
+
+    #[test]
+    fn single_star_matches_one_segment() {
+        let filter = filter(&["/blog/*"], &[]);
+        assert!(filter.is_allowed("https://example.com/blog/post"));
+        assert!(!filter.is_allowed("https://example.com/blog/2024/post"));
+        assert!(!filter.is_allowed("https://example.com/news/post"));
+    }
+
+    #[test]
+    fn double_star_matches_nested_paths_and_bare_prefix() {
+        let filter = filter(&["/guide/**"], &[]);
+        assert!(filter.is_allowed("https://example.com/guide"));
+        assert!(filter.is_allowed("https://example.com/guide/"));
+        assert!(filter.is_allowed("https://example.com/guide/intro/setup"));
+        assert!(!filter.is_allowed("https://example.com/guidelines"));
+    }
+
+    #[test]
+    fn double_star_in_the_middle() {
+        let filter = filter(&["/docs/**/api"], &[]);
+        assert!(filter.is_allowed("https://example.com/docs/v1/api"));
+        assert!(filter.is_allowed("https://example.com/docs/v1/beta/api"));
+        assert!(!filter.is_allowed("https://example.com/docs/v1/api/extra"));
+    }
+
+    #[test]
+    fn question_mark_matches_one_character() {
+        let filter = filter(&["/v?/index"], &[]);
+        assert!(filter.is_allowed("https://example.com/v1/index"));
+        assert!(!filter.is_allowed("https://example.com/v10/index"));
+        assert!(!filter.is_allowed("https://example.com/v/index"));
+    }
+
+    #[test]
+    fn query_and_fragment_are_ignored() {
+        let filter = filter(&["/blog/*"], &[]);
+        assert!(filter.is_allowed("https://example.com/blog/post?page=2"));
+        assert!(filter.is_allowed("https://example.com/blog/post#comments"));
+        assert!(filter.is_allowed("https://example.com/blog/post?page=2#comments"));
+    }
+
+    #[test]
+    fn exclude_takes_precedence_over_include() {
+        let filter = filter(&["/guide/**"], &["/guide/internal/**"]);
+        assert!(filter.is_allowed("https://example.com/guide/intro"));
+        assert!(!filter.is_allowed("https://example.com/guide/internal"));
+        assert!(!filter.is_allowed("https://example.com/guide/internal/notes"));
+    }
+
+    #[test]
+    fn empty_include_allows_everything_not_excluded() {
+        let filter = filter(&[], &["/private/**"]);
+        assert!(filter.is_allowed("https://example.com/anything"));
+        assert!(!filter.is_allowed("https://example.com/private/page"));
+    }
+}